@@ -94,6 +94,14 @@ pub trait Backend {
     /// Disables the given effect.
     fn unset_effect(&self, effect: theme::Effect);
 
+    /// Enables or disables reports of mouse motion.
+    ///
+    /// When enabled, moving the mouse without pressing any button should
+    /// send `MouseEvent::Move` events.
+    ///
+    /// Does nothing by default.
+    fn set_mouse_motion(&mut self, _enabled: bool) {}
+
     /// Returns a name to identify the backend.
     ///
     /// Mostly used for debugging.
@@ -0,0 +1,495 @@
+//! Cell buffer between the view tree and the backend.
+//!
+//! Views are not drawn straight into the backend: the [`Printer`] writes
+//! into a [`PrintBuffer`] instead, which keeps the content of each cell.
+//!
+//! When the frame is complete, [`PrintBuffer::flush`] compares it with the
+//! last frame sent, and only prints the cells that changed. Consecutive
+//! changed cells are printed together, and colors or effects are only
+//! changed when they differ from the previous cell printed.
+//!
+//! [`Printer`]: crate::Printer
+
+use crate::backend::Backend;
+use crate::event::Event;
+use crate::theme::{Color, ColorPair, Effect};
+use crate::Vec2;
+use enumset::EnumSet;
+use std::cell::RefCell;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Colors and effects of a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CellStyle {
+    colors: ColorPair,
+    effects: EnumSet<Effect>,
+}
+
+impl CellStyle {
+    fn cleared(color: Color) -> Self {
+        CellStyle {
+            colors: ColorPair {
+                front: color,
+                back: color,
+            },
+            effects: EnumSet::new(),
+        }
+    }
+}
+
+/// Content of a single cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Cell {
+    /// First character of the grapheme starting in this cell.
+    ///
+    /// `'\0'` if the cell is covered by a wide grapheme on its left.
+    first: char,
+    /// Rest of the grapheme, for the few made of several characters.
+    rest: Option<Box<str>>,
+    style: CellStyle,
+}
+
+impl Cell {
+    fn new(grapheme: &str, style: CellStyle) -> Self {
+        let mut chars = grapheme.chars();
+        let first = chars.next().unwrap_or(' ');
+        let rest = Some(chars.as_str())
+            .filter(|rest| !rest.is_empty())
+            .map(Box::from);
+        Cell { first, rest, style }
+    }
+
+    fn blank(style: CellStyle) -> Self {
+        Cell {
+            first: ' ',
+            rest: None,
+            style,
+        }
+    }
+
+    fn continuation(style: CellStyle) -> Self {
+        Cell {
+            first: '\0',
+            rest: None,
+            style,
+        }
+    }
+
+    fn is_continuation(&self) -> bool {
+        self.first == '\0'
+    }
+
+    /// Appends the grapheme starting in this cell to `text`.
+    fn push_to(&self, text: &mut String) {
+        if !self.is_continuation() {
+            text.push(self.first);
+        }
+        if let Some(ref rest) = self.rest {
+            text.push_str(rest);
+        }
+    }
+}
+
+/// Grid of cells the view tree is drawn into.
+///
+/// It implements [`Backend`], so it can be given to a [`Printer`] in place
+/// of the actual backend. It never sends events, and `refresh()` does
+/// nothing: use [`flush`](PrintBuffer::flush) to send the frame.
+///
+/// [`Printer`]: crate::Printer
+pub struct PrintBuffer {
+    size: Vec2,
+
+    // Frame being drawn.
+    cells: RefCell<Vec<Cell>>,
+
+    // Frame currently on the screen.
+    // Swapped with `cells` after each flush, to avoid a copy.
+    previous: Vec<Cell>,
+
+    // If `false`, the screen content is unknown, and the next flush will
+    // print every cell.
+    previous_valid: bool,
+
+    style: std::cell::Cell<CellStyle>,
+}
+
+impl Default for PrintBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrintBuffer {
+    /// Creates a new empty buffer.
+    ///
+    /// Call [`resize`](PrintBuffer::resize) before drawing into it.
+    pub fn new() -> Self {
+        PrintBuffer {
+            size: Vec2::zero(),
+            cells: RefCell::new(Vec::new()),
+            previous: Vec::new(),
+            previous_valid: false,
+            style: std::cell::Cell::new(CellStyle::cleared(
+                Color::TerminalDefault,
+            )),
+        }
+    }
+
+    /// Returns the size of the buffer.
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Changes the size of the buffer.
+    ///
+    /// If the size changed, the buffer is cleared, and the next flush will
+    /// print every cell.
+    pub fn resize(&mut self, size: Vec2) {
+        if size == self.size {
+            return;
+        }
+
+        let blank = Cell::blank(CellStyle::cleared(Color::TerminalDefault));
+        self.size = size;
+        self.previous = vec![blank.clone(); size.x * size.y];
+        self.previous_valid = false;
+        self.cells.replace(vec![blank; size.x * size.y]);
+    }
+
+    /// Forgets what is on the screen.
+    ///
+    /// The next flush will print every cell.
+    pub fn invalidate(&mut self) {
+        self.previous_valid = false;
+    }
+
+    /// Clears both the buffer and the backend with the given color.
+    ///
+    /// Only cells drawn with something else will be printed on next flush.
+    pub fn clear_backend(&mut self, backend: &dyn Backend, color: Color) {
+        backend.clear(color);
+        Backend::clear(self, color);
+        self.previous.clone_from_slice(&self.cells.borrow());
+        self.previous_valid = true;
+    }
+
+    /// Sends the cells that changed since the last flush to the backend.
+    ///
+    /// This does not call `Backend::refresh()`.
+    pub fn flush(&mut self, backend: &dyn Backend) {
+        let cells = self.cells.get_mut();
+        let previous = &self.previous;
+        let valid = self.previous_valid;
+        let changed = |i: usize| !valid || previous[i] != cells[i];
+
+        // Style currently active on the backend, if known.
+        let mut active: Option<CellStyle> = None;
+        let mut text = String::new();
+
+        for y in 0..self.size.y {
+            let row = y * self.size.x;
+            let mut x = 0;
+            while x < self.size.x {
+                let cell = &cells[row + x];
+                // Continuations are printed along with their grapheme.
+                if !changed(row + x) || cell.is_continuation() {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                text.clear();
+                while x < self.size.x
+                    && changed(row + x)
+                    && cells[row + x].style == cell.style
+                {
+                    cells[row + x].push_to(&mut text);
+                    x += 1;
+                }
+
+                apply_style(backend, active, cell.style);
+                active = Some(cell.style);
+                backend.print_at(Vec2::new(start, y), &text);
+            }
+        }
+
+        // Leave the backend without any effect.
+        if let Some(style) = active {
+            for effect in style.effects {
+                backend.unset_effect(effect);
+            }
+        }
+
+        // The frame sent is now the previous one. The next frame is drawn
+        // over it, so only the cells that changed are copied back.
+        std::mem::swap(cells, &mut self.previous);
+        for (cell, sent) in cells.iter_mut().zip(&self.previous) {
+            if cell != sent {
+                cell.clone_from(sent);
+            }
+        }
+        self.previous_valid = true;
+    }
+
+    /// Returns the text of row `y`.
+    #[cfg(test)]
+    pub(crate) fn row(&self, y: usize) -> String {
+        let row = y * self.size.x;
+        let mut text = String::new();
+        for cell in &self.cells.borrow()[row..row + self.size.x] {
+            cell.push_to(&mut text);
+        }
+        text
+    }
+
+    /// Returns the effects of the cell at `pos`.
+    #[cfg(test)]
+    pub(crate) fn effects_at(&self, pos: Vec2) -> EnumSet<Effect> {
+        self.cells.borrow()[pos.y * self.size.x + pos.x]
+            .style
+            .effects
+    }
+
+    /// Writes a grapheme at the given position.
+    ///
+    /// Wide graphemes partly overwritten are replaced with blank cells.
+    fn put(&self, cells: &mut [Cell], pos: Vec2, grapheme: &str) {
+        let style = self.style.get();
+        let row = pos.y * self.size.x;
+        let end = (pos.x + grapheme.width()).min(self.size.x);
+
+        // Break a wide grapheme on the left...
+        let mut x = pos.x;
+        while x > 0 && cells[row + x].is_continuation() {
+            x -= 1;
+            cells[row + x] = Cell::blank(cells[row + x].style);
+        }
+        // ... and on the right.
+        let mut x = end;
+        while x < self.size.x && cells[row + x].is_continuation() {
+            cells[row + x] = Cell::blank(cells[row + x].style);
+            x += 1;
+        }
+
+        cells[row + pos.x] = Cell::new(grapheme, style);
+        for x in pos.x + 1..end {
+            cells[row + x] = Cell::continuation(style);
+        }
+    }
+}
+
+/// Changes the backend style from `active` to `style`.
+fn apply_style(
+    backend: &dyn Backend,
+    active: Option<CellStyle>,
+    style: CellStyle,
+) {
+    if active.map(|active| active.colors) != Some(style.colors) {
+        backend.set_color(style.colors);
+    }
+
+    let effects = active.map_or_else(EnumSet::new, |active| active.effects);
+    for effect in effects - style.effects {
+        backend.unset_effect(effect);
+    }
+    for effect in style.effects - effects {
+        backend.set_effect(effect);
+    }
+}
+
+impl Backend for PrintBuffer {
+    fn poll_event(&mut self) -> Option<Event> {
+        None
+    }
+
+    fn refresh(&mut self) {}
+
+    fn has_colors(&self) -> bool {
+        true
+    }
+
+    fn screen_size(&self) -> Vec2 {
+        self.size
+    }
+
+    fn print_at(&self, pos: Vec2, text: &str) {
+        if pos.y >= self.size.y {
+            return;
+        }
+
+        let mut cells = self.cells.borrow_mut();
+        let mut x = pos.x;
+        for grapheme in text.graphemes(true) {
+            if x >= self.size.x {
+                break;
+            }
+
+            let width = grapheme.width();
+            if width > 0 {
+                self.put(&mut cells, Vec2::new(x, pos.y), grapheme);
+                x += width;
+            }
+        }
+    }
+
+    fn clear(&self, color: Color) {
+        let blank = Cell::blank(CellStyle::cleared(color));
+        for cell in self.cells.borrow_mut().iter_mut() {
+            cell.clone_from(&blank);
+        }
+    }
+
+    fn set_color(&self, colors: ColorPair) -> ColorPair {
+        let mut style = self.style.get();
+        let old = style.colors;
+        style.colors = colors;
+        self.style.set(style);
+        old
+    }
+
+    fn set_effect(&self, effect: Effect) {
+        let mut style = self.style.get();
+        style.effects.insert(effect);
+        self.style.set(style);
+    }
+
+    fn unset_effect(&self, effect: Effect) {
+        let mut style = self.style.get();
+        style.effects.remove(effect);
+        self.style.set(style);
+    }
+
+    fn name(&self) -> &str {
+        "buffer"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::BaseColor;
+
+    /// Backend logging print and style calls.
+    #[derive(Default)]
+    struct Log(RefCell<Vec<String>>);
+
+    impl Log {
+        fn take(&self) -> Vec<String> {
+            self.0.replace(Vec::new())
+        }
+    }
+
+    impl Backend for Log {
+        fn poll_event(&mut self) -> Option<Event> {
+            None
+        }
+
+        fn refresh(&mut self) {}
+
+        fn has_colors(&self) -> bool {
+            true
+        }
+
+        fn screen_size(&self) -> Vec2 {
+            Vec2::new(6, 2)
+        }
+
+        fn print_at(&self, pos: Vec2, text: &str) {
+            self.0
+                .borrow_mut()
+                .push(format!("print {} {} {}", pos.x, pos.y, text));
+        }
+
+        fn clear(&self, _: Color) {
+            self.0.borrow_mut().push(String::from("clear"));
+        }
+
+        fn set_color(&self, colors: ColorPair) -> ColorPair {
+            self.0
+                .borrow_mut()
+                .push(format!("color {:?}", colors.front));
+            colors
+        }
+
+        fn set_effect(&self, effect: Effect) {
+            self.0.borrow_mut().push(format!("set {:?}", effect));
+        }
+
+        fn unset_effect(&self, effect: Effect) {
+            self.0.borrow_mut().push(format!("unset {:?}", effect));
+        }
+    }
+
+    #[test]
+    fn only_changes_are_printed() {
+        let log = Log::default();
+        let mut buffer = PrintBuffer::new();
+        buffer.resize(Vec2::new(6, 2));
+        buffer.clear_backend(&log, Color::TerminalDefault);
+        buffer.print_at(Vec2::new(0, 0), "abc");
+        buffer.flush(&log);
+        assert_eq!(
+            log.take(),
+            vec!["clear", "color TerminalDefault", "print 0 0 abc"]
+        );
+
+        // The same frame prints nothing.
+        buffer.print_at(Vec2::new(0, 0), "abc");
+        buffer.flush(&log);
+        assert!(log.take().is_empty());
+
+        buffer.print_at(Vec2::new(0, 0), "abd");
+        buffer.set_color(ColorPair {
+            front: Color::Dark(BaseColor::Red),
+            back: Color::TerminalDefault,
+        });
+        buffer.set_effect(Effect::Bold);
+        buffer.print_at(Vec2::new(1, 1), "xy");
+        buffer.unset_effect(Effect::Bold);
+        buffer.print_at(Vec2::new(3, 1), "z");
+        assert!(buffer.effects_at(Vec2::new(2, 1)).contains(Effect::Bold));
+        buffer.flush(&log);
+        assert_eq!(
+            log.take(),
+            vec![
+                "color TerminalDefault",
+                "print 2 0 d",
+                "color Dark(Red)",
+                "set Bold",
+                "print 1 1 xy",
+                "unset Bold",
+                "print 3 1 z",
+            ]
+        );
+
+        // After the swap, the next frame is still drawn over this one.
+        assert_eq!(buffer.row(0), "abd   ");
+        assert_eq!(buffer.row(1), " xyz  ");
+        assert!(buffer.effects_at(Vec2::new(2, 1)).contains(Effect::Bold));
+    }
+
+    #[test]
+    fn wide_graphemes() {
+        let log = Log::default();
+        let mut buffer = PrintBuffer::new();
+        buffer.resize(Vec2::new(6, 2));
+        buffer.clear_backend(&log, Color::TerminalDefault);
+        buffer.print_at(Vec2::new(0, 0), "日本");
+        buffer.flush(&log);
+        log.take();
+
+        // Overwriting half of a wide grapheme blanks the other half.
+        buffer.print_at(Vec2::new(1, 0), "a");
+        buffer.flush(&log);
+        assert_eq!(log.take(), vec!["color TerminalDefault", "print 0 0  a"]);
+        assert_eq!(buffer.row(0), " a本  ");
+
+        // Graphemes overflowing the buffer are dropped.
+        buffer.print_at(Vec2::new(4, 1), "abc");
+        buffer.print_at(Vec2::new(0, 2), "abc");
+        buffer.flush(&log);
+        assert_eq!(log.take(), vec!["color TerminalDefault", "print 4 1 ab"]);
+    }
+}
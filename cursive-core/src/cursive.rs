@@ -1,7 +1,9 @@
 use std::any::Any;
+use std::collections::HashMap;
 use std::num::NonZeroU32;
 #[cfg(feature = "toml")]
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::{self, Receiver, Sender};
 
@@ -9,15 +11,18 @@ use crate::{
     backend,
     cursive_run::CursiveRunner,
     direction,
-    event::{Event, EventResult},
+    event::{Callback, Event, EventResult},
+    gestures::GestureTracker,
+    keymap::{Conflict, Keymap},
     printer::Printer,
     theme,
-    view::{self, Finder, IntoBoxedView, Position, View},
+    view::{self, Finder, IntoBoxedView, KeyHelp, Position, View},
     views::{self, LayerPosition},
     Dump, Vec2,
 };
 
 static DEBUG_VIEW_NAME: &str = "_cursive_debug_view";
+static HELP_VIEW_NAME: &str = "_cursive_help_view";
 
 /// Central part of the cursive library.
 ///
@@ -36,6 +41,12 @@ pub struct Cursive {
 
     pub(crate) needs_clear: bool,
 
+    // Whether something may have changed since the last draw.
+    pub(crate) needs_redraw: bool,
+
+    // Size given to the root view on the last layout.
+    last_layout_size: Option<Vec2>,
+
     running: bool,
 
     // Handle asynchronous callbacks
@@ -47,6 +58,26 @@ pub struct Cursive {
 
     // Handle auto-refresh when no event is received.
     fps: Option<NonZeroU32>,
+
+    // Refresh rate while the terminal is unfocused, if different.
+    unfocused_fps: Option<u32>,
+
+    // Whether the terminal has focus, as reported by the backend.
+    focused: bool,
+
+    // Builds clicks and drags from mouse events.
+    gestures: GestureTracker,
+
+    // Whether the backend should report mouse motion.
+    pub(crate) mouse_motion: bool,
+
+    // Named actions, run by the keymap.
+    actions: HashMap<String, Callback>,
+    keymap: Keymap,
+
+    // Description of the keys held by a pending sequence, and when they
+    // time out.
+    pending_keys: Option<(String, Option<Instant>)>,
 }
 
 /// Identifies a screen in the cursive root.
@@ -80,24 +111,43 @@ impl Cursive {
             theme,
             root: views::OnEventView::new(views::ScreensView::single_screen(
                 views::StackView::new(),
-            )),
+            ))
+            .help_source("Global"),
             menubar: views::Menubar::new(),
             needs_clear: true,
+            needs_redraw: true,
+            last_layout_size: None,
             running: true,
             cb_source,
             cb_sink,
             fps: None,
+            unfocused_fps: None,
+            focused: true,
+            gestures: GestureTracker::new(),
+            mouse_motion: false,
             user_data: Box::new(()),
+            actions: HashMap::new(),
+            keymap: Keymap::new(),
+            pending_keys: None,
         };
         cursive.reset_default_callbacks();
 
         cursive
     }
 
-    pub(crate) fn layout(&mut self, size: Vec2) {
+    /// Lays out the view tree, unless nothing changed since last time.
+    ///
+    /// Returns `true` if the views were laid out again.
+    pub(crate) fn layout(&mut self, size: Vec2) -> bool {
         let offset = if self.menubar.autohide { 0 } else { 1 };
         let size = size.saturating_sub((0, offset));
+        if self.last_layout_size == Some(size) && !self.root.needs_relayout() {
+            return false;
+        }
+
+        self.last_layout_size = Some(size);
         self.root.layout(size);
+        true
     }
 
     pub(crate) fn draw(&mut self, size: Vec2, backend: &dyn backend::Backend) {
@@ -237,6 +287,65 @@ impl Cursive {
         }
     }
 
+    /// Returns the described key bindings currently active.
+    ///
+    /// This includes global callbacks, bindings of the focused views, and
+    /// menu items. See [`View::key_help`].
+    pub fn key_help(&self) -> Vec<KeyHelp> {
+        let mut help = Vec::new();
+        self.root.key_help(&mut help);
+        self.menubar.key_help(&mut help);
+        help
+    }
+
+    /// Shows the key bindings currently active, grouped by source.
+    ///
+    /// Only bindings with a description are listed.
+    pub fn show_help(&mut self) {
+        let help = view::format_key_help(&self.key_help());
+        self.add_layer(
+            views::Dialog::around(views::ScrollView::new(
+                views::NamedView::new(
+                    HELP_VIEW_NAME,
+                    views::TextView::new(help),
+                ),
+            ))
+            .title("Keys")
+            .dismiss_button("Close"),
+        );
+    }
+
+    /// Shows the key bindings, or hides them if they're already visible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::Cursive;
+    /// # use cursive_core::event::Key;
+    /// # let mut siv = Cursive::new();
+    /// siv.add_global_callback(Key::F1, Cursive::toggle_help);
+    /// siv.set_global_description(Key::F1, "Show or hide this help");
+    /// ```
+    pub fn toggle_help(&mut self) {
+        if let Some(pos) =
+            self.screen_mut().find_layer_from_name(HELP_VIEW_NAME)
+        {
+            self.screen_mut().remove_layer(pos);
+        } else {
+            self.show_help();
+        }
+    }
+
+    /// Sets the key showing or hiding the key bindings.
+    ///
+    /// This replaces any global callback for this key. See
+    /// [`Cursive::toggle_help`].
+    pub fn set_help_key<E: Into<Event>>(&mut self, key: E) {
+        let key = key.into();
+        self.set_global_callback(key.clone(), Cursive::toggle_help);
+        self.set_global_description(key, "Show or hide this help");
+    }
+
     /// Returns a sink for asynchronous callbacks.
     ///
     /// Returns the sender part of a channel, that allows to send
@@ -268,6 +377,7 @@ impl Cursive {
 
     /// Selects the menubar.
     pub fn select_menubar(&mut self) {
+        self.needs_redraw = true;
         self.menubar.take_focus(direction::Direction::none());
     }
 
@@ -277,6 +387,7 @@ impl Cursive {
     /// * When disabled, the menu is always visible and reserves the top row.
     pub fn set_autohide_menu(&mut self, autohide: bool) {
         self.menubar.autohide = autohide;
+        self.needs_redraw = true;
     }
 
     /// Access the menu tree used by the menubar.
@@ -326,6 +437,7 @@ impl Cursive {
     /// siv.add_global_callback(event::Key::Esc, |s| s.select_menubar());
     /// ```
     pub fn menubar(&mut self) -> &mut views::Menubar {
+        self.needs_redraw = true;
         &mut self.menubar
     }
 
@@ -385,6 +497,9 @@ impl Cursive {
     /// Note that the actual frequency is not guaranteed.
     ///
     /// Between 0 and 30. Call with `fps = 0` to disable (default value).
+    ///
+    /// Each frame sends `Event::Refresh` to the views. The screen is only
+    /// drawn again if a view consumed it, or if a view needs a new layout.
     pub fn set_fps(&mut self, fps: u32) {
         self.fps = NonZeroU32::new(fps);
     }
@@ -397,12 +512,62 @@ impl Cursive {
         self.set_fps(if autorefresh { 30 } else { 0 });
     }
 
+    /// Sets the refresh rate used while the terminal is unfocused.
+    ///
+    /// With `Some(0)`, auto-refresh stops while the terminal is unfocused.
+    /// With `None` (the default), the rate from `set_fps` is always used.
+    ///
+    /// This requires a backend reporting focus changes with
+    /// `Event::FocusGained` and `Event::FocusLost`.
+    pub fn set_unfocused_fps(&mut self, fps: Option<u32>) {
+        self.unfocused_fps = fps;
+    }
+
     /// Returns the current refresh rate, if any.
     ///
     /// Returns `None` if no auto-refresh is set. Otherwise, returns the rate
     /// in frames per second.
+    ///
+    /// While the terminal is unfocused, this is the rate given to
+    /// `set_unfocused_fps`, if any.
     pub fn fps(&self) -> Option<NonZeroU32> {
-        self.fps
+        match self.unfocused_fps {
+            Some(fps) if !self.focused => NonZeroU32::new(fps),
+            _ => self.fps,
+        }
+    }
+
+    /// Sets the maximum delay between the clicks of a double or triple
+    /// click.
+    ///
+    /// Defaults to 500ms.
+    pub fn set_click_interval(&mut self, interval: Duration) {
+        self.gestures.click_interval = interval;
+    }
+
+    /// Returns the maximum delay between the clicks of a double click.
+    pub fn click_interval(&self) -> Duration {
+        self.gestures.click_interval
+    }
+
+    /// Enables or disables `MouseEvent::Move` events.
+    ///
+    /// When enabled, views are told where the mouse is even when no button
+    /// is pressed, which lets them show hover feedback.
+    ///
+    /// Disabled by default. Not every backend supports it.
+    pub fn set_mouse_motion(&mut self, enabled: bool) {
+        self.mouse_motion = enabled;
+    }
+
+    /// Returns `true` if `MouseEvent::Move` events are enabled.
+    pub fn mouse_motion(&self) -> bool {
+        self.mouse_motion
+    }
+
+    /// Returns `false` if the backend reported that the terminal lost focus.
+    pub fn is_terminal_focused(&self) -> bool {
+        self.focused
     }
 
     /// Returns a reference to the currently active screen.
@@ -412,6 +577,7 @@ impl Cursive {
 
     /// Returns a mutable reference to the currently active screen.
     pub fn screen_mut(&mut self) -> &mut views::StackView {
+        self.needs_redraw = true;
         self.root.get_inner_mut().screen_mut().unwrap()
     }
 
@@ -436,6 +602,9 @@ impl Cursive {
 
     /// Sets the active screen. Panics if no such screen exist.
     pub fn set_screen(&mut self, screen_id: ScreenId) {
+        self.needs_redraw = true;
+        // The new screen may have been laid out for another size.
+        self.last_layout_size = None;
         self.root.get_inner_mut().set_active_screen(screen_id);
     }
 
@@ -474,6 +643,7 @@ impl Cursive {
         V: View,
         F: FnOnce(&mut V) -> R,
     {
+        self.needs_redraw = true;
         self.root.call_on(sel, callback)
     }
 
@@ -595,7 +765,14 @@ impl Cursive {
 
     /// Moves the focus to the view identified by `sel`.
     pub fn focus(&mut self, sel: &view::Selector<'_>) -> Result<(), ()> {
-        self.root.focus_view(sel)
+        self.needs_redraw = true;
+        let screen = self.active_screen();
+        let result = self.root.focus_view(sel);
+        if self.active_screen() != screen {
+            // Focusing a view in another screen made it active.
+            self.last_layout_size = None;
+        }
+        result
     }
 
     /// Adds a global callback.
@@ -671,6 +848,97 @@ impl Cursive {
             .set_on_event_inner(trigger, move |_, event| cb(event));
     }
 
+    /// Adds a global callback for a sequence of keys.
+    ///
+    /// Sequences are checked before the view tree sees the keys. See
+    /// [`OnEventView::on_sequence`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::*;
+    /// # use cursive_core::event::Event;
+    /// let mut siv = Cursive::new();
+    ///
+    /// siv.add_global_sequence(
+    ///     vec![Event::CtrlChar('x'), Event::CtrlChar('c')],
+    ///     |s| s.quit(),
+    /// );
+    /// ```
+    ///
+    /// [`OnEventView::on_sequence`]: crate::views::OnEventView::on_sequence
+    pub fn add_global_sequence<I, E, F>(&mut self, keys: I, cb: F)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+        F: FnMut(&mut Cursive) + 'static,
+    {
+        self.root.set_on_sequence(keys, crate::immut1!(cb));
+    }
+
+    /// Describes what a global callback does, for the help layer.
+    ///
+    /// See [`Cursive::show_help`].
+    pub fn set_global_description<E, S>(&mut self, event: E, description: S)
+    where
+        E: Into<Event>,
+        S: Into<String>,
+    {
+        self.root.set_description(event, description);
+    }
+
+    /// Describes what a global sequence does, for the help layer.
+    pub fn set_global_sequence_description<I, E, S>(
+        &mut self,
+        keys: I,
+        description: S,
+    ) where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+        S: Into<String>,
+    {
+        self.root.set_sequence_description(keys, description);
+    }
+
+    /// Removes any global callback for the given sequence of keys.
+    pub fn clear_global_sequence<I, E>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+    {
+        self.root.clear_sequence(keys);
+    }
+
+    /// Sets how long to wait for the next key of a global sequence.
+    ///
+    /// With `None`, waits forever. Defaults to one second.
+    pub fn set_sequence_timeout(&mut self, timeout: Option<Duration>) {
+        self.root.set_sequence_timeout(timeout);
+    }
+
+    /// Returns the keys of a sequence waiting for completion, if any.
+    ///
+    /// This is a description like `"ctrl+x -"`, to show to the user.
+    pub fn pending_keys(&self) -> Option<&str> {
+        self.pending_keys.as_ref().map(|(text, _)| text.as_str())
+    }
+
+    pub(crate) fn set_pending_keys(
+        &mut self,
+        text: String,
+        deadline: Option<Instant>,
+    ) {
+        self.pending_keys = Some((text, deadline));
+    }
+
+    /// Returns `true` if pending keys timed out.
+    pub(crate) fn pending_keys_expired(&self) -> bool {
+        match self.pending_keys {
+            Some((_, Some(deadline))) => Instant::now() >= deadline,
+            _ => false,
+        }
+    }
+
     /// Sets the only global callback for the given event.
     ///
     /// Any other callback for this event will be removed.
@@ -685,6 +953,109 @@ impl Cursive {
         self.add_global_callback(event, cb);
     }
 
+    /// Registers a named action, to be bound to keys by the keymap.
+    ///
+    /// Any previous action with this name is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::*;
+    /// let mut siv = Cursive::new();
+    ///
+    /// siv.register_action("quit", |s| s.quit());
+    /// siv.keymap_mut().bind("ctrl+q", "quit").unwrap();
+    /// ```
+    pub fn register_action<S, F>(&mut self, name: S, cb: F)
+    where
+        S: Into<String>,
+        F: FnMut(&mut Cursive) + 'static,
+    {
+        self.actions.insert(name.into(), Callback::from_fn_mut(cb));
+    }
+
+    /// Removes the action with the given name.
+    pub fn clear_action(&mut self, name: &str) {
+        self.actions.remove(name);
+    }
+
+    /// Returns `true` if an action with the given name was registered.
+    pub fn has_action(&self, name: &str) -> bool {
+        self.actions.contains_key(name)
+    }
+
+    /// Runs the action with the given name.
+    ///
+    /// Returns `false` if no such action was registered.
+    pub fn run_action(&mut self, name: &str) -> bool {
+        match self.actions.get(name).cloned() {
+            Some(cb) => {
+                cb(self);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the keymap used to run actions.
+    ///
+    /// Keys are only looked up in the keymap if no view or global callback
+    /// consumed them.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// Returns the current keymap.
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Returns a mutable reference to the current keymap.
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    /// Returns the keymap bindings that can't currently be reached.
+    ///
+    /// This includes bindings shadowed by a later binding for the same key
+    /// (see [`Keymap::conflicts`]), and bindings whose key is handled by a
+    /// global callback or by a callback of the focused views.
+    pub fn keymap_conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = self.keymap.conflicts();
+        self.root.keymap_conflicts(&self.keymap, &mut conflicts);
+        conflicts
+    }
+
+    /// Loads keybindings from the given file.
+    ///
+    /// They are added to the current keymap, and shadow its bindings for
+    /// the same keys.
+    ///
+    /// Must have the `toml` feature enabled.
+    #[cfg(feature = "toml")]
+    pub fn load_keymap_file<P: AsRef<Path>>(
+        &mut self,
+        filename: P,
+    ) -> Result<(), crate::keymap::Error> {
+        crate::keymap::load_keymap_file(filename)
+            .map(|keymap| self.keymap.extend(keymap))
+    }
+
+    /// Loads keybindings from the given toml content.
+    ///
+    /// They are added to the current keymap, and shadow its bindings for
+    /// the same keys.
+    ///
+    /// Must have the `toml` feature enabled.
+    #[cfg(feature = "toml")]
+    pub fn load_keymap_toml(
+        &mut self,
+        content: &str,
+    ) -> Result<(), crate::keymap::Error> {
+        crate::keymap::load_toml(content)
+            .map(|keymap| self.keymap.extend(keymap))
+    }
+
     /// Fetches the type name of a view in the tree.
     pub fn debug_name(&mut self, name: &str) -> Option<&'static str> {
         let mut result = None;
@@ -771,7 +1142,32 @@ impl Cursive {
     /// * If the menubar is active, it will be handled the event.
     /// * The view tree will be handled the event.
     /// * If ignored, global_callbacks will be checked for this event.
+    ///
+    /// Mouse events may complete a gesture, like a double click. The gesture
+    /// is then processed after the event.
     pub fn on_event(&mut self, event: Event) {
+        // Views still waiting for keys will say so again.
+        self.pending_keys = None;
+
+        match event {
+            Event::FocusGained => self.focused = true,
+            Event::FocusLost => self.focused = false,
+            _ => (),
+        }
+
+        // An idle refresh only needs a redraw if a view used it.
+        if event != Event::Refresh {
+            self.needs_redraw = true;
+        }
+
+        let gestures = self.gestures.on_event(&event, Instant::now());
+        self.dispatch_event(event);
+        for gesture in gestures {
+            self.dispatch_event(gesture);
+        }
+    }
+
+    fn dispatch_event(&mut self, event: Event) {
         if let Event::Mouse {
             event, position, ..
         } = event
@@ -785,16 +1181,33 @@ impl Cursive {
             }
         }
 
-        if self.menubar.receive_events() {
+        if event.is_mouse_move() {
+            // Everyone wants to know where the mouse is.
+            self.menubar.on_event(event.clone()).process(self);
+            let offset = if self.menubar.autohide { 0 } else { 1 };
+            View::on_event(&mut self.root, event.relativized((0, offset)))
+                .process(self);
+        } else if self.menubar.receive_events() {
             self.menubar.on_event(event).process(self);
         } else {
             let offset = if self.menubar.autohide { 0 } else { 1 };
 
             let result =
                 View::on_event(&mut self.root, event.relativized((0, offset)));
+            if result.is_consumed() {
+                self.needs_redraw = true;
+            }
 
-            if let EventResult::Consumed(Some(cb)) = result {
-                cb(self);
+            match result {
+                EventResult::Consumed(Some(cb)) => cb(self),
+                EventResult::Consumed(None) => (),
+                EventResult::Ignored => {
+                    if let Some(action) = self.keymap.action(&event) {
+                        let action = action.to_string();
+                        // Unknown actions are ignored.
+                        self.run_action(&action);
+                    }
+                }
             }
         }
     }
@@ -935,6 +1348,7 @@ impl Cursive {
         self.root = dump.root_view;
         self.theme = dump.theme;
         self.user_data = dump.user_data;
+        self.last_layout_size = None;
         self.clear();
     }
 }
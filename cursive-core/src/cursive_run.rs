@@ -1,4 +1,6 @@
-use crate::{backend, event::Event, theme, Cursive, Vec2};
+use crate::{
+    backend, buffer::PrintBuffer, event::Event, theme, Cursive, Vec2,
+};
 use std::borrow::{Borrow, BorrowMut};
 use std::time::Duration;
 
@@ -12,10 +14,14 @@ const INPUT_POLL_DELAY_MS: u64 = 30;
 pub struct CursiveRunner<C> {
     siv: C,
     backend: Box<dyn backend::Backend>,
+    // Views are drawn here, and only changes are sent to the backend.
+    buffer: PrintBuffer,
     boring_frame_count: u32,
     // Last layer sizes of the stack view.
-    // If it changed, clear the screen.
+    // If it changed, clear the buffer.
     last_sizes: Vec<Vec2>,
+    // Whether mouse motion is currently enabled on the backend.
+    backend_mouse_motion: bool,
 }
 
 impl<C> std::ops::Deref for CursiveRunner<C>
@@ -43,8 +49,10 @@ impl<C> CursiveRunner<C> {
         CursiveRunner {
             siv,
             backend,
+            buffer: PrintBuffer::new(),
             boring_frame_count: 0,
             last_sizes: Vec::new(),
+            backend_mouse_motion: false,
         }
     }
 
@@ -63,30 +71,37 @@ impl<C> CursiveRunner<C>
 where
     C: BorrowMut<Cursive>,
 {
-    fn layout(&mut self) {
+    /// Lays out the views if needed.
+    ///
+    /// Returns `true` if they were laid out again.
+    fn layout(&mut self) -> bool {
         let size = self.screen_size();
-        self.siv.borrow_mut().layout(size);
+        self.siv.borrow_mut().layout(size)
     }
 
     fn draw(&mut self) {
+        let size = self.screen_size();
+        self.buffer.resize(size);
+
+        let background =
+            self.current_theme().palette[theme::PaletteColor::Background];
+
         let sizes = self.screen().layer_sizes();
         if self.last_sizes != sizes {
-            // TODO: Maybe we only need to clear if the _max_ size differs?
-            // Or if the positions change?
-            self.clear();
+            // Layers moved: start from a blank frame.
+            // Only the cells that really changed will be printed.
+            backend::Backend::clear(&self.buffer, background);
             self.last_sizes = sizes;
         }
 
         if self.needs_clear {
-            self.backend.clear(
-                self.current_theme().palette[theme::PaletteColor::Background],
-            );
+            self.buffer.clear_backend(&*self.backend, background);
             self.needs_clear = false;
         }
 
-        let size = self.screen_size();
-
-        self.siv.borrow_mut().draw(size, &*self.backend);
+        self.siv.borrow_mut().draw(size, &self.buffer);
+        self.buffer.flush(&*self.backend);
+        self.needs_redraw = false;
     }
 
     /// Performs the first half of `Self::step()`.
@@ -108,6 +123,11 @@ where
         // Things are boring if nothing significant happened.
         let mut boring = true;
 
+        if self.mouse_motion() != self.backend_mouse_motion {
+            self.backend_mouse_motion = self.mouse_motion();
+            self.backend.set_mouse_motion(self.backend_mouse_motion);
+        }
+
         // First, handle all available input
         while let Some(event) = self.backend.poll_event() {
             boring = false;
@@ -141,7 +161,14 @@ where
     /// [2]: Cursive::step()
     /// [3]: Cursive::process_events()
     pub fn post_events(&mut self, received_something: bool) {
-        let boring = !received_something;
+        let mut boring = !received_something;
+
+        // Give keys held by an unfinished sequence to their views.
+        if boring && self.pending_keys_expired() {
+            self.on_event(Event::Refresh);
+            boring = false;
+        }
+
         // How many times should we try if it's still boring?
         // Total duration will be INPUT_POLL_DELAY_MS * repeats
         // So effectively fps = 1000 / INPUT_POLL_DELAY_MS / repeats
@@ -169,16 +196,22 @@ where
     }
 
     /// Refresh the screen with the current view tree state.
+    ///
+    /// Views are only laid out if one of them needs it (see
+    /// [`View::needs_relayout`]) or if the screen size changed. They are
+    /// only drawn if something may have changed since the last frame: an
+    /// event was processed, a view was accessed through this `Cursive`, or a
+    /// view needed a new layout.
+    ///
+    /// [`View::needs_relayout`]: crate::View::needs_relayout
     pub fn refresh(&mut self) {
         self.boring_frame_count = 0;
 
-        // Do we need to redraw everytime?
-        // Probably, actually.
-        // TODO: Do we need to re-layout everytime?
-        self.layout();
+        let relayout = self.layout();
+        if !relayout && !self.needs_redraw && !self.needs_clear {
+            return;
+        }
 
-        // TODO: Do we need to redraw every view every time?
-        // (Is this getting repetitive? :p)
         self.draw();
         self.backend.refresh();
     }
@@ -16,8 +16,10 @@
 use crate::Cursive;
 use crate::Vec2;
 use std::any::Any;
-use std::ops::Deref;
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Deref};
 use std::rc::Rc;
+use std::str::FromStr;
 
 /// Callback is a function that can be triggered by an event.
 /// It has a mutable access to the cursive root.
@@ -406,6 +408,348 @@ impl Key {
     }
 }
 
+/// Names of non-character keys, as used by `KeyCombo`'s `Display` and
+/// `FromStr` implementations.
+const KEY_NAMES: &[(Key, &str)] = &[
+    (Key::Enter, "enter"),
+    (Key::Tab, "tab"),
+    (Key::Backspace, "backspace"),
+    (Key::Esc, "esc"),
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+    (Key::Ins, "ins"),
+    (Key::Del, "del"),
+    (Key::Home, "home"),
+    (Key::End, "end"),
+    (Key::PageUp, "pageup"),
+    (Key::PageDown, "pagedown"),
+    (Key::PauseBreak, "pausebreak"),
+    (Key::NumpadCenter, "numpadcenter"),
+    (Key::F0, "f0"),
+    (Key::F1, "f1"),
+    (Key::F2, "f2"),
+    (Key::F3, "f3"),
+    (Key::F4, "f4"),
+    (Key::F5, "f5"),
+    (Key::F6, "f6"),
+    (Key::F7, "f7"),
+    (Key::F8, "f8"),
+    (Key::F9, "f9"),
+    (Key::F10, "f10"),
+    (Key::F11, "f11"),
+    (Key::F12, "f12"),
+];
+
+/// Names of characters that would be ambiguous in a `KeyCombo` string.
+const CHAR_NAMES: &[(char, &str)] = &[(' ', "space"), ('+', "plus")];
+
+/// A set of modifier keys held during a key press.
+///
+/// Modifiers can be combined with `|`:
+///
+/// ```rust
+/// use cursive_core::event::Modifiers;
+///
+/// let mods = Modifiers::CTRL | Modifiers::SHIFT;
+/// assert!(mods.contains(Modifiers::CTRL));
+/// assert!(!mods.contains(Modifiers::ALT));
+/// ```
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// No modifier.
+    pub const NONE: Modifiers = Modifiers(0);
+    /// The Shift key.
+    pub const SHIFT: Modifiers = Modifiers(1);
+    /// The Alt key (sometimes called Meta).
+    pub const ALT: Modifiers = Modifiers(1 << 1);
+    /// The Ctrl key.
+    pub const CTRL: Modifiers = Modifiers(1 << 2);
+    /// The Super key (Windows or Command key).
+    pub const SUPER: Modifiers = Modifiers(1 << 3);
+
+    /// Names used by `Display`, in display order.
+    const NAMES: [(Modifiers, &'static str); 4] = [
+        (Modifiers::CTRL, "ctrl"),
+        (Modifiers::ALT, "alt"),
+        (Modifiers::SHIFT, "shift"),
+        (Modifiers::SUPER, "super"),
+    ];
+
+    /// Returns an empty set of modifiers.
+    pub fn empty() -> Self {
+        Modifiers::NONE
+    }
+
+    /// Returns the raw bits of this set.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Builds a set from raw bits, ignoring unknown ones.
+    pub fn from_bits_truncate(bits: u8) -> Self {
+        Modifiers(bits & 0b1111)
+    }
+
+    /// Decodes the modifier parameter of xterm-style escape sequences.
+    ///
+    /// In sequences like `ESC [ 1 ; 5 A` (Ctrl+Up), the parameter is one
+    /// plus a bitmask of Shift (1), Alt (2), Ctrl (4) and Super (8).
+    pub fn from_xterm(param: u8) -> Self {
+        Modifiers::from_bits_truncate(param.saturating_sub(1))
+    }
+
+    /// Returns `true` if no modifier is set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all modifiers in `other` are set in `self`.
+    pub fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Adds the modifiers in `other`.
+    pub fn insert(&mut self, other: Modifiers) {
+        self.0 |= other.0;
+    }
+
+    /// Removes the modifiers in `other`.
+    pub fn remove(&mut self, other: Modifiers) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, other: Modifiers) {
+        self.insert(other);
+    }
+}
+
+impl BitAnd for Modifiers {
+    type Output = Modifiers;
+
+    fn bitand(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 & other.0)
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for &(modifier, name) in &Modifiers::NAMES {
+            if self.contains(modifier) {
+                if !first {
+                    f.write_str("+")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Modifiers({})", self)
+    }
+}
+
+/// A key on the keyboard, without modifiers.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub enum KeyCode {
+    /// A character key.
+    Char(char),
+    /// A non-character key.
+    Key(Key),
+}
+
+/// A key pressed with a set of modifiers.
+///
+/// This can represent any combination, including the ones without a
+/// dedicated `Event` variant (like Ctrl+Alt+Shift+Left, or anything with
+/// Super). Convert it with `Event::from` to get the legacy variant when
+/// there is one, and `Event::Combo` otherwise.
+///
+/// Letters are kept lowercase, with an explicit Shift modifier: `'K'` is
+/// stored as Shift+k.
+///
+/// Combos can be written as strings like `"ctrl+shift+k"`, `"alt+left"` or
+/// `"f5"`. Space and `+` are named `"space"` and `"plus"`.
+///
+/// ```rust
+/// use cursive_core::event::{Event, KeyCombo};
+///
+/// let combo: KeyCombo = "ctrl+s".parse().unwrap();
+/// assert_eq!(Event::from(combo), Event::CtrlChar('s'));
+/// assert_eq!(combo.to_string(), "ctrl+s");
+/// ```
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub struct KeyCombo {
+    /// The key pressed.
+    pub code: KeyCode,
+    /// Modifiers held at the time.
+    pub modifiers: Modifiers,
+}
+
+impl KeyCombo {
+    /// Creates a new combo.
+    ///
+    /// Uppercase letters are turned into lowercase ones with Shift.
+    pub fn new<C: Into<KeyCode>>(code: C, modifiers: Modifiers) -> Self {
+        let mut modifiers = modifiers;
+        let code = match code.into() {
+            KeyCode::Char(c) => {
+                let mut lower = c.to_lowercase();
+                match (lower.next(), lower.next()) {
+                    (Some(l), None) if l != c => {
+                        modifiers.insert(Modifiers::SHIFT);
+                        KeyCode::Char(l)
+                    }
+                    _ => KeyCode::Char(c),
+                }
+            }
+            code => code,
+        };
+        KeyCombo { code, modifiers }
+    }
+}
+
+impl From<char> for KeyCode {
+    fn from(c: char) -> Self {
+        KeyCode::Char(c)
+    }
+}
+
+impl From<Key> for KeyCode {
+    fn from(k: Key) -> Self {
+        KeyCode::Key(k)
+    }
+}
+
+impl fmt::Display for KeyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            KeyCode::Char(c) => {
+                match CHAR_NAMES.iter().find(|&&(named, _)| named == c) {
+                    Some(&(_, name)) => f.write_str(name),
+                    None => write!(f, "{}", c),
+                }
+            }
+            KeyCode::Key(key) => {
+                // Every key has a name.
+                let &(_, name) =
+                    KEY_NAMES.iter().find(|&&(k, _)| k == key).unwrap();
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+/// Error returned when a key or key combo cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseKeyError {
+    /// The key name is not known, or missing.
+    UnknownKey(String),
+
+    /// The modifier name is not known.
+    UnknownModifier(String),
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseKeyError::UnknownKey(ref key) => {
+                write!(f, "unknown key: {:?}", key)
+            }
+            ParseKeyError::UnknownModifier(ref modifier) => {
+                write!(f, "unknown modifier: {:?}", modifier)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+impl FromStr for KeyCode {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, ParseKeyError> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(KeyCode::Char(c));
+        }
+
+        let lower = s.to_lowercase();
+        if let Some(&(c, _)) = CHAR_NAMES.iter().find(|&&(_, n)| n == lower) {
+            return Ok(KeyCode::Char(c));
+        }
+        KEY_NAMES
+            .iter()
+            .find(|&&(_, name)| name == lower)
+            .map(|&(key, _)| KeyCode::Key(key))
+            .ok_or_else(|| ParseKeyError::UnknownKey(s.to_string()))
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.modifiers.is_empty() {
+            write!(f, "{}+", self.modifiers)?;
+        }
+        write!(f, "{}", self.code)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, ParseKeyError> {
+        // A trailing "+" is the key itself, as in "ctrl++".
+        let (mods, key) = if s == "+" {
+            ("", s)
+        } else if let Some(mods) = s.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            match s.rfind('+') {
+                Some(i) => (&s[..i], &s[i + 1..]),
+                None => ("", s),
+            }
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        if !mods.is_empty() {
+            for name in mods.split('+') {
+                modifiers |= match name.to_lowercase().as_str() {
+                    "ctrl" | "control" => Modifiers::CTRL,
+                    "alt" | "meta" => Modifiers::ALT,
+                    "shift" => Modifiers::SHIFT,
+                    "super" => Modifiers::SUPER,
+                    _ => {
+                        return Err(ParseKeyError::UnknownModifier(
+                            name.to_string(),
+                        ))
+                    }
+                };
+            }
+        }
+
+        Ok(KeyCombo::new(key.parse::<KeyCode>()?, modifiers))
+    }
+}
+
 /// One of the buttons present on the mouse
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum MouseButton {
@@ -421,7 +765,13 @@ pub enum MouseButton {
     /// Fifth button if the mouse supports it.
     Button5,
 
-    // TODO: handle more buttons?
+    /// Additional button, numbered like terminals do.
+    ///
+    /// Buttons 6 and 7 are the horizontal wheel (see
+    /// `MouseEvent::WheelLeft`), so this starts at 8. Button 8 is usually
+    /// "back", and 9 "forward".
+    Extra(u8),
+
     #[doc(hidden)]
     Other,
 }
@@ -439,24 +789,97 @@ pub enum MouseEvent {
     WheelUp,
     /// The wheel was moved down.
     WheelDown,
+    /// The wheel was moved left.
+    ///
+    /// Backends reporting modifiers also send it for Shift+`WheelUp`.
+    WheelLeft,
+    /// The wheel was moved right.
+    ///
+    /// Backends reporting modifiers also send it for Shift+`WheelDown`.
+    WheelRight,
+
+    /// A button was pressed a second time at the same place, shortly after
+    /// the first time.
+    ///
+    /// Sent after the `Press` event.
+    DoubleClick(MouseButton),
+    /// A button was pressed a third time at the same place, shortly after a
+    /// double click.
+    ///
+    /// Sent after the `Press` event.
+    TripleClick(MouseButton),
+
+    /// The mouse started moving with a button held.
+    ///
+    /// Sent after the first `Hold` event away from the press position.
+    DragStart {
+        /// The button held.
+        button: MouseButton,
+        /// Where the button was pressed, in absolute coordinates.
+        origin: Vec2,
+    },
+    /// The mouse moved during a drag.
+    ///
+    /// Sent after each `Hold` event following a `DragStart`.
+    Drag {
+        /// The button held.
+        button: MouseButton,
+        /// Where the button was pressed, in absolute coordinates.
+        origin: Vec2,
+    },
+    /// The button was released, ending a drag.
+    ///
+    /// Sent after the `Release` event.
+    DragEnd {
+        /// The button released.
+        button: MouseButton,
+        /// Where the button was pressed, in absolute coordinates.
+        origin: Vec2,
+    },
+
+    /// The mouse moved without any button pressed.
+    ///
+    /// Only sent after enabling it with `Cursive::set_mouse_motion`.
+    ///
+    /// Unlike other events, layouts send it to all their children, not just
+    /// the focused one: each view should check the position to know if it
+    /// is hovered.
+    Move,
 }
 
 impl MouseEvent {
     /// Returns the button used by this event, if any.
     ///
-    /// Returns `None` if `self` is `WheelUp` or `WheelDown`.
+    /// Returns `None` if `self` is a wheel event.
     pub fn button(self) -> Option<MouseButton> {
         match self {
             MouseEvent::Press(btn)
             | MouseEvent::Release(btn)
-            | MouseEvent::Hold(btn) => Some(btn),
+            | MouseEvent::Hold(btn)
+            | MouseEvent::DoubleClick(btn)
+            | MouseEvent::TripleClick(btn)
+            | MouseEvent::DragStart { button: btn, .. }
+            | MouseEvent::Drag { button: btn, .. }
+            | MouseEvent::DragEnd { button: btn, .. } => Some(btn),
             _ => None,
         }
     }
 
+    /// Returns the event to send when Shift is held.
+    ///
+    /// Shift turns the vertical wheel into the horizontal one. Other events
+    /// are unchanged.
+    pub fn shifted(self) -> Self {
+        match self {
+            MouseEvent::WheelUp => MouseEvent::WheelLeft,
+            MouseEvent::WheelDown => MouseEvent::WheelRight,
+            event => event,
+        }
+    }
+
     /// Returns `true` if `self` is an event that can grab focus.
     ///
-    /// This includes `Press`, `WheelUp` and `WheelDown`.
+    /// This includes `Press` and wheel events.
     ///
     /// It does _not_ include `Release` or `Hold`.
     ///
@@ -466,7 +889,9 @@ impl MouseEvent {
         match self {
             MouseEvent::Press(_)
             | MouseEvent::WheelUp
-            | MouseEvent::WheelDown => true,
+            | MouseEvent::WheelDown
+            | MouseEvent::WheelLeft
+            | MouseEvent::WheelRight => true,
             _ => false,
         }
     }
@@ -481,7 +906,16 @@ pub enum Event {
     /// Event fired regularly when a auto-refresh is set.
     Refresh,
 
-    // TODO: have Char(modifier, char) and Key(modifier, key) enums?
+    /// The terminal gained focus.
+    ///
+    /// Only sent by backends supporting focus reporting.
+    FocusGained,
+
+    /// The terminal lost focus.
+    ///
+    /// Only sent by backends supporting focus reporting.
+    FocusLost,
+
     /// A character was entered (includes numbers, punctuation, ...).
     Char(char),
     /// A character was entered with the Ctrl key pressed.
@@ -504,6 +938,19 @@ pub enum Event {
     /// A non-character key was pressed with the Ctrl and Alt keys pressed.
     CtrlAlt(Key),
 
+    /// A key was pressed with modifiers not covered by the other variants.
+    ///
+    /// Backends never send a combo that has a dedicated variant: use
+    /// `Event::from(combo)` rather than building this variant directly.
+    Combo(KeyCombo),
+
+    /// A key was released.
+    ///
+    /// Only sent by backends using the kitty keyboard protocol with release
+    /// reports, and by crossterm on Windows. This is not a key press:
+    /// `key_combo()` returns `None`.
+    KeyRelease(KeyCombo),
+
     /// A mouse event was sent.
     Mouse {
         /// Position of the top-left corner of the view receiving this event.
@@ -514,6 +961,12 @@ pub enum Event {
         event: MouseEvent,
     },
 
+    /// Some text was pasted.
+    ///
+    /// Only sent by backends supporting bracketed paste. Otherwise, pasted
+    /// text arrives as individual `Char` and `Key` events.
+    Paste(String),
+
     // TODO: use a backend-dependent type for the unknown values?
     /// An unknown event was received.
     Unknown(Vec<u8>),
@@ -530,6 +983,33 @@ pub enum Event {
 }
 
 impl Event {
+    /// Returns the key and modifiers of a keyboard event.
+    ///
+    /// Returns `None` if `self` is not a keyboard event.
+    pub fn key_combo(&self) -> Option<KeyCombo> {
+        let (code, modifiers) = match *self {
+            Event::Char(c) => (KeyCode::Char(c), Modifiers::NONE),
+            Event::CtrlChar(c) => (KeyCode::Char(c), Modifiers::CTRL),
+            Event::AltChar(c) => (KeyCode::Char(c), Modifiers::ALT),
+            Event::Key(key) => (KeyCode::Key(key), Modifiers::NONE),
+            Event::Shift(key) => (KeyCode::Key(key), Modifiers::SHIFT),
+            Event::Alt(key) => (KeyCode::Key(key), Modifiers::ALT),
+            Event::AltShift(key) => {
+                (KeyCode::Key(key), Modifiers::ALT | Modifiers::SHIFT)
+            }
+            Event::Ctrl(key) => (KeyCode::Key(key), Modifiers::CTRL),
+            Event::CtrlShift(key) => {
+                (KeyCode::Key(key), Modifiers::CTRL | Modifiers::SHIFT)
+            }
+            Event::CtrlAlt(key) => {
+                (KeyCode::Key(key), Modifiers::CTRL | Modifiers::ALT)
+            }
+            Event::Combo(combo) => return Some(combo),
+            _ => return None,
+        };
+        Some(KeyCombo::new(code, modifiers))
+    }
+
     /// Returns the position of the mouse, if `self` is a mouse event.
     pub fn mouse_position(&self) -> Option<Vec2> {
         if let Event::Mouse { position, .. } = *self {
@@ -578,6 +1058,30 @@ impl Event {
         result.relativize(top_left);
         result
     }
+
+    /// Returns a `MouseEvent::Move` event outside of any view.
+    ///
+    /// Layouts send it to the children the mouse cannot reach, like layers
+    /// covered by another one, so they know they are no longer hovered.
+    pub fn mouse_moved_away() -> Self {
+        // No view contains a position before its offset.
+        Event::Mouse {
+            event: MouseEvent::Move,
+            position: Vec2::zero(),
+            offset: Vec2::new(1, 1),
+        }
+    }
+
+    /// Returns `true` if `self` is a `MouseEvent::Move` event.
+    pub fn is_mouse_move(&self) -> bool {
+        matches!(
+            *self,
+            Event::Mouse {
+                event: MouseEvent::Move,
+                ..
+            }
+        )
+    }
 }
 
 impl From<char> for Event {
@@ -591,3 +1095,109 @@ impl From<Key> for Event {
         Event::Key(k)
     }
 }
+
+impl From<KeyCombo> for Event {
+    /// Returns the legacy variant for `combo`, or `Event::Combo`.
+    ///
+    /// Shift on a character is folded into it: Shift+k gives `Char('K')`,
+    /// Alt+Shift+k gives `AltChar('K')`, and Shift+1 gives `Char('1')`.
+    fn from(combo: KeyCombo) -> Event {
+        let combo = KeyCombo::new(combo.code, combo.modifiers);
+        let modifiers = combo.modifiers;
+
+        // Uppercase letter, or the character itself.
+        let shifted = |c: char| {
+            let mut upper = c.to_uppercase();
+            match (upper.next(), upper.next()) {
+                (Some(u), None) => u,
+                _ => c,
+            }
+        };
+
+        match combo.code {
+            KeyCode::Char(c) => match modifiers {
+                Modifiers::NONE => Event::Char(c),
+                Modifiers::SHIFT => Event::Char(shifted(c)),
+                Modifiers::CTRL => Event::CtrlChar(c),
+                Modifiers::ALT => Event::AltChar(c),
+                _ if modifiers == Modifiers::ALT | Modifiers::SHIFT => {
+                    Event::AltChar(shifted(c))
+                }
+                _ => Event::Combo(combo),
+            },
+            KeyCode::Key(_) if modifiers.contains(Modifiers::SUPER) => {
+                Event::Combo(combo)
+            }
+            KeyCode::Key(key) => match (
+                modifiers.contains(Modifiers::CTRL),
+                modifiers.contains(Modifiers::ALT),
+                modifiers.contains(Modifiers::SHIFT),
+            ) {
+                (false, false, false) => Event::Key(key),
+                (false, false, true) => Event::Shift(key),
+                (false, true, false) => Event::Alt(key),
+                (false, true, true) => Event::AltShift(key),
+                (true, false, false) => Event::Ctrl(key),
+                (true, false, true) => Event::CtrlShift(key),
+                (true, true, false) => Event::CtrlAlt(key),
+                (true, true, true) => Event::Combo(combo),
+            },
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = ParseKeyError;
+
+    /// Parses a key combo, like `"ctrl+shift+k"`.
+    fn from_str(s: &str) -> Result<Self, ParseKeyError> {
+        s.parse::<KeyCombo>().map(Event::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_combos() {
+        let combo: KeyCombo = "ctrl+shift+k".parse().unwrap();
+        assert_eq!(combo.modifiers, Modifiers::CTRL | Modifiers::SHIFT);
+        assert_eq!(combo.to_string(), "ctrl+shift+k");
+        assert_eq!(Event::from(combo), Event::Combo(combo));
+
+        let event: Event = "Ctrl+Alt+Del".parse().unwrap();
+        assert_eq!(event, Event::CtrlAlt(Key::Del));
+        assert_eq!(event.key_combo().unwrap().to_string(), "ctrl+alt+del");
+
+        assert_eq!("K".parse(), Ok(Event::Char('K')));
+        assert_eq!("alt++".parse(), Ok(Event::AltChar('+')));
+        assert_eq!("space".parse(), Ok(Event::Char(' ')));
+        assert_eq!(
+            "ctrl+".parse::<Event>(),
+            Err(ParseKeyError::UnknownKey(String::new()))
+        );
+        assert_eq!(
+            "hyper+a".parse::<Event>(),
+            Err(ParseKeyError::UnknownModifier("hyper".into()))
+        );
+
+        // Alt and Shift on a letter give the uppercase `AltChar`.
+        let combo = KeyCombo::new('k', Modifiers::ALT | Modifiers::SHIFT);
+        assert_eq!(Event::from(combo), Event::AltChar('K'));
+        assert_eq!("alt+K".parse(), Ok(Event::AltChar('K')));
+
+        // Legacy variants survive a round trip.
+        for event in &[
+            Event::Char('K'),
+            Event::CtrlChar('s'),
+            Event::AltChar('K'),
+            Event::AltShift(Key::Left),
+            Event::Key(Key::F5),
+        ] {
+            let combo = event.key_combo().unwrap();
+            assert_eq!(&Event::from(combo), event);
+            assert_eq!(combo.to_string().parse().as_ref(), Ok(event));
+        }
+    }
+}
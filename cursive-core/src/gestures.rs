@@ -0,0 +1,173 @@
+use crate::event::{Event, MouseButton, MouseEvent};
+use crate::Vec2;
+use std::time::{Duration, Instant};
+
+/// Default maximum delay between the clicks of a double or triple click.
+const CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Builds clicks and drags from the raw mouse events sent by the backend.
+pub(crate) struct GestureTracker {
+    pub click_interval: Duration,
+
+    /// Button, position and time of the last press, and the number of
+    /// clicks it completed.
+    last_click: Option<(MouseButton, Vec2, Instant, usize)>,
+
+    /// Button held and where it was pressed, and whether it moved since.
+    drag: Option<(MouseButton, Vec2, bool)>,
+}
+
+impl GestureTracker {
+    pub fn new() -> Self {
+        GestureTracker {
+            click_interval: CLICK_INTERVAL,
+            last_click: None,
+            drag: None,
+        }
+    }
+
+    /// Returns the gestures completed by `event`, received at `now`.
+    ///
+    /// They should be processed after `event` itself.
+    pub fn on_event(&mut self, event: &Event, now: Instant) -> Vec<Event> {
+        let (event, position, offset) = match *event {
+            Event::Mouse {
+                event,
+                position,
+                offset,
+            } => (event, position, offset),
+            _ => return Vec::new(),
+        };
+        let gesture = |event| Event::Mouse {
+            event,
+            position,
+            offset,
+        };
+
+        match event {
+            MouseEvent::Press(button) => {
+                let count = match self.last_click {
+                    Some((b, p, time, count))
+                        if b == button
+                            && p == position
+                            && now.duration_since(time)
+                                < self.click_interval =>
+                    {
+                        count % 3 + 1
+                    }
+                    _ => 1,
+                };
+                self.last_click = Some((button, position, now, count));
+                self.drag = Some((button, position, false));
+
+                match count {
+                    2 => vec![gesture(MouseEvent::DoubleClick(button))],
+                    3 => vec![gesture(MouseEvent::TripleClick(button))],
+                    _ => Vec::new(),
+                }
+            }
+            MouseEvent::Hold(button) => match self.drag {
+                Some((b, origin, moved)) if b == button => {
+                    if moved {
+                        vec![gesture(MouseEvent::Drag { button, origin })]
+                    } else if position != origin {
+                        self.drag = Some((button, origin, true));
+                        // A drag is not a click.
+                        self.last_click = None;
+                        vec![gesture(MouseEvent::DragStart { button, origin })]
+                    } else {
+                        Vec::new()
+                    }
+                }
+                _ => Vec::new(),
+            },
+            MouseEvent::Release(button) => match self.drag.take() {
+                Some((b, origin, true)) if b == button => {
+                    vec![gesture(MouseEvent::DragEnd { button, origin })]
+                }
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(event: MouseEvent, x: usize) -> Event {
+        Event::Mouse {
+            offset: Vec2::zero(),
+            position: Vec2::new(x, 0),
+            event,
+        }
+    }
+
+    #[test]
+    fn clicks_and_drags() {
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+        let left = MouseButton::Left;
+        let mut click = |x, millis| {
+            let now = start + Duration::from_millis(millis);
+            let mut gestures =
+                tracker.on_event(&mouse(MouseEvent::Press(left), x), now);
+            gestures.extend(
+                tracker.on_event(&mouse(MouseEvent::Release(left), x), now),
+            );
+            gestures
+        };
+
+        assert!(click(1, 0).is_empty());
+        assert_eq!(click(1, 100), [mouse(MouseEvent::DoubleClick(left), 1)]);
+        assert_eq!(click(1, 200), [mouse(MouseEvent::TripleClick(left), 1)]);
+        assert!(click(1, 300).is_empty());
+        // Too slow, or too far.
+        assert!(click(1, 1000).is_empty());
+        assert!(click(2, 1100).is_empty());
+
+        let origin = Vec2::new(2, 0);
+        let later = start + Duration::from_secs(2);
+        let mut events = vec![
+            MouseEvent::Press(left),
+            MouseEvent::Hold(left),
+            MouseEvent::Hold(left),
+            MouseEvent::Release(left),
+        ]
+        .into_iter()
+        .zip(vec![2, 3, 4, 4])
+        .map(|(event, x)| tracker.on_event(&mouse(event, x), later));
+        assert!(events.next().unwrap().is_empty());
+        assert_eq!(
+            events.next().unwrap(),
+            [mouse(
+                MouseEvent::DragStart {
+                    button: left,
+                    origin
+                },
+                3
+            )]
+        );
+        assert_eq!(
+            events.next().unwrap(),
+            [mouse(
+                MouseEvent::Drag {
+                    button: left,
+                    origin
+                },
+                4
+            )]
+        );
+        assert_eq!(
+            events.next().unwrap(),
+            [mouse(
+                MouseEvent::DragEnd {
+                    button: left,
+                    origin
+                },
+                4
+            )]
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! Configurable keybindings.
+//!
+//! A [`Keymap`] binds keys to action names, like `"ctrl+s"` to `"save"`.
+//! Actions are registered on the root with [`Cursive::register_action`], and
+//! run when a bound key is pressed and not consumed by any view or global
+//! callback.
+//!
+//! With the `toml` feature, keymaps can be loaded from and saved to a file
+//! binding key strings to actions:
+//!
+//! ```toml
+//! "ctrl+s" = "save"
+//! "ctrl+q" = "quit"
+//! q = "quit"
+//! ```
+//!
+//! Keys are written as in [`KeyCombo`]'s `FromStr` implementation.
+//!
+//! [`Cursive::register_action`]: crate::Cursive::register_action
+//! [`KeyCombo`]: crate::event::KeyCombo
+
+use crate::event::{Event, EventTrigger, KeyCombo};
+use std::fmt;
+#[cfg(feature = "toml")]
+use std::fs::File;
+#[cfg(feature = "toml")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "toml")]
+use std::path::Path;
+
+/// A key bound to an action.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Binding {
+    /// The key triggering the action.
+    pub key: KeyCombo,
+    /// Name of the action.
+    pub action: String,
+}
+
+/// A keymap action shadowed by another binding for the same key.
+///
+/// The other binding is either a later binding in the keymap, or a view
+/// callback (like a global callback) handling the key first.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// The key bound twice.
+    pub key: KeyCombo,
+    /// Action no longer reachable with this key.
+    pub shadowed: String,
+    /// Binding actually used for this key.
+    ///
+    /// This is an action name for keymap bindings, and the description of
+    /// view callbacks (or `"callback"` if they have none).
+    pub action: String,
+    /// Help source of the view handling the key.
+    ///
+    /// `None` if the key is bound again in the keymap.
+    pub source: Option<String>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is bound to `{}`", self.key, self.action)?;
+        if let Some(ref source) = self.source {
+            write!(f, " in {}", source)?;
+        }
+        write!(f, ", shadowing `{}`", self.shadowed)
+    }
+}
+
+/// Bindings from keys to action names.
+///
+/// A key can be bound several times: the last binding wins, and the others
+/// are reported by [`Keymap::conflicts`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::event::Event;
+/// use cursive_core::keymap::Keymap;
+///
+/// let keymap = Keymap::new().binding("ctrl+s", "save").unwrap();
+/// assert_eq!(keymap.action(&Event::CtrlChar('s')), Some("save"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    /// Creates an empty keymap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `key` to `action`.
+    ///
+    /// `key` is parsed as a [`KeyCombo`], like `"ctrl+shift+k"`.
+    ///
+    /// Returns `Error::InvalidKey` if `key` is not a valid key.
+    ///
+    /// [`KeyCombo`]: crate::event::KeyCombo
+    pub fn bind<S: Into<String>>(
+        &mut self,
+        key: &str,
+        action: S,
+    ) -> Result<(), Error> {
+        let combo = key
+            .parse()
+            .map_err(|_| Error::InvalidKey(key.to_string()))?;
+        self.bind_key(combo, action);
+        Ok(())
+    }
+
+    /// Binds `key` to `action`.
+    ///
+    /// Chainable variant.
+    pub fn binding<S: Into<String>>(
+        mut self,
+        key: &str,
+        action: S,
+    ) -> Result<Self, Error> {
+        self.bind(key, action)?;
+        Ok(self)
+    }
+
+    /// Binds the given key combo to `action`.
+    pub fn bind_key<S: Into<String>>(&mut self, key: KeyCombo, action: S) {
+        self.bindings.push(Binding {
+            key,
+            action: action.into(),
+        });
+    }
+
+    /// Removes all bindings for the given key.
+    pub fn unbind(&mut self, key: KeyCombo) {
+        self.bindings.retain(|binding| binding.key != key);
+    }
+
+    /// Adds all bindings from `other`, after the ones from `self`.
+    ///
+    /// Bindings in `other` shadow the ones in `self` for the same key.
+    pub fn extend(&mut self, other: Keymap) {
+        self.bindings.extend(other.bindings);
+    }
+
+    /// Returns all bindings, in the order they were added.
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Returns the action bound to the given key, if any.
+    pub fn key_action(&self, key: KeyCombo) -> Option<&str> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|binding| binding.key == key)
+            .map(|binding| binding.action.as_str())
+    }
+
+    /// Returns the action bound to the given event, if any.
+    pub fn action(&self, event: &Event) -> Option<&str> {
+        event.key_combo().and_then(|key| self.key_action(key))
+    }
+
+    /// Returns the keys currently bound to `action`.
+    ///
+    /// Shadowed bindings are not included.
+    pub fn keys(&self, action: &str) -> Vec<KeyCombo> {
+        self.effective()
+            .filter(|binding| binding.action == action)
+            .map(|binding| binding.key)
+            .collect()
+    }
+
+    /// Returns a trigger for the keys currently bound to `action`.
+    ///
+    /// This can be given to [`OnEventView`]. Later changes to the keymap do
+    /// not affect the trigger.
+    ///
+    /// [`OnEventView`]: crate::views::OnEventView
+    pub fn trigger(&self, action: &str) -> EventTrigger {
+        let keys = self.keys(action);
+        EventTrigger::from_fn_and_tag(
+            move |event| {
+                let combo = event.key_combo();
+                matches!(combo, Some(k) if keys.contains(&k))
+            },
+            action.to_string(),
+        )
+    }
+
+    /// Returns every binding shadowed by a later one for the same key.
+    ///
+    /// Bindings shadowed by global callbacks or by the focused views are
+    /// reported by [`Cursive::keymap_conflicts`].
+    ///
+    /// [`Cursive::keymap_conflicts`]: crate::Cursive::keymap_conflicts
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .filter_map(|(i, binding)| {
+                let later = self.bindings[i + 1..]
+                    .iter()
+                    .rev()
+                    .find(|other| other.key == binding.key)?;
+                Some(Conflict {
+                    key: binding.key,
+                    shadowed: binding.action.clone(),
+                    action: later.action.clone(),
+                    source: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Iterates on the bindings that are not shadowed.
+    pub(crate) fn effective(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, binding)| {
+                if self.bindings[i + 1..].iter().any(|b| b.key == binding.key)
+                {
+                    None
+                } else {
+                    Some(binding)
+                }
+            })
+    }
+
+    /// Returns the TOML representation of this keymap.
+    ///
+    /// Shadowed bindings are not included.
+    ///
+    /// Must have the `toml` feature enabled.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> String {
+        let table: toml::value::Table = self
+            .effective()
+            .map(|binding| {
+                let action = toml::Value::String(binding.action.clone());
+                (binding.key.to_string(), action)
+            })
+            .collect();
+        toml::Value::Table(table).to_string()
+    }
+}
+
+/// Possible error returned when building or loading a keymap.
+#[derive(Debug)]
+pub enum Error {
+    /// An error occured when reading or writing the file.
+    #[cfg(feature = "toml")]
+    Io(io::Error),
+
+    /// An error occured when parsing the toml content.
+    #[cfg(feature = "toml")]
+    Parse(toml::de::Error),
+
+    /// A key could not be parsed.
+    InvalidKey(String),
+
+    /// The action for this key is not a string.
+    InvalidAction(String),
+
+    /// This key is bound several times in the same file.
+    DuplicateKey(String),
+}
+
+#[cfg(feature = "toml")]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Parse(err)
+    }
+}
+
+/// Loads a keymap from a file.
+///
+/// Must have the `toml` feature enabled.
+#[cfg(feature = "toml")]
+pub fn load_keymap_file<P: AsRef<Path>>(filename: P) -> Result<Keymap, Error> {
+    let content = {
+        let mut content = String::new();
+        let mut file = File::open(filename)?;
+        file.read_to_string(&mut content)?;
+        content
+    };
+
+    load_toml(&content)
+}
+
+/// Loads a keymap from a toml string.
+///
+/// Returns `Error::DuplicateKey` if two entries are the same key, like
+/// `"ctrl+s"` and `"Ctrl+s"`.
+///
+/// Must have the `toml` feature enabled.
+#[cfg(feature = "toml")]
+pub fn load_toml(content: &str) -> Result<Keymap, Error> {
+    let table: toml::value::Table = toml::de::from_str(content)?;
+
+    let mut keymap = Keymap::new();
+    for (key, value) in table {
+        let action = match value {
+            toml::Value::String(action) => action,
+            _ => return Err(Error::InvalidAction(key)),
+        };
+        let combo = key
+            .parse()
+            .map_err(|_| Error::InvalidKey(key.to_string()))?;
+        if keymap.key_action(combo).is_some() {
+            return Err(Error::DuplicateKey(key));
+        }
+        keymap.bind_key(combo, action);
+    }
+
+    Ok(keymap)
+}
+
+/// Saves a keymap to a file.
+///
+/// Must have the `toml` feature enabled.
+#[cfg(feature = "toml")]
+pub fn save_keymap_file<P: AsRef<Path>>(
+    keymap: &Keymap,
+    filename: P,
+) -> Result<(), Error> {
+    let mut file = File::create(filename)?;
+    file.write_all(keymap.to_toml().as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Key;
+
+    #[test]
+    fn later_bindings_shadow_earlier_ones() {
+        let mut keymap = Keymap::new()
+            .binding("ctrl+s", "save")
+            .unwrap()
+            .binding("q", "quit")
+            .unwrap();
+        assert!(keymap.conflicts().is_empty());
+
+        let mut user = Keymap::new();
+        user.bind("control+S", "search").unwrap();
+        user.bind("Ctrl+s", "save_all").unwrap();
+        user.bind("ctrl+alt+del", "quit").unwrap();
+        assert!(user.bind("ctrl+nope", "quit").is_err());
+        keymap.extend(user);
+
+        assert_eq!(keymap.action(&Event::CtrlChar('s')), Some("save_all"));
+        assert!(keymap.keys("save").is_empty());
+        assert_eq!(keymap.keys("quit").len(), 2);
+        assert!(keymap.trigger("quit").apply(&Event::CtrlAlt(Key::Del)));
+
+        let conflicts = keymap.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "`ctrl+s` is bound to `save_all`, shadowing `save`"
+        );
+    }
+
+    #[test]
+    fn views_shadow_keymap() {
+        use crate::views::{OnEventView, TextView};
+
+        let mut siv = crate::Cursive::new();
+        siv.set_keymap(
+            Keymap::new()
+                .binding("q", "quit")
+                .unwrap()
+                .binding("ctrl+s", "save")
+                .unwrap()
+                .binding("g", "top")
+                .unwrap()
+                .binding("ctrl+z", "undo")
+                .unwrap(),
+        );
+        siv.add_global_callback('q', |s| s.quit());
+        siv.add_layer(
+            OnEventView::new(TextView::new("editor"))
+                .on_event(Event::CtrlChar('s'), |_| ())
+                .description(Event::CtrlChar('s'), "Save")
+                .on_sequence(vec!['g', 'g'], |_| ())
+                .help_source("Editor"),
+        );
+
+        let conflicts: Vec<_> = siv
+            .keymap_conflicts()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            conflicts,
+            [
+                "`q` is bound to `callback` in Global, shadowing `quit`",
+                "`ctrl+s` is bound to `Save` in Editor, shadowing `save`",
+                "`g` is bound to `callback` in Editor, shadowing `top`",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn duplicate_keys_in_file() {
+        let keymap = load_toml("q = \"quit\"\n\"ctrl+s\" = \"save\"").unwrap();
+        assert_eq!(keymap.key_action("ctrl+s".parse().unwrap()), Some("save"));
+
+        let err = load_toml("\"ctrl+s\" = \"save\"\n\"Ctrl+s\" = \"search\"");
+        assert!(matches!(err, Err(Error::DuplicateKey(_))));
+    }
+}
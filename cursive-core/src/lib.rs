@@ -43,8 +43,10 @@ pub mod views;
 
 pub mod align;
 pub mod backend;
+pub mod buffer;
 pub mod direction;
 pub mod event;
+pub mod keymap;
 pub mod logger;
 pub mod menu;
 pub mod theme;
@@ -54,6 +56,7 @@ pub mod vec;
 mod cursive;
 mod cursive_run;
 mod dump;
+mod gestures;
 mod printer;
 mod rect;
 mod with;
@@ -14,6 +14,7 @@
 //! [menubar]: ../struct.Cursive.html#method.menubar
 
 use crate::event::Callback;
+use crate::view::KeyHelp;
 use crate::Cursive;
 use crate::With;
 use std::rc::Rc;
@@ -29,7 +30,9 @@ pub struct MenuTree {
 #[derive(Clone)]
 pub enum MenuItem {
     /// Actionnable button with a label.
-    Leaf(String, Callback),
+    ///
+    /// Leaves with a description are listed in the help layer.
+    Leaf(String, Callback, Option<String>),
     /// Sub-menu with a label.
     Subtree(String, Rc<MenuTree>),
     /// Delimiter without a label.
@@ -43,9 +46,8 @@ impl MenuItem {
     pub fn label(&self) -> &str {
         match *self {
             MenuItem::Delimiter => "│",
-            MenuItem::Leaf(ref label, _) | MenuItem::Subtree(ref label, _) => {
-                label
-            }
+            MenuItem::Leaf(ref label, _, _)
+            | MenuItem::Subtree(ref label, _) => label,
         }
     }
 
@@ -60,7 +62,7 @@ impl MenuItem {
     /// Returns `true` if `self` is a leaf node.
     pub fn is_leaf(&self) -> bool {
         match *self {
-            MenuItem::Leaf(_, _) => true,
+            MenuItem::Leaf(..) => true,
             _ => false,
         }
     }
@@ -73,6 +75,25 @@ impl MenuItem {
         }
     }
 
+    /// Returns the description of this leaf, if any.
+    ///
+    /// Returns `None` if `self` is not a `MenuItem::Leaf`.
+    pub fn description(&self) -> Option<&str> {
+        match *self {
+            MenuItem::Leaf(_, _, ref description) => description.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Describes what this leaf does, for the help layer.
+    ///
+    /// Does nothing if `self` is not a `MenuItem::Leaf`.
+    pub fn set_description<S: Into<String>>(&mut self, description: S) {
+        if let MenuItem::Leaf(_, _, ref mut current) = *self {
+            *current = Some(description.into());
+        }
+    }
+
     /// Return a mutable reference to the subtree, if applicable.
     ///
     /// Returns `None` if `self` is not a `MenuItem::Subtree`.
@@ -133,7 +154,7 @@ impl MenuTree {
         F: 'static + Fn(&mut Cursive),
     {
         let title = title.into();
-        self.insert(i, MenuItem::Leaf(title, Callback::from_fn(cb)));
+        self.insert(i, MenuItem::Leaf(title, Callback::from_fn(cb), None));
     }
 
     /// Adds a actionnable leaf to the end of this tree - chainable variant.
@@ -230,4 +251,114 @@ impl MenuTree {
     pub fn is_empty(&self) -> bool {
         self.children.is_empty()
     }
+
+    /// Describes what the leaf with the given label does.
+    ///
+    /// If several leaves have this label, the last one is described. Does
+    /// nothing if there is no such leaf.
+    ///
+    /// Described leaves are listed in the help layer.
+    pub fn set_description<S>(&mut self, title: &str, description: S)
+    where
+        S: Into<String>,
+    {
+        if let Some(leaf) = self.find_leaf_mut(title) {
+            leaf.set_description(description);
+        }
+    }
+
+    /// Describes what the leaf with the given label does.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::menu::MenuTree;
+    /// let menu = MenuTree::new()
+    ///     .leaf("Quit", |s| s.quit())
+    ///     .description("Quit", "Exit the application");
+    /// ```
+    pub fn description<S>(self, title: &str, description: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.with(|menu| menu.set_description(title, description))
+    }
+
+    /// Returns the description of the leaf with the given label, if any.
+    ///
+    /// If several leaves have this label, the last one is used.
+    pub fn get_description(&self, title: &str) -> Option<&str> {
+        self.children
+            .iter()
+            .rev()
+            .find(|child| child.is_leaf() && child.label() == title)
+            .and_then(MenuItem::description)
+    }
+
+    /// Looks for the last leaf with the given label.
+    fn find_leaf_mut(&mut self, title: &str) -> Option<&mut MenuItem> {
+        self.children
+            .iter_mut()
+            .rev()
+            .find(|child| child.is_leaf() && child.label() == title)
+    }
+
+    /// Lists described leaves, with their path from `parent`.
+    pub(crate) fn key_help(&self, parent: &str, help: &mut Vec<KeyHelp>) {
+        for child in &self.children {
+            let path = if parent.is_empty() {
+                child.label().to_string()
+            } else {
+                format!("{} > {}", parent, child.label())
+            };
+            match *child {
+                MenuItem::Leaf(_, _, Some(ref description)) => {
+                    help.push(KeyHelp::new(
+                        "Menu",
+                        path,
+                        description.as_str(),
+                    ));
+                }
+                MenuItem::Leaf(_, _, None) => (),
+                MenuItem::Subtree(_, ref tree) => tree.key_help(&path, help),
+                MenuItem::Delimiter => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptions_stay_on_their_leaf() {
+        let mut menu = MenuTree::new()
+            .leaf("Open", |_| ())
+            .description("Open", "Open a file")
+            .leaf("Open", |_| ())
+            .subtree(
+                "Edit",
+                MenuTree::new()
+                    .leaf("Undo", |_| ())
+                    .description("Undo", "Undo the last change"),
+            );
+
+        if let Some(MenuItem::Leaf(label, _, _)) = menu.get_mut(0) {
+            *label = String::from("Open file");
+        }
+
+        let mut help = Vec::new();
+        menu.key_help("", &mut help);
+        assert_eq!(
+            help,
+            [
+                KeyHelp::new("Menu", "Open file", "Open a file"),
+                KeyHelp::new("Menu", "Edit > Undo", "Undo the last change"),
+            ]
+        );
+        assert_eq!(menu.get_description("Open"), None);
+    }
 }
@@ -41,6 +41,12 @@
 //!   Defaults to **blue**.
 //! * **`HighlightText`**: used to print primary text when highlighted
 //!   Defaults to **white**.
+//! * **`Invalid`**: used to show invalid input, for example in an `EditView`
+//!   with a validator.
+//!   Defaults to **red**.
+//! * **`CurrentLine`**: used as background for the line with the cursor in a
+//!   `TextArea`.
+//!   Defaults to **light blue**.
 //!
 //! A [`Palette`] then maps each of these to an actual [`Color`].
 //!
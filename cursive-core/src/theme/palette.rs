@@ -165,6 +165,8 @@ impl Extend<(PaletteColor, Color)> for Palette {
 /// * `Highlight` => `Dark(Red)`
 /// * `HighlightInactive` => `Dark(Blue)`
 /// * `HighlightText` => `Dark(White)`
+/// * `Invalid` => `Dark(Red)`
+/// * `CurrentLine` => `Light(Blue)`
 impl Default for Palette {
     fn default() -> Palette {
         use self::PaletteColor::*;
@@ -184,6 +186,8 @@ impl Default for Palette {
                 Highlight => Dark(Red),
                 HighlightInactive => Dark(Blue),
                 HighlightText => Dark(White),
+                Invalid => Dark(Red),
+                CurrentLine => Light(Blue),
             },
             custom: HashMap::default(),
         }
@@ -275,6 +279,10 @@ pub enum PaletteColor {
     HighlightInactive,
     /// Color used for highlighted text
     HighlightText,
+    /// Color used for invalid input.
+    Invalid,
+    /// Color used for the background of the current line.
+    CurrentLine,
 }
 
 impl PaletteColor {
@@ -302,6 +310,8 @@ impl FromStr for PaletteColor {
             "Highlight" | "highlight" => Highlight,
             "HighlightInactive" | "highlight_inactive" => HighlightInactive,
             "HighlightText" | "highlight_text" => HighlightText,
+            "Invalid" | "invalid" => Invalid,
+            "CurrentLine" | "current_line" => CurrentLine,
             _ => return Err(()),
         })
     }
@@ -0,0 +1,167 @@
+//! In-process clipboard and kill ring.
+//!
+//! Editing views like [`EditView`] and [`TextArea`] share this register for
+//! their cut, copy and paste operations.
+//!
+//! They also share a kill ring, used by the Emacs keymap: killed text is
+//! kept apart from the clipboard, and can be yanked back later.
+//!
+//! Neither is connected to the system clipboard.
+//!
+//! By default, every view uses the same process-wide [`Clipboard`]. Views
+//! can be given their own with `EditView::set_clipboard` or
+//! `TextArea::set_clipboard`.
+//!
+//! [`EditView`]: crate::views::EditView
+//! [`TextArea`]: crate::views::TextArea
+
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of entries kept in the kill ring.
+pub const KILL_RING_SIZE: usize = 32;
+
+lazy_static! {
+    static ref GLOBAL: Clipboard = Clipboard::new();
+}
+
+/// Clipboard register and kill ring.
+///
+/// Cloning a `Clipboard` gives another handle to the same content.
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::utils::clipboard::Clipboard;
+/// use cursive_core::views::TextArea;
+///
+/// // These two views share a clipboard, separate from the global one.
+/// let clipboard = Clipboard::new();
+/// let first = TextArea::new().clipboard(clipboard.clone());
+/// let second = TextArea::new().clipboard(clipboard);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Clipboard {
+    inner: Arc<Mutex<Registers>>,
+}
+
+#[derive(Debug, Default)]
+struct Registers {
+    content: String,
+    kill_ring: VecDeque<String>,
+}
+
+impl Clipboard {
+    /// Creates a new empty clipboard.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a handle to the process-wide clipboard.
+    ///
+    /// This is the clipboard used by views by default, and by the functions
+    /// in this module.
+    pub fn global() -> Self {
+        GLOBAL.clone()
+    }
+
+    /// Returns the current content of the clipboard.
+    pub fn get_content(&self) -> String {
+        self.inner.lock().unwrap().content.clone()
+    }
+
+    /// Replaces the content of the clipboard.
+    pub fn set_content<S: Into<String>>(&self, content: S) {
+        self.inner.lock().unwrap().content = content.into();
+    }
+
+    /// Adds killed text as a new entry in the kill ring.
+    ///
+    /// The oldest entry is dropped if the ring is full.
+    pub fn kill<S: Into<String>>(&self, text: S) {
+        let ring = &mut self.inner.lock().unwrap().kill_ring;
+        ring.push_back(text.into());
+        if ring.len() > KILL_RING_SIZE {
+            ring.pop_front();
+        }
+    }
+
+    /// Appends killed text to the most recent kill ring entry.
+    ///
+    /// This is used when killing forward several times in a row.
+    pub fn kill_append(&self, text: &str) {
+        let ring = &mut self.inner.lock().unwrap().kill_ring;
+        match ring.back_mut() {
+            Some(last) => last.push_str(text),
+            None => ring.push_back(text.to_string()),
+        }
+    }
+
+    /// Prepends killed text to the most recent kill ring entry.
+    ///
+    /// This is used when killing backward several times in a row.
+    pub fn kill_prepend(&self, text: &str) {
+        let ring = &mut self.inner.lock().unwrap().kill_ring;
+        match ring.back_mut() {
+            Some(last) => last.insert_str(0, text),
+            None => ring.push_back(text.to_string()),
+        }
+    }
+
+    /// Returns the most recently killed text, if any.
+    pub fn yank(&self) -> Option<String> {
+        self.inner.lock().unwrap().kill_ring.back().cloned()
+    }
+}
+
+/// Returns the current content of the global clipboard.
+pub fn get_content() -> String {
+    GLOBAL.get_content()
+}
+
+/// Replaces the content of the global clipboard.
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::utils::clipboard;
+///
+/// clipboard::set_content("foo");
+/// assert_eq!(clipboard::get_content(), "foo");
+/// ```
+pub fn set_content<S: Into<String>>(content: S) {
+    GLOBAL.set_content(content);
+}
+
+/// Adds killed text as a new entry in the global kill ring.
+///
+/// The oldest entry is dropped if the ring is full.
+pub fn kill<S: Into<String>>(text: S) {
+    GLOBAL.kill(text);
+}
+
+/// Appends killed text to the most recent global kill ring entry.
+pub fn kill_append(text: &str) {
+    GLOBAL.kill_append(text);
+}
+
+/// Prepends killed text to the most recent global kill ring entry.
+pub fn kill_prepend(text: &str) {
+    GLOBAL.kill_prepend(text);
+}
+
+/// Returns the most recently killed text in the global kill ring, if any.
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::utils::clipboard;
+///
+/// clipboard::kill("foo");
+/// clipboard::kill_prepend("bar ");
+/// assert_eq!(clipboard::yank().as_deref(), Some("bar foo"));
+/// ```
+pub fn yank() -> Option<String> {
+    GLOBAL.yank()
+}
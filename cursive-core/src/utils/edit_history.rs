@@ -0,0 +1,407 @@
+use std::collections::VecDeque;
+
+/// Default number of undo steps kept by an [`EditHistory`].
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// A single modification of a text buffer.
+///
+/// At byte offset `position`, the text `removed` was replaced by `inserted`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    /// Byte offset where the modification happened.
+    pub position: usize,
+
+    /// Text removed from the buffer, starting at `position`.
+    pub removed: String,
+
+    /// Text inserted in the buffer, starting at `position`.
+    pub inserted: String,
+}
+
+impl TextEdit {
+    /// Creates an edit inserting `text` at `position`.
+    pub fn insertion<S: Into<String>>(position: usize, text: S) -> Self {
+        TextEdit {
+            position,
+            removed: String::new(),
+            inserted: text.into(),
+        }
+    }
+
+    /// Creates an edit removing `text` at `position`.
+    pub fn removal<S: Into<String>>(position: usize, text: S) -> Self {
+        TextEdit {
+            position,
+            removed: text.into(),
+            inserted: String::new(),
+        }
+    }
+
+    /// Returns the edit cancelling `self`.
+    pub fn inverse(&self) -> Self {
+        TextEdit {
+            position: self.position,
+            removed: self.inserted.clone(),
+            inserted: self.removed.clone(),
+        }
+    }
+
+    /// Applies this edit to the given text.
+    ///
+    /// # Panics
+    ///
+    /// If the removed range is not on char boundaries in `text`.
+    pub fn apply(&self, text: &mut String) {
+        let end = self.position + self.removed.len();
+        text.replace_range(self.position..end, &self.inserted);
+    }
+
+    /// Returns `true` if this edit only inserts text.
+    fn is_insertion(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+/// A group of edits undone or redone together.
+#[derive(Clone, Debug)]
+struct EditGroup {
+    edits: Vec<TextEdit>,
+    cursor_before: usize,
+    cursor_after: usize,
+}
+
+/// Undo/redo history for text editing views.
+///
+/// Edits are recorded with [`EditHistory::record`], and grouped together
+/// when they result from consecutive typing. Calling [`EditHistory::seal`]
+/// (for example when the cursor moves) ends the current group.
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::utils::{EditHistory, TextEdit};
+///
+/// let mut text = String::from("ab");
+/// let mut history = EditHistory::new();
+///
+/// let edit = TextEdit::insertion(2, "c");
+/// edit.apply(&mut text);
+/// history.record(edit, 2, 3, true);
+/// assert_eq!(text, "abc");
+///
+/// let (edits, cursor) = history.undo().unwrap();
+/// for edit in &edits {
+///     edit.apply(&mut text);
+/// }
+/// assert_eq!(text, "ab");
+/// assert_eq!(cursor, 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EditHistory {
+    undo_stack: VecDeque<EditGroup>,
+    redo_stack: Vec<EditGroup>,
+
+    /// Maximum number of groups in `undo_stack`.
+    depth: usize,
+
+    /// When `true`, the next edit will start a new group.
+    sealed: bool,
+
+    /// When `true`, the last group is a change that typing is added to.
+    open_change: bool,
+}
+
+new_default!(EditHistory);
+
+impl EditHistory {
+    /// Creates a new, empty history.
+    pub fn new() -> Self {
+        Self::with_depth(DEFAULT_HISTORY_DEPTH)
+    }
+
+    /// Creates a new, empty history keeping at most `depth` undo steps.
+    pub fn with_depth(depth: usize) -> Self {
+        EditHistory {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            depth,
+            sealed: true,
+            open_change: false,
+        }
+    }
+
+    /// Returns the maximum number of undo steps kept.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets the maximum number of undo steps kept.
+    ///
+    /// Oldest steps are dropped if the history is already longer.
+    ///
+    /// A depth of `0` disables the history.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.trim();
+    }
+
+    /// Records an edit that was just applied.
+    ///
+    /// `cursor_before` and `cursor_after` are the cursor positions before
+    /// and after the edit.
+    ///
+    /// If `coalesce` is `true`, and the previous edit was also a
+    /// coalescable insertion right before this one, both will be undone in a
+    /// single step.
+    ///
+    /// This clears the redo stack.
+    pub fn record(
+        &mut self,
+        edit: TextEdit,
+        cursor_before: usize,
+        cursor_after: usize,
+        coalesce: bool,
+    ) {
+        self.redo_stack.clear();
+
+        if self.depth == 0 {
+            return;
+        }
+
+        if coalesce && !self.sealed && edit.is_insertion() {
+            if let Some(group) = self.undo_stack.back_mut() {
+                let last = group.edits.last_mut().unwrap();
+                if (last.is_insertion() || self.open_change)
+                    && last.position + last.inserted.len() == edit.position
+                {
+                    last.inserted.push_str(&edit.inserted);
+                    group.cursor_after = cursor_after;
+                    self.sealed = edit.inserted.ends_with('\n');
+                    return;
+                }
+            }
+        }
+
+        self.undo_stack.push_back(EditGroup {
+            edits: vec![edit],
+            cursor_before,
+            cursor_after,
+        });
+        self.sealed = !coalesce;
+        self.open_change = false;
+        self.trim();
+    }
+
+    /// Records a change that was just applied, like vi's `cw`.
+    ///
+    /// Unlike with [`record`](#method.record), insertions right after the
+    /// change are added to it until the group is sealed, so the change and
+    /// the text typed after it are undone in a single step.
+    pub(crate) fn record_change(
+        &mut self,
+        edit: TextEdit,
+        cursor_before: usize,
+        cursor_after: usize,
+    ) {
+        self.record(edit, cursor_before, cursor_after, true);
+        self.open_change = true;
+    }
+
+    /// Ends the current group.
+    ///
+    /// The next recorded edit will not be coalesced with the previous ones.
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Pops the last undo step.
+    ///
+    /// Returns the edits to apply, in order, to revert it, as well as the
+    /// cursor position to restore.
+    ///
+    /// Returns `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<(Vec<TextEdit>, usize)> {
+        let group = self.undo_stack.pop_back()?;
+        self.sealed = true;
+
+        let edits = group.edits.iter().rev().map(TextEdit::inverse).collect();
+        let cursor = group.cursor_before;
+        self.redo_stack.push(group);
+
+        Some((edits, cursor))
+    }
+
+    /// Pops the last redo step.
+    ///
+    /// Returns the edits to apply, in order, to re-do it, as well as the
+    /// cursor position to restore.
+    ///
+    /// Returns `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<(Vec<TextEdit>, usize)> {
+        let group = self.redo_stack.pop()?;
+        self.sealed = true;
+
+        let edits = group.edits.clone();
+        let cursor = group.cursor_after;
+        self.undo_stack.push_back(group);
+
+        Some((edits, cursor))
+    }
+
+    /// Returns `true` if there is something to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Returns `true` if there is something to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Forgets every recorded edit.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.sealed = true;
+    }
+
+    fn trim(&mut self) {
+        while self.undo_stack.len() > self.depth {
+            self.undo_stack.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_str(
+        history: &mut EditHistory,
+        text: &mut String,
+        cursor: &mut usize,
+        s: &str,
+    ) {
+        for c in s.chars() {
+            let edit = TextEdit::insertion(*cursor, c.to_string());
+            edit.apply(text);
+            history.record(edit, *cursor, *cursor + c.len_utf8(), true);
+            *cursor += c.len_utf8();
+        }
+    }
+
+    fn apply(text: &mut String, step: Option<(Vec<TextEdit>, usize)>) {
+        for edit in step.unwrap().0 {
+            edit.apply(text);
+        }
+    }
+
+    #[test]
+    fn typing_is_coalesced() {
+        let mut history = EditHistory::new();
+        let mut text = String::new();
+        let mut cursor = 0;
+
+        type_str(&mut history, &mut text, &mut cursor, "hello");
+        history.seal();
+        type_str(&mut history, &mut text, &mut cursor, " world");
+        assert_eq!(text, "hello world");
+
+        apply(&mut text, history.undo());
+        assert_eq!(text, "hello");
+        apply(&mut text, history.undo());
+        assert_eq!(text, "");
+        assert!(!history.can_undo());
+
+        apply(&mut text, history.redo());
+        apply(&mut text, history.redo());
+        assert_eq!(text, "hello world");
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn typing_after_removal_is_not_coalesced() {
+        let mut history = EditHistory::new();
+        let mut text = String::from("foo bar");
+        let mut cursor = 0;
+
+        let edit = TextEdit::removal(0, "foo");
+        edit.apply(&mut text);
+        history.record(edit, 0, 0, true);
+        type_str(&mut history, &mut text, &mut cursor, "baz");
+        assert_eq!(text, "baz bar");
+
+        apply(&mut text, history.undo());
+        assert_eq!(text, " bar");
+        apply(&mut text, history.undo());
+        assert_eq!(text, "foo bar");
+    }
+
+    #[test]
+    fn typing_after_change_is_coalesced() {
+        let mut history = EditHistory::new();
+        let mut text = String::from("foo bar");
+        let mut cursor = 0;
+
+        let edit = TextEdit::removal(0, "foo");
+        edit.apply(&mut text);
+        history.record_change(edit, 0, 0);
+        type_str(&mut history, &mut text, &mut cursor, "baz");
+        history.seal();
+        type_str(&mut history, &mut text, &mut cursor, "!");
+        assert_eq!(text, "baz! bar");
+
+        apply(&mut text, history.undo());
+        assert_eq!(text, "baz bar");
+        apply(&mut text, history.undo());
+        assert_eq!(text, "foo bar");
+    }
+
+    #[test]
+    fn removal_is_not_coalesced() {
+        let mut history = EditHistory::new();
+        let mut text = String::from("abc");
+
+        for cursor in (0..3).rev() {
+            let edit = TextEdit::removal(cursor, &text[cursor..=cursor]);
+            edit.apply(&mut text);
+            history.record(edit, cursor + 1, cursor, false);
+        }
+        assert_eq!(text, "");
+
+        apply(&mut text, history.undo());
+        assert_eq!(text, "a");
+    }
+
+    #[test]
+    fn depth_is_respected() {
+        let mut history = EditHistory::with_depth(2);
+        let mut text = String::new();
+
+        for i in 0..5 {
+            let edit = TextEdit::insertion(i, "x");
+            edit.apply(&mut text);
+            history.record(edit, i, i + 1, false);
+        }
+
+        apply(&mut text, history.undo());
+        apply(&mut text, history.undo());
+        assert!(history.undo().is_none());
+        assert_eq!(text, "xxx");
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut history = EditHistory::new();
+        let mut text = String::new();
+        let mut cursor = 0;
+
+        type_str(&mut history, &mut text, &mut cursor, "ab");
+        apply(&mut text, history.undo());
+        assert!(history.can_redo());
+
+        cursor = 0;
+        type_str(&mut history, &mut text, &mut cursor, "c");
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,306 @@
+use ropey::Rope;
+use std::borrow::Cow;
+use std::cmp::min;
+use std::ops::Range;
+
+/// Options for text search.
+///
+/// By default, searches ignore case and match anywhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FindOptions {
+    /// Only match text with the same case as the query.
+    pub case_sensitive: bool,
+
+    /// Only match entire words.
+    pub whole_word: bool,
+}
+
+impl FindOptions {
+    /// Creates default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the search is case-sensitive.
+    ///
+    /// Chainable variant.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Sets whether only entire words match.
+    ///
+    /// Chainable variant.
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// Returns the end of the match of `query` starting at `start`, if any.
+    ///
+    /// `start` must be a char boundary in `text`.
+    pub(crate) fn match_at(
+        self,
+        text: &str,
+        query: &str,
+        start: usize,
+    ) -> Option<usize> {
+        if query.is_empty() {
+            return None;
+        }
+
+        let end = if self.case_sensitive {
+            if !text[start..].starts_with(query) {
+                return None;
+            }
+            start + query.len()
+        } else {
+            let mut chars = text[start..].char_indices();
+            let mut end = start;
+            for q in query.chars() {
+                let (i, c) = chars.next()?;
+                if !c.to_lowercase().eq(q.to_lowercase()) {
+                    return None;
+                }
+                end = start + i + c.len_utf8();
+            }
+            end
+        };
+
+        if self.whole_word {
+            let before = text[..start].chars().next_back();
+            let after = text[end..].chars().next();
+            if matches!(before, Some(c) if is_word_char(c))
+                || matches!(after, Some(c) if is_word_char(c))
+            {
+                return None;
+            }
+        }
+
+        Some(end)
+    }
+
+    /// Returns the first match of `query` starting at or after `from`.
+    pub(crate) fn find_forward(
+        self,
+        text: &str,
+        query: &str,
+        from: usize,
+    ) -> Option<Range<usize>> {
+        let first = query.chars().next()?;
+        let mut from = from;
+        loop {
+            let start = from
+                + if self.case_sensitive {
+                    text[from..].find(query)?
+                } else {
+                    text[from..].find(|c| self.same_char(c, first))?
+                };
+            if let Some(end) = self.match_at(text, query, start) {
+                return Some(start..end);
+            }
+            from = start + text[start..].chars().next()?.len_utf8();
+        }
+    }
+
+    /// Returns the last match of `query` starting before `before`.
+    pub(crate) fn find_backward(
+        self,
+        text: &str,
+        query: &str,
+        before: usize,
+    ) -> Option<Range<usize>> {
+        let first = query.chars().next()?;
+        let mut before = before;
+        loop {
+            let start = text[..before].rfind(|c| self.same_char(c, first))?;
+            if let Some(end) = self.match_at(text, query, start) {
+                return Some(start..end);
+            }
+            before = start;
+        }
+    }
+
+    /// Returns all the non-overlapping matches of `query` in `text`.
+    pub(crate) fn find_all(
+        self,
+        text: &str,
+        query: &str,
+    ) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while let Some(found) = self.find_forward(text, query, from) {
+            from = found.end;
+            matches.push(found);
+        }
+        matches
+    }
+
+    /// Returns the first match of `query` in `rope` starting at or after
+    /// `from`.
+    ///
+    /// The rope is searched one line at a time, and is never copied.
+    pub(crate) fn find_forward_in_rope(
+        self,
+        rope: &Rope,
+        query: &str,
+        from: usize,
+    ) -> Option<Range<usize>> {
+        if query.is_empty() {
+            return None;
+        }
+
+        let first = rope.byte_to_line(from);
+        (first..rope.len_lines()).find_map(|line| {
+            let window = Window::new(rope, query, line);
+            let from = from.saturating_sub(window.start);
+            self.find_forward(&window.text, query, from)
+                // Later matches are found from their own line.
+                .filter(|found| found.start < window.line_len)
+                .map(|found| window.offset(found))
+        })
+    }
+
+    /// Returns the last match of `query` in `rope` starting before
+    /// `before`.
+    pub(crate) fn find_backward_in_rope(
+        self,
+        rope: &Rope,
+        query: &str,
+        before: usize,
+    ) -> Option<Range<usize>> {
+        if query.is_empty() {
+            return None;
+        }
+
+        let last = rope.byte_to_line(before);
+        (0..=last).rev().find_map(|line| {
+            let window = Window::new(rope, query, line);
+            let before = min(before - window.start, window.line_len);
+            self.find_backward(&window.text, query, before)
+                .map(|found| window.offset(found))
+        })
+    }
+
+    /// Returns all the non-overlapping matches of `query` in `rope`.
+    pub(crate) fn find_all_in_rope(
+        self,
+        rope: &Rope,
+        query: &str,
+    ) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while let Some(found) = self.find_forward_in_rope(rope, query, from) {
+            from = found.end;
+            matches.push(found);
+        }
+        matches
+    }
+
+    /// Returns `true` if `range` is a match of `query` in `rope`.
+    pub(crate) fn is_match_in_rope(
+        self,
+        rope: &Rope,
+        query: &str,
+        range: Range<usize>,
+    ) -> bool {
+        let window = Window::new(rope, query, rope.byte_to_line(range.start));
+        self.match_at(&window.text, query, range.start - window.start)
+            == Some(range.end - window.start)
+    }
+
+    /// Returns `true` if `c` is the same character as `query_char`.
+    fn same_char(self, c: char, query_char: char) -> bool {
+        if self.case_sensitive {
+            c == query_char
+        } else {
+            c.to_lowercase().eq(query_char.to_lowercase())
+        }
+    }
+}
+
+/// Lines of a rope where matches starting on a given line can be found.
+struct Window<'a> {
+    /// Byte offset of the window in the rope.
+    start: usize,
+
+    /// Length of the first line, including the newline.
+    line_len: usize,
+
+    text: Cow<'a, str>,
+}
+
+impl<'a> Window<'a> {
+    fn new(rope: &'a Rope, query: &str, line: usize) -> Self {
+        // A match spans one line per newline in the query. One more line
+        // is needed to know the character after a match ending a line.
+        let lines = query.matches('\n').count()
+            + 1
+            + usize::from(query.ends_with('\n'));
+
+        let start = rope.line_to_byte(line);
+        let end = rope.line_to_byte(min(line + lines, rope.len_lines()));
+        Window {
+            start,
+            line_len: rope.line(line).len_bytes(),
+            text: rope.byte_slice(start..end).into(),
+        }
+    }
+
+    /// Returns the position of `found` in the rope.
+    fn offset(&self, found: Range<usize>) -> Range<usize> {
+        found.start + self.start..found.end + self.start
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_with_options() {
+        let text = "Foo food foo_bar FOO";
+        let options = FindOptions::new();
+        assert_eq!(options.find_all(text, "foo").len(), 4);
+        assert_eq!(options.find_backward(text, "foo", 17), Some(9..12));
+
+        let options = FindOptions::new().case_sensitive(true);
+        assert_eq!(options.find_forward(text, "foo", 0), Some(4..7));
+
+        let options = FindOptions::new().whole_word(true);
+        assert_eq!(options.find_all(text, "foo"), vec![0..3, 17..20]);
+    }
+
+    #[test]
+    fn find_in_rope() {
+        let rope = Rope::from_str("Foo bar\nfoo\nbar fOo\n");
+        let options = FindOptions::new();
+        assert_eq!(
+            options.find_all_in_rope(&rope, "foo"),
+            vec![0..3, 8..11, 16..19]
+        );
+        assert_eq!(options.find_forward_in_rope(&rope, "foo", 1), Some(8..11));
+        assert_eq!(
+            options.find_backward_in_rope(&rope, "foo", 16),
+            Some(8..11)
+        );
+        assert_eq!(
+            options.find_backward_in_rope(&rope, "foo", 20),
+            Some(16..19)
+        );
+
+        // Matches may span several lines.
+        assert_eq!(options.find_all_in_rope(&rope, "foo\nbar"), vec![8..15]);
+        assert!(options.is_match_in_rope(&rope, "bar\nfoo", 4..11));
+
+        // The start of the next line is checked for whole words.
+        let options = FindOptions::new().whole_word(true);
+        assert!(!options.is_match_in_rope(&rope, "foo\n", 8..12));
+        assert_eq!(options.find_forward_in_rope(&rope, "o\n", 0), None);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::theme::Style;
+use crate::utils::span::{IndexedCow, IndexedSpan};
+use ropey::Rope;
+use std::borrow::Cow;
+
+/// Syntax highlighter for a [`TextArea`].
+///
+/// Lines are highlighted in order, and `State` carries information from the
+/// end of a line to the start of the next one (for example, whether a
+/// multi-line comment is still open). Only lines that changed are
+/// highlighted again, as well as the lines after them until the state
+/// matches the previous run.
+///
+/// Closures `Fn(&str) -> Vec<IndexedSpan<Style>>` implement this trait,
+/// with no state.
+///
+/// [`TextArea`]: crate::views::TextArea
+pub trait Highlighter {
+    /// State carried from one line to the next.
+    ///
+    /// The first line starts with the default state.
+    type State: Clone + Default + PartialEq;
+
+    /// Returns the styled spans of `line`.
+    ///
+    /// `line` does not include the newline. `state` is the state at the
+    /// start of the line, and should be updated to the state at its end.
+    ///
+    /// Spans should borrow from `line` (as `IndexedCow::Borrowed`), and
+    /// not overlap. Parts of the line not covered by a span use the default
+    /// style.
+    fn highlight_line(
+        &self,
+        line: &str,
+        state: &mut Self::State,
+    ) -> Vec<IndexedSpan<Style>>;
+}
+
+impl<F> Highlighter for F
+where
+    F: Fn(&str) -> Vec<IndexedSpan<Style>>,
+{
+    type State = ();
+
+    fn highlight_line(
+        &self,
+        line: &str,
+        _: &mut Self::State,
+    ) -> Vec<IndexedSpan<Style>> {
+        self(line)
+    }
+}
+
+/// Highlighting results for each line of a text.
+pub(crate) trait HighlightCache {
+    /// Forgets everything, for a text with `lines` lines.
+    fn reset(&mut self, lines: usize);
+
+    /// Records that `removed` lines starting at `first` were replaced by
+    /// `inserted` lines.
+    fn edit(&mut self, first: usize, removed: usize, inserted: usize);
+
+    /// Highlights the lines up to `last` (included) that changed.
+    fn update(&mut self, content: &Rope, last: usize);
+
+    /// Returns the spans of the given line.
+    ///
+    /// Lines not highlighted yet have no span.
+    fn spans(&self, line: usize) -> &[IndexedSpan<Style>];
+}
+
+struct Line<S> {
+    /// State at the end of this line, if it was ever highlighted.
+    end_state: Option<S>,
+
+    spans: Vec<IndexedSpan<Style>>,
+
+    /// `true` if this line needs to be highlighted again.
+    dirty: bool,
+}
+
+impl<S> Line<S> {
+    fn dirty() -> Self {
+        Line {
+            end_state: None,
+            spans: Vec::new(),
+            dirty: true,
+        }
+    }
+}
+
+/// Line-based cache around a `Highlighter`.
+pub(crate) struct LineCache<H: Highlighter> {
+    highlighter: H,
+    lines: Vec<Line<H::State>>,
+
+    /// All lines before this one are up to date.
+    first_dirty: usize,
+}
+
+impl<H: Highlighter> LineCache<H> {
+    pub fn new(highlighter: H) -> Self {
+        LineCache {
+            highlighter,
+            lines: Vec::new(),
+            first_dirty: 0,
+        }
+    }
+}
+
+impl<H: Highlighter> HighlightCache for LineCache<H> {
+    fn reset(&mut self, lines: usize) {
+        self.lines = (0..lines).map(|_| Line::dirty()).collect();
+        self.first_dirty = 0;
+    }
+
+    fn edit(&mut self, first: usize, removed: usize, inserted: usize) {
+        // Lines still present are kept, to compare their end state later.
+        let kept = removed.min(inserted);
+        for line in &mut self.lines[first..first + kept] {
+            line.dirty = true;
+        }
+
+        let rest = first + kept;
+        self.lines.splice(
+            rest..first + removed,
+            (kept..inserted).map(|_| Line::dirty()),
+        );
+        self.first_dirty = self.first_dirty.min(first);
+    }
+
+    fn update(&mut self, content: &Rope, last: usize) {
+        let end = (last + 1).min(self.lines.len());
+
+        let mut i = self.first_dirty;
+        while i < end {
+            if !self.lines[i].dirty {
+                i += 1;
+                continue;
+            }
+
+            let mut state = match i {
+                0 => H::State::default(),
+                _ => self.lines[i - 1].end_state.clone().unwrap_or_default(),
+            };
+            let text: Cow<str> = content.line(i).into();
+            let text = text.trim_end_matches('\n');
+            let spans = self.highlighter.highlight_line(text, &mut state);
+
+            let line = &mut self.lines[i];
+            let changed = line.end_state.as_ref() != Some(&state);
+            *line = Line {
+                end_state: Some(state),
+                spans,
+                dirty: false,
+            };
+
+            // The next line starts differently now.
+            if changed {
+                if let Some(next) = self.lines.get_mut(i + 1) {
+                    next.dirty = true;
+                }
+            }
+            i += 1;
+        }
+
+        self.first_dirty = self.lines[i.min(self.lines.len())..]
+            .iter()
+            .position(|line| line.dirty)
+            .map_or(self.lines.len(), |p| i + p);
+    }
+
+    fn spans(&self, line: usize) -> &[IndexedSpan<Style>] {
+        self.lines.get(line).map_or(&[], |line| &line.spans)
+    }
+}
+
+/// Returns the byte range of `span` in its line, if it borrows from it.
+pub(crate) fn span_range(span: &IndexedSpan<Style>) -> Option<(usize, usize)> {
+    match span.content {
+        IndexedCow::Borrowed { start, end } => Some((start, end)),
+        IndexedCow::Owned(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Marks lines inside `/* */` comments, counting calls.
+    struct Comments(Rc<Cell<usize>>);
+
+    impl Highlighter for Comments {
+        type State = bool;
+
+        fn highlight_line(
+            &self,
+            line: &str,
+            in_comment: &mut bool,
+        ) -> Vec<IndexedSpan<Style>> {
+            self.0.set(self.0.get() + 1);
+            let commented = *in_comment || line.contains("/*");
+            *in_comment =
+                (*in_comment || line.contains("/*")) && !line.contains("*/");
+            if commented {
+                vec![IndexedSpan::simple_borrowed(line, Style::none())]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    #[test]
+    fn only_changed_lines_are_highlighted() {
+        let calls = Rc::new(Cell::new(0));
+        let mut cache = LineCache::new(Comments(Rc::clone(&calls)));
+        let mut content = Rope::from_str("a\n/*\nb\n*/\nc");
+        cache.reset(content.len_lines());
+        cache.update(&content, 4);
+        assert_eq!(calls.get(), 5);
+        assert!(!cache.spans(2).is_empty());
+        assert!(cache.spans(4).is_empty());
+
+        // Editing inside the comment doesn't change the state.
+        content.insert(6, "x");
+        cache.edit(2, 1, 1);
+        cache.update(&content, 4);
+        assert_eq!(calls.get(), 6);
+
+        // Closing the comment early changes the following lines.
+        content.insert(6, "*/");
+        cache.edit(2, 1, 1);
+        cache.update(&content, 4);
+        assert_eq!(calls.get(), 8);
+        assert!(cache.spans(3).is_empty());
+    }
+}
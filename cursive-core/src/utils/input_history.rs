@@ -0,0 +1,199 @@
+use crate::With;
+use std::sync::{Arc, Mutex};
+
+/// Default maximum number of entries kept in an [`InputHistory`].
+pub const DEFAULT_INPUT_HISTORY_LEN: usize = 500;
+
+/// History of values submitted in an input field.
+///
+/// Cloning an `InputHistory` gives another handle on the same history, so it
+/// can be shared between several views.
+///
+/// Entries are ordered from oldest to newest.
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::utils::InputHistory;
+/// use cursive_core::views::EditView;
+///
+/// // Restore a history saved from a previous run.
+/// let history = InputHistory::from(vec![
+///     String::from("ls"),
+///     String::from("cd /tmp"),
+/// ]);
+///
+/// let edit = EditView::new().input_history(history.clone());
+///
+/// history.push("ls");
+/// assert_eq!(history.to_vec(), vec!["cd /tmp", "ls"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct InputHistory {
+    inner: Arc<Mutex<InputHistoryInner>>,
+}
+
+#[derive(Debug)]
+struct InputHistoryInner {
+    entries: Vec<String>,
+    max_len: usize,
+    dedup: bool,
+}
+
+new_default!(InputHistory);
+
+impl InputHistory {
+    /// Creates a new, empty history.
+    ///
+    /// It keeps up to [`DEFAULT_INPUT_HISTORY_LEN`] entries, and removes
+    /// duplicates.
+    pub fn new() -> Self {
+        Self::with_max_len(DEFAULT_INPUT_HISTORY_LEN)
+    }
+
+    /// Creates a new, empty history keeping at most `max_len` entries.
+    pub fn with_max_len(max_len: usize) -> Self {
+        InputHistory {
+            inner: Arc::new(Mutex::new(InputHistoryInner {
+                entries: Vec::new(),
+                max_len,
+                dedup: true,
+            })),
+        }
+    }
+
+    fn with_inner<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut InputHistoryInner) -> R,
+    {
+        f(&mut self.inner.lock().unwrap())
+    }
+
+    /// Sets the maximum number of entries kept.
+    ///
+    /// Oldest entries are dropped if the history is already longer.
+    pub fn set_max_len(&self, max_len: usize) {
+        self.with_inner(|inner| {
+            inner.max_len = max_len;
+            inner.trim();
+        });
+    }
+
+    /// Returns the maximum number of entries kept.
+    pub fn max_len(&self) -> usize {
+        self.with_inner(|inner| inner.max_len)
+    }
+
+    /// Enables or disables de-duplication.
+    ///
+    /// When enabled (the default), adding an entry removes any previous
+    /// identical entry.
+    pub fn set_dedup(&self, dedup: bool) {
+        self.with_inner(|inner| inner.dedup = dedup);
+    }
+
+    /// Adds an entry to the history.
+    ///
+    /// Empty entries are ignored.
+    pub fn push<S: Into<String>>(&self, entry: S) {
+        let entry = entry.into();
+        if entry.is_empty() {
+            return;
+        }
+
+        self.with_inner(|inner| {
+            if inner.dedup {
+                inner.entries.retain(|e| *e != entry);
+            }
+            inner.entries.push(entry);
+            inner.trim();
+        });
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.with_inner(|inner| inner.entries.len())
+    }
+
+    /// Returns `true` if the history is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the entry at the given index, if any.
+    ///
+    /// Index `0` is the oldest entry.
+    pub fn get(&self, index: usize) -> Option<String> {
+        self.with_inner(|inner| inner.entries.get(index).cloned())
+    }
+
+    /// Returns the index of the newest entry before `before` containing
+    /// `query`.
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.with_inner(|inner| {
+            let before = before.min(inner.entries.len());
+            inner.entries[..before]
+                .iter()
+                .rposition(|e| e.contains(query))
+        })
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        self.with_inner(|inner| inner.entries.clear());
+    }
+
+    /// Returns a copy of all entries, from oldest to newest.
+    pub fn to_vec(&self) -> Vec<String> {
+        self.with_inner(|inner| inner.entries.clone())
+    }
+
+    /// Replaces all entries, given from oldest to newest.
+    ///
+    /// Empty entries are skipped, and duplicates are removed if enabled.
+    pub fn set_entries<I>(&self, entries: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.clear();
+        for entry in entries {
+            self.push(entry);
+        }
+    }
+}
+
+impl InputHistoryInner {
+    fn trim(&mut self) {
+        if self.entries.len() > self.max_len {
+            let excess = self.entries.len() - self.max_len;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+impl From<Vec<String>> for InputHistory {
+    fn from(entries: Vec<String>) -> Self {
+        InputHistory::new().with(|history| history.set_entries(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedup_and_max_len() {
+        let history = InputHistory::with_max_len(3);
+        for entry in &["a", "b", "", "a", "c", "d"] {
+            history.push(*entry);
+        }
+        assert_eq!(history.to_vec(), vec!["a", "c", "d"]);
+
+        assert_eq!(history.search("a", 3), Some(0));
+        assert_eq!(history.search("a", 0), None);
+
+        let shared = history.clone();
+        shared.push("e");
+        assert_eq!(history.get(2).as_deref(), Some("e"));
+    }
+}
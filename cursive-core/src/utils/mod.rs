@@ -1,12 +1,25 @@
 //! Toolbox to make text layout easier.
 
+pub mod clipboard;
 mod counter;
+mod edit_history;
+mod find;
+mod highlight;
 #[macro_use]
 mod immutify;
+mod input_history;
 pub mod lines;
 pub mod markup;
+mod placeholder;
 mod reader;
 pub mod span;
+pub(crate) mod words;
 
 pub use self::counter::Counter;
+pub use self::edit_history::{EditHistory, TextEdit, DEFAULT_HISTORY_DEPTH};
+pub use self::find::FindOptions;
+pub use self::highlight::Highlighter;
+pub(crate) use self::highlight::{span_range, HighlightCache, LineCache};
+pub use self::input_history::{InputHistory, DEFAULT_INPUT_HISTORY_LEN};
+pub(crate) use self::placeholder::draw_placeholder;
 pub use self::reader::ProgressReader;
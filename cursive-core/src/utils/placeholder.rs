@@ -0,0 +1,27 @@
+use crate::theme::Style;
+use crate::utils::lines::spans;
+use crate::utils::markup::StyledString;
+use crate::Printer;
+use unicode_width::UnicodeWidthStr;
+
+/// Draws `placeholder` wrapped in the available space.
+///
+/// Unstyled parts use `style`.
+pub(crate) fn draw_placeholder<S: Into<Style>>(
+    printer: &Printer,
+    placeholder: &StyledString,
+    style: S,
+) {
+    printer.with_style(style, |printer| {
+        let rows = spans::LinesIterator::new(placeholder, printer.size.x);
+        for (y, row) in rows.take(printer.size.y).enumerate() {
+            let mut x = 0;
+            for span in row.resolve(placeholder) {
+                printer.with_style(*span.attr, |printer| {
+                    printer.print((x, y), span.content);
+                    x += span.content.width();
+                });
+            }
+        }
+    });
+}
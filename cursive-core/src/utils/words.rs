@@ -0,0 +1,82 @@
+//! Word boundaries, used for word-wise selection and motion.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Returns the byte range of the word around `offset` in `text`.
+///
+/// If `offset` is not in a word, returns the range of the segment there
+/// (for example a run of spaces).
+pub(crate) fn word_at(text: &str, offset: usize) -> (usize, usize) {
+    text.split_word_bound_indices()
+        .map(|(start, segment)| (start, start + segment.len()))
+        .find(|&(start, end)| start <= offset && offset < end)
+        .unwrap_or((offset, offset))
+}
+
+/// Returns `true` if `segment` is a word, rather than spaces or punctuation.
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+/// Returns the start of the word before `offset` in `text`.
+///
+/// Like readline's `backward-word`, non-word characters right before
+/// `offset` are skipped first.
+///
+/// Returns `None` if there is no word before `offset`.
+pub(crate) fn prev_word_start(text: &str, offset: usize) -> Option<usize> {
+    text[..offset]
+        .split_word_bound_indices()
+        .rev()
+        .find(|&(_, segment)| is_word(segment))
+        .map(|(start, _)| start)
+}
+
+/// Returns the end of the word after `offset` in `text`.
+///
+/// Like readline's `forward-word`, non-word characters right after `offset`
+/// are skipped first.
+///
+/// Returns `None` if there is no word after `offset`.
+pub(crate) fn next_word_end(text: &str, offset: usize) -> Option<usize> {
+    text[offset..]
+        .split_word_bound_indices()
+        .find(|&(_, segment)| is_word(segment))
+        .map(|(start, segment)| offset + start + segment.len())
+}
+
+/// Returns the start of the first word after `offset` in `text`.
+///
+/// Like vi's `w` motion, the rest of the word at `offset` is skipped.
+///
+/// Returns `None` if there is no word after `offset`.
+pub(crate) fn next_word_start(text: &str, offset: usize) -> Option<usize> {
+    text.split_word_bound_indices()
+        .find(|&(start, segment)| start > offset && is_word(segment))
+        .map(|(start, _)| start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_motions() {
+        let text = "foo, bär  baz";
+
+        assert_eq!(prev_word_start(text, text.len()), Some(11));
+        assert_eq!(prev_word_start(text, 11), Some(5));
+        assert_eq!(prev_word_start(text, 8), Some(5));
+        assert_eq!(prev_word_start(text, 5), Some(0));
+        assert_eq!(prev_word_start(text, 0), None);
+
+        assert_eq!(next_word_end(text, 0), Some(3));
+        assert_eq!(next_word_end(text, 3), Some(9));
+        assert_eq!(next_word_end(text, 9), Some(text.len()));
+        assert_eq!(next_word_end(text, text.len()), None);
+
+        assert_eq!(next_word_start(text, 0), Some(5));
+        assert_eq!(next_word_start(text, 5), Some(11));
+        assert_eq!(next_word_start(text, 11), None);
+    }
+}
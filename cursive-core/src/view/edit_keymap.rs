@@ -0,0 +1,159 @@
+use crate::event::{Event, Key, KeyCombo, Modifiers};
+
+/// Key bindings used by text editing views.
+///
+/// Both [`EditView`] and [`TextArea`] can switch between these keymaps.
+///
+/// [`EditView`]: crate::views::EditView
+/// [`TextArea`]: crate::views::TextArea
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EditKeymap {
+    /// Bindings common in graphical text fields.
+    ///
+    /// Ctrl-Left/Right move by word, Alt-Backspace and Ctrl-Del delete a
+    /// word, and Ctrl-Z/Ctrl-Y undo and redo.
+    ///
+    /// Ctrl-Shift-Z also redoes when the backend uses the kitty keyboard
+    /// protocol. Most terminals send it as Ctrl-Z otherwise.
+    #[default]
+    Default,
+
+    /// Readline/Emacs-style bindings, on top of the default ones.
+    ///
+    /// * Ctrl-A/Ctrl-E move to the start/end of the line.
+    /// * Alt-B/Alt-F move by word.
+    /// * Ctrl-U/Ctrl-K kill the text before/after the cursor on this line.
+    /// * Ctrl-W and Alt-Backspace kill the previous word, Alt-D the next one.
+    /// * Ctrl-Y yanks the last killed text, instead of redoing.
+    ///
+    /// Consecutive kills are merged in a single kill ring entry.
+    Emacs,
+
+    /// Vi-style modal bindings.
+    ///
+    /// Views start in normal mode, where keys are commands:
+    ///
+    /// * `h`/`j`/`k`/`l`, `w`/`b`/`e`, `0`/`^`/`$` and `gg`/`G` move the
+    ///   cursor.
+    /// * `d`, `c` and `y` delete, change or copy the text covered by the
+    ///   next motion, or entire lines when repeated (`dd`).
+    /// * `x` deletes a character, `p`/`P` paste, and `u`/Ctrl-R undo and
+    ///   redo.
+    /// * `i`, `a`, `I`, `A`, `o` and `O` enter insert mode, and `v` enters
+    ///   visual mode.
+    ///
+    /// Commands can be preceded by a count, as in `3w` or `d2w`.
+    ///
+    /// In insert mode, the default bindings apply, and Esc goes back to
+    /// normal mode. In visual mode, motions extend the selection, and
+    /// `d`, `c` and `y` apply to it.
+    ///
+    /// Deleted and copied text goes to the [`clipboard`].
+    ///
+    /// [`clipboard`]: crate::utils::clipboard
+    Vi,
+}
+
+/// Editing command bound to an event by an [`EditKeymap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EditAction {
+    /// Move to the start of the previous word.
+    WordLeft,
+    /// Move to the end of the next word.
+    WordRight,
+    /// Move to the start of the line.
+    LineStart,
+    /// Move to the end of the line.
+    LineEnd,
+    /// Kill from the start of the line to the cursor.
+    KillLineStart,
+    /// Kill from the cursor to the end of the line.
+    KillLineEnd,
+    /// Kill from the start of the previous word to the cursor.
+    KillWordLeft,
+    /// Kill from the cursor to the end of the next word.
+    KillWordRight,
+    /// Insert the last killed text.
+    Yank,
+}
+
+impl EditAction {
+    /// Returns `true` if this action only moves the cursor.
+    pub(crate) fn is_motion(self) -> bool {
+        matches!(
+            self,
+            EditAction::WordLeft
+                | EditAction::WordRight
+                | EditAction::LineStart
+                | EditAction::LineEnd
+        )
+    }
+
+    /// Returns `true` if this action adds text to the kill ring.
+    pub(crate) fn is_kill(self) -> bool {
+        matches!(
+            self,
+            EditAction::KillLineStart
+                | EditAction::KillLineEnd
+                | EditAction::KillWordLeft
+                | EditAction::KillWordRight
+        )
+    }
+}
+
+impl EditKeymap {
+    /// Returns the action bound to `event`, if any.
+    pub(crate) fn action(self, event: &Event) -> Option<EditAction> {
+        let action = match (self, event) {
+            (_, Event::Ctrl(Key::Left)) => EditAction::WordLeft,
+            (_, Event::Ctrl(Key::Right)) => EditAction::WordRight,
+            (_, Event::Alt(Key::Backspace)) => EditAction::KillWordLeft,
+            (_, Event::Ctrl(Key::Del)) => EditAction::KillWordRight,
+            (EditKeymap::Emacs, Event::CtrlChar('a')) => EditAction::LineStart,
+            (EditKeymap::Emacs, Event::CtrlChar('e')) => EditAction::LineEnd,
+            (EditKeymap::Emacs, Event::AltChar('b')) => EditAction::WordLeft,
+            (EditKeymap::Emacs, Event::AltChar('f')) => EditAction::WordRight,
+            (EditKeymap::Emacs, Event::CtrlChar('u')) => {
+                EditAction::KillLineStart
+            }
+            (EditKeymap::Emacs, Event::CtrlChar('k')) => {
+                EditAction::KillLineEnd
+            }
+            (EditKeymap::Emacs, Event::CtrlChar('w')) => {
+                EditAction::KillWordLeft
+            }
+            (EditKeymap::Emacs, Event::AltChar('d')) => {
+                EditAction::KillWordRight
+            }
+            (EditKeymap::Emacs, Event::CtrlChar('y')) => EditAction::Yank,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// Returns `true` if `event` is Ctrl-Y or Ctrl-Shift-Z, bound to redo.
+///
+/// With [`EditKeymap::Emacs`], Ctrl-Y is bound to yank first.
+pub(crate) fn is_redo(event: &Event) -> bool {
+    *event == Event::CtrlChar('y')
+        || *event
+            == KeyCombo::new('z', Modifiers::CTRL | Modifiers::SHIFT).into()
+}
+
+/// Returns `true` if `key` moves the cursor in a text editing view.
+///
+/// With Shift, these keys extend the selection.
+pub(crate) fn is_motion_key(key: Key) -> bool {
+    matches!(
+        key,
+        Key::Left
+            | Key::Right
+            | Key::Up
+            | Key::Down
+            | Key::Home
+            | Key::End
+            | Key::PageUp
+            | Key::PageDown
+    )
+}
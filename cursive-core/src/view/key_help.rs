@@ -0,0 +1,99 @@
+use crate::event::Event;
+use crate::theme::Effect;
+use crate::utils::markup::StyledString;
+use unicode_width::UnicodeWidthStr;
+
+/// Description of a key binding, shown in the help layer.
+///
+/// See [`View::key_help`] and [`Cursive::toggle_help`].
+///
+/// [`View::key_help`]: crate::View::key_help
+/// [`Cursive::toggle_help`]: crate::Cursive::toggle_help
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyHelp {
+    /// Where the binding comes from, like `"Global"` or `"Menu"`.
+    ///
+    /// Bindings are grouped by source in the help layer.
+    pub source: String,
+    /// Keys triggering the binding, like `"ctrl+s"` or `"g g"`.
+    pub keys: String,
+    /// What the binding does.
+    pub description: String,
+}
+
+impl KeyHelp {
+    /// Creates a new description for the given keys.
+    pub fn new<S, K, D>(source: S, keys: K, description: D) -> Self
+    where
+        S: Into<String>,
+        K: Into<String>,
+        D: Into<String>,
+    {
+        KeyHelp {
+            source: source.into(),
+            keys: keys.into(),
+            description: description.into(),
+        }
+    }
+}
+
+/// Names a sequence of keys, like `"ctrl+x ctrl+s"`.
+pub(crate) fn key_names(keys: &[Event]) -> String {
+    let names: Vec<String> = keys
+        .iter()
+        .map(|key| match key.key_combo() {
+            Some(combo) => combo.to_string(),
+            None => format!("{:?}", key),
+        })
+        .collect();
+    names.join(" ")
+}
+
+/// Lists bindings grouped by source, in order of first appearance.
+pub(crate) fn format_key_help(help: &[KeyHelp]) -> StyledString {
+    let mut sources: Vec<&str> = Vec::new();
+    for entry in help {
+        if !sources.contains(&entry.source.as_str()) {
+            sources.push(&entry.source);
+        }
+    }
+    let width = help.iter().map(|entry| entry.keys.width()).max();
+
+    let mut text = StyledString::new();
+    for (i, source) in sources.into_iter().enumerate() {
+        if i > 0 {
+            text.append_plain("\n");
+        }
+        text.append_styled(source, Effect::Bold);
+        for entry in help.iter().filter(|entry| entry.source == source) {
+            let padding = width.unwrap_or(0) - entry.keys.width();
+            text.append_plain(format!(
+                "\n  {}{}  {}",
+                entry.keys,
+                " ".repeat(padding),
+                entry.description
+            ));
+        }
+        text.append_plain("\n");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_by_source() {
+        let help = vec![
+            KeyHelp::new("Global", "q", "Quit"),
+            KeyHelp::new("Editor", "ctrl+s", "Save"),
+            KeyHelp::new("Global", "f1", "Help"),
+        ];
+        assert_eq!(
+            format_key_help(&help).source(),
+            "Global\n  q       Quit\n  f1      Help\n\
+             \nEditor\n  ctrl+s  Save\n"
+        );
+    }
+}
@@ -85,6 +85,7 @@ mod view_wrapper;
 // Essentials components
 mod any;
 mod finder;
+mod key_help;
 mod margins;
 mod position;
 mod size_cache;
@@ -93,6 +94,7 @@ mod view_path;
 mod view_trait;
 
 // Helper bases
+mod edit_keymap;
 mod nameable;
 mod resizable;
 #[macro_use]
@@ -100,12 +102,17 @@ pub mod scroll;
 
 mod scroll_base;
 mod scrollable;
+mod vi;
 
 mod into_boxed_view;
 
 pub use self::any::AnyView;
+pub use self::edit_keymap::EditKeymap;
+pub(crate) use self::edit_keymap::{is_motion_key, is_redo, EditAction};
 pub use self::finder::{Finder, Selector};
 pub use self::into_boxed_view::IntoBoxedView;
+pub use self::key_help::KeyHelp;
+pub(crate) use self::key_help::{format_key_help, key_names};
 pub use self::margins::Margins;
 pub use self::nameable::Nameable;
 pub use self::position::{Offset, Position};
@@ -115,6 +122,10 @@ pub use self::scroll_base::ScrollBase;
 pub use self::scrollable::Scrollable;
 pub use self::size_cache::SizeCache;
 pub use self::size_constraint::SizeConstraint;
+pub use self::vi::ViMode;
+pub(crate) use self::vi::{
+    run_vi_command, OnViModeChange, ViCommand, ViEditor, ViInput, ViState,
+};
 pub use self::view_path::ViewPath;
 pub use self::view_trait::View;
 pub use self::view_wrapper::ViewWrapper;
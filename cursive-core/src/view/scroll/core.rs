@@ -253,6 +253,26 @@ impl Core {
                             self.offset.y + 3,
                         );
                     }
+                    Event::Mouse {
+                        event: MouseEvent::WheelLeft,
+                        ..
+                    } if self.enabled.x && self.offset.x > 0 => {
+                        self.offset.x = self.offset.x.saturating_sub(3);
+                    }
+                    Event::Mouse {
+                        event: MouseEvent::WheelRight,
+                        ..
+                    } if self.enabled.x
+                        && (self.offset.x + self.available_size().x
+                            < self.inner_size.x) =>
+                    {
+                        self.offset.x = min(
+                            self.inner_size
+                                .x
+                                .saturating_sub(self.available_size().x),
+                            self.offset.x + 3,
+                        );
+                    }
                     Event::Mouse {
                         event: MouseEvent::Press(MouseButton::Left),
                         position,
@@ -777,3 +797,43 @@ impl Core {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wheel(core: &mut Core, event: MouseEvent) -> usize {
+        let event = Event::Mouse {
+            offset: Vec2::zero(),
+            position: Vec2::zero(),
+            event,
+        };
+        core.on_inner_event(
+            event,
+            EventResult::Ignored,
+            Rect::from_size((0, 0), (1, 1)),
+        );
+        core.content_viewport().left()
+    }
+
+    #[test]
+    fn horizontal_wheel() {
+        let mut core = Core::new().scroll_x(true);
+        core.set_inner_size(Vec2::new(20, 3));
+        core.set_last_size(Vec2::new(10, 4), XY::new(true, false));
+        assert_eq!(core.content_viewport().width(), 10);
+
+        assert_eq!(wheel(&mut core, MouseEvent::WheelRight), 3);
+        assert_eq!(wheel(&mut core, MouseEvent::WheelRight), 6);
+        // The vertical wheel doesn't move the view sideways.
+        assert_eq!(wheel(&mut core, MouseEvent::WheelDown), 6);
+        assert_eq!(wheel(&mut core, MouseEvent::WheelLeft), 3);
+
+        for _ in 0..5 {
+            wheel(&mut core, MouseEvent::WheelRight);
+        }
+        assert_eq!(wheel(&mut core, MouseEvent::WheelRight), 10);
+        // Shift+wheel is sent as the horizontal wheel by backends.
+        assert_eq!(wheel(&mut core, MouseEvent::WheelUp.shifted()), 7);
+    }
+}
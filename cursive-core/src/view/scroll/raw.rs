@@ -227,6 +227,10 @@ where
     let inside = get_scroller(model).is_event_inside(&mut relative_event);
     let result = if inside {
         on_event(model, relative_event)
+    } else if event.is_mouse_move() {
+        // The content must know the mouse left, but any position we give
+        // could be scrolled out of sight.
+        on_event(model, Event::mouse_moved_away())
     } else {
         EventResult::Ignored
     };
@@ -0,0 +1,1051 @@
+use super::is_motion_key;
+use crate::event::{Event, Key};
+use crate::utils::{clipboard::Clipboard, words};
+use crate::Cursive;
+use std::borrow::Cow;
+use std::cmp::{max, min};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Input mode of the [`Vi`] keymap.
+///
+/// Displays as the usual vi mode name, for status lines:
+///
+/// ```rust
+/// # use cursive_core::view::ViMode;
+/// assert_eq!(format!("-- {} --", ViMode::Insert), "-- INSERT --");
+/// ```
+///
+/// [`Vi`]: crate::view::EditKeymap::Vi
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ViMode {
+    /// Keys run commands: motions, operators and mode switches.
+    #[default]
+    Normal,
+
+    /// Keys insert text, as with the default keymap.
+    Insert,
+
+    /// Motions extend the selection, and operators apply to it.
+    Visual,
+}
+
+impl fmt::Display for ViMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ViMode::Normal => "NORMAL",
+            ViMode::Insert => "INSERT",
+            ViMode::Visual => "VISUAL",
+        })
+    }
+}
+
+/// Closure type for callbacks when the vi mode changes.
+pub(crate) type OnViModeChange = dyn Fn(&mut Cursive, ViMode);
+
+/// Cursor motion in normal and visual mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ViMotion {
+    /// `h`: previous character on the line.
+    Left,
+    /// `l`: next character on the line.
+    Right,
+    /// `k`: previous row.
+    Up,
+    /// `j`: next row.
+    Down,
+    /// `w`: start of the next word.
+    WordForward,
+    /// `b`: start of the previous word.
+    WordBackward,
+    /// `e`: end of the next word.
+    WordEnd,
+    /// `0`: start of the line.
+    LineStart,
+    /// `^`: first non-blank character of the line.
+    FirstNonBlank,
+    /// `$`: end of the line.
+    LineEnd,
+    /// `gg` and `G`: first non-blank character of the given line (starting
+    /// at 1), or of the last line.
+    GotoLine(Option<usize>),
+}
+
+impl ViMotion {
+    /// Returns `true` if operators apply to entire lines with this motion.
+    fn is_linewise(self) -> bool {
+        matches!(self, ViMotion::Up | ViMotion::Down | ViMotion::GotoLine(_))
+    }
+}
+
+/// Operator applied to the text covered by a motion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ViOperator {
+    /// `d`: delete the text.
+    Delete,
+    /// `c`: delete the text and enter insert mode.
+    Change,
+    /// `y`: copy the text.
+    Yank,
+}
+
+/// Where insert mode starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ViInsert {
+    /// `i`: before the cursor.
+    Before,
+    /// `a`: after the cursor.
+    After,
+    /// `I`: before the first non-blank character of the line.
+    LineStart,
+    /// `A`: at the end of the line.
+    LineEnd,
+    /// `o`: on a new line below.
+    LineBelow,
+    /// `O`: on a new line above.
+    LineAbove,
+}
+
+/// Command parsed from normal or visual mode keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ViCommand {
+    /// Moves the cursor. In visual mode, this extends the selection.
+    Move(ViMotion),
+    /// Applies an operator from the cursor to the target of a motion.
+    Operate(ViOperator, ViMotion),
+    /// Applies an operator to entire lines (`dd`, `cc`, `yy`).
+    OperateLines(ViOperator),
+    /// Applies an operator to the selection, in visual mode.
+    OperateSelection(ViOperator),
+    /// Enters insert mode.
+    Insert(ViInsert),
+    /// `v`: enters or leaves visual mode.
+    ToggleVisual,
+    /// `Esc`: goes back to normal mode.
+    Escape,
+    /// `x`: deletes the character under the cursor.
+    DeleteChar,
+    /// `p` and `P`: pastes after or before the cursor.
+    Paste { before: bool },
+    /// `u`: undoes the last change.
+    Undo,
+    /// `Ctrl-R`: redoes the last undone change.
+    Redo,
+}
+
+/// Result of giving an event to a [`ViState`].
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ViInput {
+    /// The event should be processed normally.
+    ///
+    /// In visual mode, motion keys are turned into their Shift variant, to
+    /// extend the selection.
+    Pass(Event),
+    /// The event was used, nothing needs to be done yet.
+    Consumed,
+    /// The event completed a command, to repeat `count` times.
+    Command(ViCommand, usize),
+}
+
+/// Mode and pending keys of the vi keymap.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ViState {
+    mode: ViMode,
+
+    /// Count typed before the current command.
+    count: Option<usize>,
+
+    /// Operator waiting for a motion, with the count typed before it.
+    operator: Option<(ViOperator, Option<usize>)>,
+
+    /// `true` after `g`, waiting for the second key.
+    g: bool,
+}
+
+impl ViState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> ViMode {
+        self.mode
+    }
+
+    /// Switches to the given mode, dropping pending keys.
+    ///
+    /// Returns `true` if the mode changed.
+    pub fn set_mode(&mut self, mode: ViMode) -> bool {
+        let changed = self.mode != mode;
+        *self = ViState {
+            mode,
+            ..ViState::default()
+        };
+        changed
+    }
+
+    /// Parses `event`, which may complete a command.
+    pub fn on_event(&mut self, event: Event) -> ViInput {
+        if self.mode == ViMode::Insert {
+            return match event {
+                Event::Key(Key::Esc) => ViInput::Command(ViCommand::Escape, 1),
+                event => ViInput::Pass(event),
+            };
+        }
+
+        let ch = match event {
+            Event::Char(ch) => ch,
+            Event::Key(Key::Backspace) => 'h',
+            Event::Key(Key::Enter) => 'j',
+            Event::Key(Key::Del) => 'x',
+            Event::Key(Key::Esc) => {
+                self.set_mode(self.mode);
+                return ViInput::Command(ViCommand::Escape, 1);
+            }
+            Event::CtrlChar('r') => {
+                let count = self.take_count().unwrap_or(1);
+                return ViInput::Command(ViCommand::Redo, count);
+            }
+            Event::Key(key)
+                if self.mode == ViMode::Visual && is_motion_key(key) =>
+            {
+                self.set_mode(self.mode);
+                return ViInput::Pass(Event::Shift(key));
+            }
+            event => {
+                self.set_mode(self.mode);
+                return ViInput::Pass(event);
+            }
+        };
+
+        if let Some(digit) = ch.to_digit(10) {
+            if digit != 0 || self.count.is_some() {
+                let count = self.count.unwrap_or(0);
+                self.count =
+                    Some(count.saturating_mul(10).saturating_add(digit as _));
+                return ViInput::Consumed;
+            }
+        }
+
+        let g = std::mem::replace(&mut self.g, false);
+        if ch == 'g' && !g {
+            self.g = true;
+            return ViInput::Consumed;
+        }
+
+        let operator = self.operator.map(|(operator, _)| operator);
+        let count = self.take_count();
+        let n = count.unwrap_or(1);
+
+        let motion = match (g, ch) {
+            (true, 'g') => Some(ViMotion::GotoLine(Some(n))),
+            // Other `g` commands are not supported.
+            (true, _) => return ViInput::Consumed,
+            (false, 'h') => Some(ViMotion::Left),
+            (false, 'l') | (false, ' ') => Some(ViMotion::Right),
+            (false, 'k') => Some(ViMotion::Up),
+            (false, 'j') => Some(ViMotion::Down),
+            (false, 'w') => Some(ViMotion::WordForward),
+            (false, 'b') => Some(ViMotion::WordBackward),
+            (false, 'e') => Some(ViMotion::WordEnd),
+            (false, '0') => Some(ViMotion::LineStart),
+            (false, '^') => Some(ViMotion::FirstNonBlank),
+            (false, '$') => Some(ViMotion::LineEnd),
+            (false, 'G') => Some(ViMotion::GotoLine(count)),
+            _ => None,
+        };
+        if let Some(motion) = motion {
+            let command = match operator {
+                Some(operator) => ViCommand::Operate(operator, motion),
+                None => ViCommand::Move(motion),
+            };
+            return ViInput::Command(command, n);
+        }
+
+        let operator_key = match ch {
+            'd' => Some(ViOperator::Delete),
+            'c' => Some(ViOperator::Change),
+            'y' => Some(ViOperator::Yank),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            // Only a repeated operator (`dd`) makes sense here.
+            return match operator_key {
+                Some(key) if key == operator => {
+                    ViInput::Command(ViCommand::OperateLines(operator), n)
+                }
+                _ => ViInput::Consumed,
+            };
+        }
+
+        let command = if self.mode == ViMode::Visual {
+            match ch {
+                'd' | 'x' => ViCommand::OperateSelection(ViOperator::Delete),
+                'c' | 's' => ViCommand::OperateSelection(ViOperator::Change),
+                'y' => ViCommand::OperateSelection(ViOperator::Yank),
+                'v' => ViCommand::ToggleVisual,
+                _ => return ViInput::Consumed,
+            }
+        } else {
+            match ch {
+                'd' | 'c' | 'y' => {
+                    self.operator = operator_key.map(|key| (key, count));
+                    return ViInput::Consumed;
+                }
+                'D' => {
+                    ViCommand::Operate(ViOperator::Delete, ViMotion::LineEnd)
+                }
+                'C' => {
+                    ViCommand::Operate(ViOperator::Change, ViMotion::LineEnd)
+                }
+                'Y' => ViCommand::OperateLines(ViOperator::Yank),
+                's' => ViCommand::Operate(ViOperator::Change, ViMotion::Right),
+                'x' => ViCommand::DeleteChar,
+                'i' => ViCommand::Insert(ViInsert::Before),
+                'a' => ViCommand::Insert(ViInsert::After),
+                'I' => ViCommand::Insert(ViInsert::LineStart),
+                'A' => ViCommand::Insert(ViInsert::LineEnd),
+                'o' => ViCommand::Insert(ViInsert::LineBelow),
+                'O' => ViCommand::Insert(ViInsert::LineAbove),
+                'v' => ViCommand::ToggleVisual,
+                'p' => ViCommand::Paste { before: false },
+                'P' => ViCommand::Paste { before: true },
+                'u' => ViCommand::Undo,
+                _ => return ViInput::Consumed,
+            }
+        };
+        ViInput::Command(command, n)
+    }
+
+    /// Returns the count for the current command, and forgets pending keys.
+    ///
+    /// Counts before and after an operator multiply (`2d3w` deletes 6
+    /// words).
+    fn take_count(&mut self) -> Option<usize> {
+        let before = self.operator.take().and_then(|(_, count)| count);
+        match (before, self.count.take()) {
+            (Some(a), Some(b)) => Some(a.saturating_mul(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Text editing primitives needed to run vi commands.
+///
+/// Lines are separated by `\n`; views without newlines have a single line.
+pub(crate) trait ViEditor {
+    /// Returns the clipboard deleted and copied text goes to.
+    fn clipboard(&self) -> &Clipboard;
+
+    /// Returns the cursor position, as a byte offset.
+    fn cursor(&self) -> usize;
+
+    /// Moves the cursor, keeping the selection anchor.
+    fn move_cursor(&mut self, cursor: usize);
+
+    /// Returns the selection anchor, if any.
+    fn anchor(&self) -> Option<usize>;
+
+    /// Sets the selection anchor.
+    fn set_anchor(&mut self, anchor: Option<usize>);
+
+    /// Returns `false` if the content cannot contain newlines.
+    fn is_multiline(&self) -> bool;
+
+    /// Returns the number of lines, at least 1.
+    fn line_count(&self) -> usize;
+
+    /// Returns the line containing the given byte offset.
+    fn line_at(&self, offset: usize) -> usize;
+
+    /// Returns the byte range of the given line, without the newline.
+    fn line_range(&self, line: usize) -> (usize, usize);
+
+    /// Returns the text between the given byte offsets.
+    fn slice(&self, start: usize, end: usize) -> Cow<'_, str>;
+
+    /// Returns the position one row above or below `offset`, if any.
+    fn row_target(&self, offset: usize, down: bool) -> Option<usize>;
+
+    /// Replaces the text between `start` and `end` as one undo step.
+    ///
+    /// The selection is cleared.
+    fn replace_text(&mut self, start: usize, end: usize, text: &str);
+
+    /// Replaces the text between `start` and `end`, before insert mode.
+    ///
+    /// Text typed right after is part of the same undo step.
+    fn begin_change(&mut self, start: usize, end: usize, text: &str);
+
+    /// Reverts the last change. Returns `false` if there was none.
+    fn undo_step(&mut self) -> bool;
+
+    /// Re-applies the last undone change. Returns `false` if there was
+    /// none.
+    fn redo_step(&mut self) -> bool;
+}
+
+/// Runs `command` `count` times on `editor`, in the given mode.
+///
+/// Deleted and copied text goes to the editor clipboard. Returns the mode
+/// after the command.
+pub(crate) fn run_vi_command<E: ViEditor>(
+    editor: &mut E,
+    mode: ViMode,
+    command: ViCommand,
+    count: usize,
+) -> ViMode {
+    let mode = match command {
+        ViCommand::Move(motion) => {
+            let target = match motion {
+                // Move to the last character of the word, rather than after
+                // it.
+                ViMotion::WordEnd => {
+                    let end = motion_target(editor, motion, count);
+                    max(editor.cursor(), prev_grapheme(editor, end))
+                }
+                _ => motion_target(editor, motion, count),
+            };
+            editor.move_cursor(target);
+            mode
+        }
+        ViCommand::Operate(operator, motion) if motion.is_linewise() => {
+            let target = motion_target(editor, motion, count);
+            let first = editor.line_at(editor.cursor());
+            let last = editor.line_at(target);
+            operate_lines(editor, operator, min(first, last), max(first, last))
+        }
+        ViCommand::Operate(operator, motion) => {
+            let cursor = editor.cursor();
+            let (_, line_end) = editor.line_range(editor.line_at(cursor));
+            let target = match motion {
+                // `cw` changes until the end of the word, like `ce`.
+                ViMotion::WordForward
+                    if operator == ViOperator::Change
+                        && !next_grapheme(editor, cursor)
+                            .trim_start()
+                            .is_empty() =>
+                {
+                    motion_target(editor, ViMotion::WordEnd, count)
+                }
+                // `dw` on the last word doesn't join the next line.
+                ViMotion::WordForward => {
+                    min(motion_target(editor, motion, count), line_end)
+                }
+                _ => motion_target(editor, motion, count),
+            };
+            operate(editor, operator, min(cursor, target), max(cursor, target))
+        }
+        ViCommand::OperateLines(operator) => {
+            let first = editor.line_at(editor.cursor());
+            let last = min(first + count - 1, editor.line_count() - 1);
+            operate_lines(editor, operator, first, last)
+        }
+        ViCommand::OperateSelection(operator) => {
+            let cursor = editor.cursor();
+            let anchor = editor.anchor().unwrap_or(cursor);
+            let (start, end) = (min(cursor, anchor), max(cursor, anchor));
+            // The character under the cursor is part of the selection.
+            let end = end + next_grapheme(editor, end).len();
+            editor.set_anchor(None);
+            operate(editor, operator, start, end)
+        }
+        ViCommand::Insert(insert) => {
+            let cursor = editor.cursor();
+            let (start, end) = editor.line_range(editor.line_at(cursor));
+            let multiline = editor.is_multiline();
+            match insert {
+                ViInsert::Before => (),
+                ViInsert::After => {
+                    if cursor < end {
+                        let len = next_grapheme(editor, cursor).len();
+                        editor.move_cursor(cursor + len);
+                    }
+                }
+                ViInsert::LineStart => {
+                    editor.move_cursor(first_non_blank(editor, start, end))
+                }
+                ViInsert::LineEnd => editor.move_cursor(end),
+                ViInsert::LineBelow if multiline => {
+                    editor.begin_change(end, end, "\n");
+                }
+                ViInsert::LineAbove if multiline => {
+                    editor.begin_change(start, start, "\n");
+                    editor.move_cursor(start);
+                }
+                // Without newlines, act like `A` and `I`.
+                ViInsert::LineBelow => editor.move_cursor(end),
+                ViInsert::LineAbove => editor.move_cursor(start),
+            }
+            ViMode::Insert
+        }
+        ViCommand::ToggleVisual if mode == ViMode::Visual => {
+            editor.set_anchor(None);
+            ViMode::Normal
+        }
+        ViCommand::ToggleVisual => {
+            editor.set_anchor(Some(editor.cursor()));
+            ViMode::Visual
+        }
+        ViCommand::Escape => {
+            if mode == ViMode::Insert {
+                // The cursor goes back on the last inserted character.
+                let cursor = editor.cursor();
+                let (start, _) = editor.line_range(editor.line_at(cursor));
+                if cursor > start {
+                    editor.move_cursor(prev_grapheme(editor, cursor));
+                }
+            }
+            editor.set_anchor(None);
+            ViMode::Normal
+        }
+        ViCommand::DeleteChar => {
+            let cursor = editor.cursor();
+            let target = motion_target(editor, ViMotion::Right, count);
+            operate(editor, ViOperator::Delete, cursor, target)
+        }
+        ViCommand::Paste { before } => {
+            paste(editor, before, count);
+            mode
+        }
+        ViCommand::Undo => {
+            for _ in 0..count {
+                if !editor.undo_step() {
+                    break;
+                }
+            }
+            mode
+        }
+        ViCommand::Redo => {
+            for _ in 0..count {
+                if !editor.redo_step() {
+                    break;
+                }
+            }
+            mode
+        }
+    };
+
+    if mode == ViMode::Normal {
+        // In normal mode, the cursor stays on a character.
+        let cursor = editor.cursor();
+        let (start, end) = editor.line_range(editor.line_at(cursor));
+        if cursor >= end && end > start {
+            editor.move_cursor(prev_grapheme(editor, end));
+        }
+    }
+    mode
+}
+
+/// Returns the grapheme starting at `offset`, on the same line.
+fn next_grapheme<E: ViEditor>(editor: &E, offset: usize) -> String {
+    let (_, end) = editor.line_range(editor.line_at(offset));
+    let text = editor.slice(offset, max(offset, end));
+    text.graphemes(true).next().unwrap_or_default().to_string()
+}
+
+/// Returns the start of the grapheme before `offset`, on the same line.
+fn prev_grapheme<E: ViEditor>(editor: &E, offset: usize) -> usize {
+    let (start, _) = editor.line_range(editor.line_at(offset));
+    let text = editor.slice(start, offset);
+    let len = text.graphemes(true).next_back().map_or(0, str::len);
+    offset - len
+}
+
+/// Returns the first non-blank character between `start` and `end`.
+fn first_non_blank<E: ViEditor>(
+    editor: &E,
+    start: usize,
+    end: usize,
+) -> usize {
+    let text = editor.slice(start, end);
+    end - text.trim_start().len()
+}
+
+/// Returns the position reached by repeating `motion` `count` times.
+fn motion_target<E: ViEditor>(
+    editor: &E,
+    motion: ViMotion,
+    count: usize,
+) -> usize {
+    let mut target = editor.cursor();
+    for _ in 0..count {
+        let line = editor.line_at(target);
+        let (start, end) = editor.line_range(line);
+        let last_line = line + 1 == editor.line_count();
+        let next = match motion {
+            ViMotion::Left if target > start => prev_grapheme(editor, target),
+            ViMotion::Right if target < end => {
+                target + next_grapheme(editor, target).len()
+            }
+            ViMotion::Up => editor.row_target(target, false).unwrap_or(target),
+            ViMotion::Down => {
+                editor.row_target(target, true).unwrap_or(target)
+            }
+            ViMotion::WordForward => {
+                let text = editor.slice(start, end);
+                match words::next_word_start(&text, target - start) {
+                    Some(word) => start + word,
+                    None if last_line => end,
+                    None => {
+                        let (start, end) = editor.line_range(line + 1);
+                        first_non_blank(editor, start, end)
+                    }
+                }
+            }
+            ViMotion::WordBackward => {
+                let text = editor.slice(start, target);
+                match words::prev_word_start(&text, text.len()) {
+                    Some(word) => start + word,
+                    None if line == 0 => 0,
+                    None if target > first_non_blank(editor, start, end) => {
+                        start
+                    }
+                    None => {
+                        let (start, end) = editor.line_range(line - 1);
+                        let text = editor.slice(start, end);
+                        words::prev_word_start(&text, text.len())
+                            .map_or(start, |word| start + word)
+                    }
+                }
+            }
+            ViMotion::WordEnd => word_end(editor, target),
+            ViMotion::LineStart => start,
+            ViMotion::FirstNonBlank => first_non_blank(editor, start, end),
+            ViMotion::LineEnd => end,
+            ViMotion::GotoLine(line) => {
+                let last = editor.line_count() - 1;
+                let line = line
+                    .map_or(last, |line| min(line.saturating_sub(1), last));
+                let (start, end) = editor.line_range(line);
+                return first_non_blank(editor, start, end);
+            }
+            _ => target,
+        };
+        if next == target {
+            break;
+        }
+        target = next;
+    }
+    target
+}
+
+/// Returns the end of the word after the character at `offset`.
+///
+/// Looks into the next lines if needed.
+fn word_end<E: ViEditor>(editor: &E, offset: usize) -> usize {
+    let mut line = editor.line_at(offset);
+    let mut from = offset + next_grapheme(editor, offset).len();
+    loop {
+        let (start, end) = editor.line_range(line);
+        let text = editor.slice(start, end);
+        if let Some(word) = words::next_word_end(&text, from - start) {
+            return start + word;
+        }
+        line += 1;
+        if line == editor.line_count() {
+            return end;
+        }
+        from = editor.line_range(line).0;
+    }
+}
+
+/// Applies `operator` to the text between `start` and `end`.
+fn operate<E: ViEditor>(
+    editor: &mut E,
+    operator: ViOperator,
+    start: usize,
+    end: usize,
+) -> ViMode {
+    if start < end {
+        let text = editor.slice(start, end).into_owned();
+        editor.clipboard().set_content(text);
+    }
+    match operator {
+        ViOperator::Delete if start < end => {
+            editor.replace_text(start, end, "")
+        }
+        ViOperator::Change => editor.begin_change(start, end, ""),
+        _ => (),
+    }
+    editor.move_cursor(start);
+
+    match operator {
+        ViOperator::Change => ViMode::Insert,
+        _ => ViMode::Normal,
+    }
+}
+
+/// Applies `operator` to the lines `first..=last`.
+///
+/// The text copied to the clipboard ends with a newline, so it is pasted
+/// as entire lines.
+fn operate_lines<E: ViEditor>(
+    editor: &mut E,
+    operator: ViOperator,
+    first: usize,
+    last: usize,
+) -> ViMode {
+    let start = editor.line_range(first).0;
+    let end = editor.line_range(last).1;
+    let mut text = editor.slice(start, end).into_owned();
+    text.push('\n');
+    editor.clipboard().set_content(text);
+
+    match operator {
+        ViOperator::Yank => {
+            if editor.line_at(editor.cursor()) != first {
+                editor.move_cursor(start);
+            }
+            ViMode::Normal
+        }
+        ViOperator::Change => {
+            let indent = first_non_blank(editor, start, end);
+            editor.begin_change(indent, end, "");
+            editor.move_cursor(indent);
+            ViMode::Insert
+        }
+        ViOperator::Delete => {
+            // Remove one of the newlines around the lines, if any.
+            let (start, end) = if last + 1 < editor.line_count() {
+                (start, editor.line_range(last + 1).0)
+            } else if first > 0 {
+                (editor.line_range(first - 1).1, end)
+            } else {
+                (start, end)
+            };
+            editor.replace_text(start, end, "");
+
+            let line = min(first, editor.line_count() - 1);
+            let (start, end) = editor.line_range(line);
+            editor.move_cursor(first_non_blank(editor, start, end));
+            ViMode::Normal
+        }
+    }
+}
+
+/// Pastes the clipboard `count` times.
+///
+/// Text ending with a newline is pasted as entire lines, below or above the
+/// cursor line.
+fn paste<E: ViEditor>(editor: &mut E, before: bool, count: usize) {
+    let mut text = editor.clipboard().get_content();
+    if !editor.is_multiline() {
+        text.retain(|c| c != '\n' && c != '\r');
+    }
+    if text.is_empty() {
+        return;
+    }
+    let text = text.repeat(count);
+
+    let cursor = editor.cursor();
+    let line = editor.line_at(cursor);
+    let (start, end) = editor.line_range(line);
+
+    if text.ends_with('\n') {
+        let position = if before {
+            editor.replace_text(start, start, &text);
+            start
+        } else if line + 1 < editor.line_count() {
+            let position = editor.line_range(line + 1).0;
+            editor.replace_text(position, position, &text);
+            position
+        } else {
+            // Add the newline before the pasted lines instead.
+            let lines = format!("\n{}", &text[..text.len() - 1]);
+            editor.replace_text(end, end, &lines);
+            end + 1
+        };
+        // The cursor ends on the first pasted line.
+        let (start, end) = editor.line_range(editor.line_at(position));
+        editor.move_cursor(first_non_blank(editor, start, end));
+    } else {
+        let position = if before || cursor == end {
+            cursor
+        } else {
+            cursor + next_grapheme(editor, cursor).len()
+        };
+        editor.replace_text(position, position, &text);
+        // The cursor ends on the last pasted character.
+        let last = text.graphemes(true).next_back().map_or(0, str::len);
+        editor.move_cursor(position + text.len() - last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{EditHistory, TextEdit};
+
+    /// Editor over a `String`, with its own clipboard and history.
+    struct Editor {
+        text: String,
+        cursor: usize,
+        anchor: Option<usize>,
+        clipboard: Clipboard,
+        history: EditHistory,
+        vi: ViState,
+    }
+
+    impl Editor {
+        fn new(text: &str) -> Self {
+            Editor {
+                text: text.to_string(),
+                cursor: 0,
+                anchor: None,
+                clipboard: Clipboard::new(),
+                history: EditHistory::new(),
+                vi: ViState::new(),
+            }
+        }
+
+        /// Types `keys` (`\x1b` is Esc), and returns the text with `|`
+        /// before the cursor.
+        fn keys(&mut self, keys: &str) -> String {
+            for c in keys.chars() {
+                let event = match c {
+                    '\x1b' => Event::Key(Key::Esc),
+                    c => Event::Char(c),
+                };
+                match self.vi.on_event(event) {
+                    ViInput::Command(command, count) => {
+                        self.history.seal();
+                        let mode = self.vi.mode();
+                        let mode = run_vi_command(self, mode, command, count);
+                        self.vi.set_mode(mode);
+                    }
+                    ViInput::Pass(Event::Char(c)) => {
+                        let cursor = self.cursor;
+                        let edit = TextEdit::insertion(cursor, c.to_string());
+                        edit.apply(&mut self.text);
+                        self.cursor += c.len_utf8();
+                        self.history.record(edit, cursor, self.cursor, true);
+                    }
+                    _ => (),
+                }
+            }
+
+            let mut text = self.text.clone();
+            text.insert(self.cursor, '|');
+            text
+        }
+
+        fn apply(&mut self, step: Option<(Vec<TextEdit>, usize)>) -> bool {
+            match step {
+                Some((edits, cursor)) => {
+                    for edit in edits {
+                        edit.apply(&mut self.text);
+                    }
+                    self.cursor = cursor;
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    impl ViEditor for Editor {
+        fn clipboard(&self) -> &Clipboard {
+            &self.clipboard
+        }
+
+        fn cursor(&self) -> usize {
+            self.cursor
+        }
+
+        fn move_cursor(&mut self, cursor: usize) {
+            self.cursor = cursor;
+        }
+
+        fn anchor(&self) -> Option<usize> {
+            self.anchor
+        }
+
+        fn set_anchor(&mut self, anchor: Option<usize>) {
+            self.anchor = anchor;
+        }
+
+        fn is_multiline(&self) -> bool {
+            true
+        }
+
+        fn line_count(&self) -> usize {
+            self.text.matches('\n').count() + 1
+        }
+
+        fn line_at(&self, offset: usize) -> usize {
+            self.text[..offset].matches('\n').count()
+        }
+
+        fn line_range(&self, line: usize) -> (usize, usize) {
+            let start = self
+                .text
+                .match_indices('\n')
+                .nth(line.wrapping_sub(1))
+                .map_or(0, |(i, _)| i + 1);
+            let end = self.text[start..]
+                .find('\n')
+                .map_or(self.text.len(), |i| start + i);
+            (start, end)
+        }
+
+        fn slice(&self, start: usize, end: usize) -> Cow<'_, str> {
+            Cow::Borrowed(&self.text[start..end])
+        }
+
+        fn row_target(&self, offset: usize, down: bool) -> Option<usize> {
+            let line = self.line_at(offset);
+            let column = offset - self.line_range(line).0;
+            let line = if down {
+                Some(line + 1).filter(|&line| line < self.line_count())
+            } else {
+                line.checked_sub(1)
+            }?;
+            let (start, end) = self.line_range(line);
+            Some(min(start + column, end))
+        }
+
+        fn replace_text(&mut self, start: usize, end: usize, text: &str) {
+            let edit = TextEdit {
+                position: start,
+                removed: self.text[start..end].to_string(),
+                inserted: text.to_string(),
+            };
+            edit.apply(&mut self.text);
+            self.history.record(edit, self.cursor, start, false);
+            self.anchor = None;
+        }
+
+        fn begin_change(&mut self, start: usize, end: usize, text: &str) {
+            let edit = TextEdit {
+                position: start,
+                removed: self.text[start..end].to_string(),
+                inserted: text.to_string(),
+            };
+            edit.apply(&mut self.text);
+            let cursor = start + text.len();
+            self.history.record_change(edit, self.cursor, cursor);
+            self.cursor = cursor;
+            self.anchor = None;
+        }
+
+        fn undo_step(&mut self) -> bool {
+            let step = self.history.undo();
+            self.apply(step)
+        }
+
+        fn redo_step(&mut self) -> bool {
+            let step = self.history.redo();
+            self.apply(step)
+        }
+    }
+
+    #[test]
+    fn delete_words_and_chars() {
+        let mut editor = Editor::new("foo bar baz\nqux");
+        assert_eq!(editor.keys("dw"), "|bar baz\nqux");
+        assert_eq!(editor.clipboard.get_content(), "foo ");
+        // `dw` on the last word of a line doesn't join the next one.
+        assert_eq!(editor.keys("w2dw"), "bar| \nqux");
+        assert_eq!(editor.keys("u"), "bar |baz\nqux");
+
+        let mut editor = Editor::new("abcdef");
+        assert_eq!(editor.keys("x"), "|bcdef");
+        assert_eq!(editor.keys("l3x"), "b|f");
+        // The cursor stays on a character.
+        assert_eq!(editor.keys("$x"), "|b");
+    }
+
+    #[test]
+    fn change_is_one_undo_step() {
+        let mut editor = Editor::new("foo bar");
+        assert_eq!(editor.keys("cwqux"), "qux| bar");
+        assert_eq!(editor.keys("\x1b"), "qu|x bar");
+        assert_eq!(editor.keys("u"), "|foo bar");
+        assert_eq!(
+            editor.vi.on_event(Event::CtrlChar('r')),
+            ViInput::Command(ViCommand::Redo, 1)
+        );
+        assert!(editor.redo_step());
+        assert_eq!(editor.text, "qux bar");
+    }
+
+    #[test]
+    fn delete_and_paste_lines() {
+        let mut editor = Editor::new("one\n  two\nthree");
+        assert_eq!(editor.keys("jdd"), "one\n|three");
+        assert_eq!(editor.clipboard.get_content(), "  two\n");
+        assert_eq!(editor.keys("p"), "one\nthree\n  |two");
+        assert_eq!(editor.keys("kP"), "one\n  |two\nthree\n  two");
+        assert_eq!(editor.keys("gg2dd"), "|three\n  two");
+        assert_eq!(editor.keys("Gdd"), "|three");
+    }
+
+    #[test]
+    fn yank_and_paste() {
+        let mut editor = Editor::new("foo bar");
+        assert_eq!(editor.keys("ywP"), "foo| foo bar");
+        assert_eq!(editor.keys("$2p"), "foo foo barfoo foo| ");
+    }
+
+    #[test]
+    fn visual_operators() {
+        let mut editor = Editor::new("abcdef");
+        assert_eq!(editor.keys("lvll"), "abc|def");
+        assert_eq!(editor.anchor, Some(1));
+        // The character under the cursor is part of the selection.
+        assert_eq!(editor.keys("d"), "a|ef");
+        assert_eq!(editor.clipboard.get_content(), "bcd");
+        assert_eq!(editor.vi.mode(), ViMode::Normal);
+
+        assert_eq!(editor.keys("vlcX"), "aX|");
+        assert_eq!(editor.vi.mode(), ViMode::Insert);
+    }
+
+    fn parse(state: &mut ViState, keys: &str) -> Vec<ViInput> {
+        keys.chars()
+            .map(|c| state.on_event(Event::Char(c)))
+            .filter(|input| *input != ViInput::Consumed)
+            .collect()
+    }
+
+    #[test]
+    fn counts_and_operators() {
+        let mut state = ViState::new();
+        assert_eq!(
+            parse(&mut state, "3w2d3wddgg10G"),
+            vec![
+                ViInput::Command(ViCommand::Move(ViMotion::WordForward), 3),
+                ViInput::Command(
+                    ViCommand::Operate(
+                        ViOperator::Delete,
+                        ViMotion::WordForward
+                    ),
+                    6
+                ),
+                ViInput::Command(
+                    ViCommand::OperateLines(ViOperator::Delete),
+                    1
+                ),
+                ViInput::Command(
+                    ViCommand::Move(ViMotion::GotoLine(Some(1))),
+                    1
+                ),
+                ViInput::Command(
+                    ViCommand::Move(ViMotion::GotoLine(Some(10))),
+                    10
+                ),
+            ]
+        );
+
+        // Insert mode only reacts to Esc.
+        state.set_mode(ViMode::Insert);
+        assert_eq!(
+            parse(&mut state, "d"),
+            vec![ViInput::Pass(Event::Char('d'))]
+        );
+        assert_eq!(
+            state.on_event(Event::Key(Key::Esc)),
+            ViInput::Command(ViCommand::Escape, 1)
+        );
+    }
+}
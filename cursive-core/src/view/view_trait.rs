@@ -1,7 +1,8 @@
 use crate::direction::Direction;
 use crate::event::{AnyCb, Event, EventResult};
+use crate::keymap::{Conflict, Keymap};
 use crate::rect::Rect;
-use crate::view::{AnyView, Selector};
+use crate::view::{AnyView, KeyHelp, Selector};
 use crate::Printer;
 use crate::Vec2;
 use std::any::Any;
@@ -118,6 +119,35 @@ pub trait View: Any + AnyView {
         Rect::from_size((0, 0), view_size)
     }
 
+    /// Lists the key bindings of this view and of its focused children.
+    ///
+    /// This is used by the help layer. Bindings are pushed from the
+    /// outermost view to the innermost one.
+    ///
+    /// View groups should forward the call to their focused child.
+    ///
+    /// Default implementation does nothing.
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        let _ = help;
+    }
+
+    /// Lists the keymap bindings shadowed by this view or its focused
+    /// children.
+    ///
+    /// A binding is shadowed when a callback of the view handles its key,
+    /// since the keymap only gets keys ignored by the view tree.
+    ///
+    /// View groups should forward the call to their focused child.
+    ///
+    /// Default implementation does nothing.
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        let _ = (keymap, conflicts);
+    }
+
     /// Returns the type of this view.
     ///
     /// Useful when you have a `&dyn View`.
@@ -1,7 +1,8 @@
 use crate::direction::Direction;
 use crate::event::{AnyCb, Event, EventResult};
+use crate::keymap::{Conflict, Keymap};
 use crate::rect::Rect;
-use crate::view::{Selector, View};
+use crate::view::{KeyHelp, Selector, View};
 use crate::Printer;
 use crate::Vec2;
 
@@ -99,6 +100,20 @@ pub trait ViewWrapper: 'static {
         self.with_view(|v| v.important_area(size))
             .unwrap_or_else(|| Rect::from((0, 0)))
     }
+
+    /// Wraps the `key_help` method.
+    fn wrap_key_help(&self, help: &mut Vec<KeyHelp>) {
+        self.with_view(|v| v.key_help(help));
+    }
+
+    /// Wraps the `keymap_conflicts` method.
+    fn wrap_keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        self.with_view(|v| v.keymap_conflicts(keymap, conflicts));
+    }
 }
 
 // The main point of implementing ViewWrapper is to have View for free.
@@ -142,6 +157,18 @@ impl<T: ViewWrapper> View for T {
     fn important_area(&self, size: Vec2) -> Rect {
         self.wrap_important_area(size)
     }
+
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        self.wrap_key_help(help)
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        self.wrap_keymap_conflicts(keymap, conflicts)
+    }
 }
 
 /// Convenient macro to implement the [`ViewWrapper`] trait.
@@ -25,6 +25,9 @@ pub struct Button {
     enabled: bool,
     last_size: Vec2,
 
+    // `true` if the mouse is over the label.
+    hovered: bool,
+
     invalidated: bool,
 }
 
@@ -59,6 +62,7 @@ impl Button {
             callback: Callback::from_fn(cb),
             enabled: true,
             last_size: Vec2::zero(),
+            hovered: false,
             invalidated: true,
         }
     }
@@ -137,6 +141,8 @@ impl View for Button {
             ColorStyle::secondary()
         } else if printer.focused {
             ColorStyle::highlight()
+        } else if self.hovered {
+            ColorStyle::highlight_inactive()
         } else {
             ColorStyle::primary()
         };
@@ -160,14 +166,27 @@ impl View for Button {
     }
 
     fn on_event(&mut self, event: Event) -> EventResult {
+        let width = self.label.width();
+        let self_offset = HAlign::Center.get_offset(width, self.last_size.x);
+
+        if let Event::Mouse {
+            event: MouseEvent::Move,
+            position,
+            offset,
+        } = event
+        {
+            self.hovered = self.enabled
+                && position
+                    .fits_in_rect(offset + (self_offset, 0), self.req_size());
+            return EventResult::Ignored;
+        }
+
         if !self.enabled {
             return EventResult::Ignored;
         }
 
         // eprintln!("{:?}", event);
         // eprintln!("{:?}", self.req_size());
-        let width = self.label.width();
-        let self_offset = HAlign::Center.get_offset(width, self.last_size.x);
         match event {
             // 10 is the ascii code for '\n', that is the return key
             Event::Key(Key::Enter) => {
@@ -141,6 +141,11 @@ impl View for Checkbox {
         Vec2::new(3, 1)
     }
 
+    fn needs_relayout(&self) -> bool {
+        // Our size never changes.
+        false
+    }
+
     fn take_focus(&mut self, _: Direction) -> bool {
         self.enabled
     }
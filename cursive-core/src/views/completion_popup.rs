@@ -0,0 +1,329 @@
+use crate::direction::Direction;
+use crate::event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use crate::theme::ColorStyle;
+use crate::view::View;
+use crate::{Printer, Vec2};
+use std::cell::RefCell;
+use std::cmp::min;
+use std::rc::Rc;
+use unicode_width::UnicodeWidthStr;
+
+/// Maximum number of candidates visible at once in the popup.
+const MAX_VISIBLE: usize = 8;
+
+/// Candidate offered by an [`EditView`] completion provider.
+///
+/// [`EditView`]: crate::views::EditView
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Completion {
+    /// Text shown in the popup.
+    pub label: String,
+
+    /// Text inserted when this completion is accepted.
+    pub text: String,
+
+    /// Byte offset where the completed part of the content starts.
+    ///
+    /// Accepting this completion replaces the content between `start` and
+    /// the cursor with `text`.
+    pub start: usize,
+}
+
+impl Completion {
+    /// Creates a completion replacing the content from `start` to the
+    /// cursor with `text`.
+    ///
+    /// The label defaults to `text`.
+    pub fn new<S: Into<String>>(start: usize, text: S) -> Self {
+        let text = text.into();
+        Completion {
+            label: text.clone(),
+            text,
+            start,
+        }
+    }
+
+    /// Sets the text shown in the popup.
+    ///
+    /// Chainable variant.
+    pub fn label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = label.into();
+        self
+    }
+}
+
+/// How completion candidates are matched against the typed text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CompletionMode {
+    /// Candidates must start with the typed text.
+    #[default]
+    Prefix,
+
+    /// The typed characters must appear in order in the candidate, ignoring
+    /// case. Candidates with closer matches come first.
+    Fuzzy,
+}
+
+impl CompletionMode {
+    /// Returns a score for `candidate` if it matches `typed`.
+    ///
+    /// Lower scores are better matches.
+    fn score(self, typed: &str, candidate: &str) -> Option<usize> {
+        match self {
+            CompletionMode::Prefix => {
+                if candidate.starts_with(typed) {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            CompletionMode::Fuzzy => fuzzy_score(typed, candidate),
+        }
+    }
+
+    /// Keeps the candidates matching the text they complete, best matches
+    /// first.
+    ///
+    /// Each candidate completes the text from its `start` to `cursor`.
+    pub(crate) fn filter(
+        self,
+        content: &str,
+        cursor: usize,
+        candidates: Vec<Completion>,
+    ) -> Vec<Completion> {
+        let mut scored: Vec<_> = candidates
+            .into_iter()
+            .filter_map(|c| {
+                let typed = content.get(c.start..cursor)?;
+                Some((self.score(typed, &c.text)?, c))
+            })
+            .collect();
+        // Stable sort: equal scores keep the provider's order.
+        scored.sort_by_key(|&(score, _)| score);
+        scored.into_iter().map(|(_, c)| c).collect()
+    }
+}
+
+/// Matches the characters of `typed`, in order, inside `candidate`.
+///
+/// The score is the position of the first match plus the number of
+/// characters skipped after it.
+fn fuzzy_score(typed: &str, candidate: &str) -> Option<usize> {
+    let mut chars = candidate.chars().enumerate();
+    let mut first = None;
+    let mut last = 0;
+    for t in typed.chars().flat_map(char::to_lowercase) {
+        let (i, _) = chars
+            .by_ref()
+            .find(|&(_, c)| c.to_lowercase().any(|c| c == t))?;
+        first.get_or_insert(i);
+        last = i;
+    }
+
+    Some(match first {
+        Some(first) => first + (last - first + 1 - typed.chars().count()),
+        None => 0,
+    })
+}
+
+/// Candidates currently offered by an `EditView`.
+///
+/// Shared between the view and its popup.
+#[derive(Default)]
+pub(crate) struct CompletionState {
+    /// Matching candidates, best first.
+    pub candidates: Vec<Completion>,
+
+    /// Index of the selected candidate.
+    pub selected: usize,
+
+    /// Set by the popup when the selected candidate is accepted.
+    pub accepted: bool,
+}
+
+impl CompletionState {
+    /// Returns the selected candidate, if any.
+    pub fn selection(&self) -> Option<&Completion> {
+        self.candidates.get(self.selected)
+    }
+
+    /// Removes all candidates.
+    pub fn clear(&mut self) {
+        self.candidates.clear();
+        self.selected = 0;
+        self.accepted = false;
+    }
+}
+
+/// Dropdown showing completion candidates under an `EditView`.
+///
+/// Up/Down change the selection, Tab/Enter accept it, and Esc closes the
+/// popup. Other events close the popup and are sent back to the `EditView`.
+pub(crate) struct CompletionPopup {
+    state: Rc<RefCell<CompletionState>>,
+}
+
+impl CompletionPopup {
+    /// Creates a popup for the given candidates.
+    pub fn new(state: Rc<RefCell<CompletionState>>) -> Self {
+        CompletionPopup { state }
+    }
+
+    fn height(&self) -> usize {
+        min(self.state.borrow().candidates.len(), MAX_VISIBLE)
+    }
+
+    /// Index of the first visible candidate.
+    fn first_visible(&self) -> usize {
+        let height = self.height();
+        let selected = self.state.borrow().selected;
+        (selected + 1).saturating_sub(height)
+    }
+
+    /// Closes the popup, then runs `event` on the view below.
+    fn forward(event: Event) -> EventResult {
+        EventResult::with_cb(move |s| {
+            s.pop_layer();
+            s.on_event(event.clone());
+        })
+    }
+
+    /// Accepts the selected candidate.
+    fn accept(&mut self, event: Event) -> EventResult {
+        self.state.borrow_mut().accepted = true;
+        // The `EditView` will notice the accepted candidate when it gets
+        // this event.
+        Self::forward(event)
+    }
+
+    fn dismiss(&mut self) -> EventResult {
+        self.state.borrow_mut().clear();
+        EventResult::with_cb(|s| {
+            s.pop_layer();
+        })
+    }
+}
+
+impl View for CompletionPopup {
+    fn draw(&self, printer: &Printer) {
+        let state = self.state.borrow();
+        let first = self.first_visible();
+        let visible = state.candidates.iter().enumerate().skip(first);
+
+        for (y, (i, candidate)) in visible.take(printer.size.y).enumerate() {
+            let style = if i == state.selected {
+                ColorStyle::highlight()
+            } else {
+                ColorStyle::primary()
+            };
+            printer.with_color(style, |printer| {
+                printer.print_hline((0, y), printer.size.x, " ");
+                printer.print((1, y), &candidate.label);
+            });
+        }
+    }
+
+    fn required_size(&mut self, _: Vec2) -> Vec2 {
+        let state = self.state.borrow();
+        let width = state
+            .candidates
+            .iter()
+            .map(|c| c.label.width())
+            .max()
+            .unwrap_or(0);
+        Vec2::new(width + 2, self.height())
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        let len = self.state.borrow().candidates.len();
+        if len == 0 {
+            // The candidates were cleared: there is nothing to select.
+            return self.dismiss();
+        }
+
+        match event {
+            Event::Key(Key::Up)
+            | Event::Mouse {
+                event: MouseEvent::WheelUp,
+                ..
+            } => {
+                let mut state = self.state.borrow_mut();
+                state.selected = (state.selected + len - 1) % len;
+            }
+            Event::Key(Key::Down)
+            | Event::Mouse {
+                event: MouseEvent::WheelDown,
+                ..
+            } => {
+                let mut state = self.state.borrow_mut();
+                state.selected = (state.selected + 1) % len;
+            }
+            Event::Key(Key::Tab) | Event::Key(Key::Enter) => {
+                return self.accept(event);
+            }
+            Event::Key(Key::Esc) => return self.dismiss(),
+            Event::Mouse {
+                event: MouseEvent::Release(MouseButton::Left),
+                position,
+                offset,
+            } => {
+                let size = Vec2::new(usize::MAX, self.height());
+                match position.checked_sub(offset) {
+                    Some(position) if position.fits_in(size) => {
+                        let i = self.first_visible() + position.y;
+                        self.state.borrow_mut().selected = i;
+                        return self.accept(Event::Key(Key::Enter));
+                    }
+                    _ => return self.dismiss(),
+                }
+            }
+            // Other mouse events are only relevant to the popup.
+            Event::Mouse { .. } => (),
+            Event::Refresh | Event::WindowResize => {
+                return EventResult::Ignored;
+            }
+            _ => return Self::forward(event),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn take_focus(&mut self, _: Direction) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matching() {
+        let candidates = vec![
+            Completion::new(0, "Kyoto"),
+            Completion::new(0, "Tokyo"),
+            Completion::new(0, "Toulouse"),
+        ];
+
+        let found = CompletionMode::Fuzzy.filter("tky", 3, candidates.clone());
+        let texts: Vec<_> = found.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["Tokyo"]);
+
+        let found = CompletionMode::Fuzzy.filter("to", 2, candidates.clone());
+        let texts: Vec<_> = found.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["Tokyo", "Toulouse", "Kyoto"]);
+
+        let found = CompletionMode::Prefix.filter("To", 2, candidates);
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn no_candidates() {
+        let state = Rc::new(RefCell::new(CompletionState::default()));
+        let mut popup = CompletionPopup::new(Rc::clone(&state));
+
+        for key in &[Key::Up, Key::Down] {
+            let result = popup.on_event(Event::Key(*key));
+            assert!(result.has_callback());
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::align::*;
 use crate::direction::{Absolute, Direction, Relative};
 use crate::event::{AnyCb, Event, EventResult, Key};
+use crate::keymap::{Conflict, Keymap};
 use crate::rect::Rect;
 use crate::theme::ColorStyle;
-use crate::view::{IntoBoxedView, Margins, Selector, View};
+use crate::view::{IntoBoxedView, KeyHelp, Margins, Selector, View};
 use crate::views::{BoxedView, Button, DummyView, LastSizeView, TextView};
 use crate::Cursive;
 use crate::Printer;
@@ -342,6 +343,7 @@ impl Dialog {
     /// Chainable variant.
     pub fn set_padding(&mut self, padding: Margins) {
         self.padding = padding;
+        self.invalidate();
     }
 
     /// Sets the top padding in the dialog (under the title).
@@ -352,6 +354,7 @@ impl Dialog {
     /// Sets the top padding in the dialog (under the title).
     pub fn set_padding_top(&mut self, padding: usize) {
         self.padding.top = padding;
+        self.invalidate();
     }
 
     /// Sets the bottom padding in the dialog (under buttons).
@@ -362,6 +365,7 @@ impl Dialog {
     /// Sets the bottom padding in the dialog (under buttons).
     pub fn set_padding_bottom(&mut self, padding: usize) {
         self.padding.bottom = padding;
+        self.invalidate();
     }
 
     /// Sets the left padding in the dialog.
@@ -372,6 +376,7 @@ impl Dialog {
     /// Sets the left padding in the dialog.
     pub fn set_padding_left(&mut self, padding: usize) {
         self.padding.left = padding;
+        self.invalidate();
     }
 
     /// Sets the right padding in the dialog.
@@ -382,6 +387,7 @@ impl Dialog {
     /// Sets the right padding in the dialog.
     pub fn set_padding_right(&mut self, padding: usize) {
         self.padding.right = padding;
+        self.invalidate();
     }
 
     /// Returns an iterator on this buttons for this dialog.
@@ -713,6 +719,16 @@ impl View for Dialog {
     }
 
     fn on_event(&mut self, event: Event) -> EventResult {
+        if event.is_mouse_move() {
+            // The content and every button need to know where the mouse is.
+            let offset = (self.padding + self.borders).top_left();
+            let result = self.content.on_event(event.relativized(offset));
+            return self.buttons.iter_mut().fold(result, |result, button| {
+                let offset = button.offset.get();
+                result.and(button.button.on_event(event.relativized(offset)))
+            });
+        }
+
         // First: some mouse events can instantly change the focus.
         self.check_focus_grab(&event);
 
@@ -784,4 +800,20 @@ impl View for Dialog {
     fn needs_relayout(&self) -> bool {
         self.invalidated || self.content.needs_relayout()
     }
+
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        if self.focus == DialogFocus::Content {
+            self.content.key_help(help);
+        }
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        if self.focus == DialogFocus::Content {
+            self.content.keymap_conflicts(keymap, conflicts);
+        }
+    }
 }
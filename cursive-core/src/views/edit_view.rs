@@ -1,12 +1,28 @@
 use crate::direction::Direction;
-use crate::event::{Callback, Event, EventResult, Key, MouseEvent};
+use crate::event::{
+    Callback, Event, EventResult, Key, MouseButton, MouseEvent,
+};
 use crate::rect::Rect;
-use crate::theme::{ColorStyle, Effect};
+use crate::theme::{ColorStyle, Effect, PaletteColor};
+use crate::utils::clipboard::Clipboard;
 use crate::utils::lines::simple::{simple_prefix, simple_suffix};
-use crate::view::View;
+use crate::utils::markup::StyledString;
+use crate::utils::{
+    draw_placeholder, words, EditHistory, InputHistory, TextEdit,
+};
+use crate::view::{
+    is_motion_key, is_redo, run_vi_command, EditAction, EditKeymap,
+    OnViModeChange, Position, ViCommand, ViEditor, ViInput, ViMode, ViState,
+    View,
+};
+use crate::views::completion_popup::{CompletionPopup, CompletionState};
+use crate::views::{Completion, CompletionMode};
 use crate::Vec2;
 use crate::{Cursive, Printer, With};
-use std::cell::RefCell;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::cmp::{max, min};
+use std::ops::Range;
 use std::rc::Rc;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
@@ -22,8 +38,46 @@ pub type OnEdit = dyn Fn(&mut Cursive, &str, usize);
 /// Arguments are the `Cursive` and the content of the input.
 pub type OnSubmit = dyn Fn(&mut Cursive, &str);
 
+/// Closure type for filtering typed characters.
+///
+/// Returns `true` if the character should be accepted.
+pub type CharFilter = dyn Fn(char) -> bool;
+
+/// Closure type for validating the content.
+///
+/// Returns an error message if the content is invalid.
+pub type Validator = dyn Fn(&str) -> Result<(), String>;
+
+/// Closure type for completion providers.
+///
+/// Arguments are the current content of the input and cursor position.
+/// Returns the candidates to offer; they are then matched against the typed
+/// text according to the view's [`CompletionMode`].
+pub type Completer = dyn Fn(&str, usize) -> Vec<Completion>;
+
 /// Input box where the user can enter and edit text.
 ///
+/// Text can be selected with Shift+arrows, or with the mouse, and then cut,
+/// copied or pasted with Ctrl-X, Ctrl-C and Ctrl-V. By default, the clipboard
+/// is shared between all editing views (see [`clipboard`]). Note that Ctrl-C
+/// quits the application by default; clear this global callback to use it
+/// here.
+///
+/// Ctrl-Left/Right move by word. More readline-style bindings are available
+/// with [`EditKeymap::Emacs`].
+///
+/// With an [`InputHistory`], submitted values can be recalled with Up/Down,
+/// or searched with Ctrl-R.
+///
+/// With a completion provider (see [`set_completer`]), matching candidates
+/// are shown in a popup while typing, and the selected one is previewed after
+/// the cursor. Up/Down select a candidate, Tab or Enter accept it, and Esc
+/// closes the popup. Tab also asks for candidates explicitly.
+///
+/// [`set_completer`]: EditView::set_completer
+/// [`clipboard`]: crate::utils::clipboard
+/// [`InputHistory`]: crate::utils::InputHistory
+///
 /// # Examples
 ///
 /// From the [edit example][1].
@@ -89,6 +143,12 @@ pub struct EditView {
     /// Last display length, to know the possible offset range
     last_length: usize,
 
+    /// `true` until the first layout.
+    ///
+    /// Only the display length comes from the layout, and it only changes
+    /// with the size given by the parent.
+    invalidated: bool,
+
     /// Callback when the content is modified.
     ///
     /// Will be called with the current content and the cursor position.
@@ -97,15 +157,87 @@ pub struct EditView {
     /// Callback when <Enter> is pressed.
     on_submit: Option<Rc<OnSubmit>>,
 
+    /// Only typed characters accepted by this filter are inserted.
+    filter: Option<Rc<CharFilter>>,
+
+    /// Checks whether the content is valid.
+    validator: Option<Rc<Validator>>,
+
     /// When `true`, only print `*` instead of the true content.
     secret: bool,
 
     /// Character to fill empty space
     filler: String,
 
+    /// Text shown when the content is empty.
+    placeholder: StyledString,
+
+    /// When `false`, the placeholder is hidden while focused.
+    placeholder_when_focused: bool,
+
     enabled: bool,
 
     style: ColorStyle,
+
+    /// Undo/redo history.
+    history: EditHistory,
+
+    /// Other end of the selection, if any.
+    ///
+    /// The selection spans from here to the cursor.
+    selection_anchor: Option<usize>,
+
+    /// Key bindings for editing commands.
+    keymap: EditKeymap,
+
+    /// Clipboard and kill ring used by cut, copy, paste, kill and yank.
+    clipboard: Clipboard,
+
+    /// `true` if the last event killed some text.
+    ///
+    /// Consecutive kills are merged in the kill ring.
+    killing: bool,
+
+    /// Mode and pending keys of the vi keymap.
+    vi: ViState,
+
+    /// Callback when the vi mode changes.
+    on_vi_mode_change: Option<Rc<OnViModeChange>>,
+
+    /// History of submitted values.
+    input_history: Option<InputHistory>,
+
+    /// Index of the history entry being shown, when browsing the history.
+    history_index: Option<usize>,
+
+    /// Content before browsing the history, restored after the last entry.
+    history_draft: String,
+
+    /// Ongoing reverse search in the history, if any.
+    search: Option<HistorySearch>,
+
+    /// Provides completion candidates.
+    completer: Option<Rc<Completer>>,
+
+    /// How candidates are matched against the typed text.
+    completion_mode: CompletionMode,
+
+    /// Candidates currently offered, shared with the popup.
+    completions: Rc<RefCell<CompletionState>>,
+
+    /// Absolute offset where this view was last drawn.
+    ///
+    /// Used to place the completion popup.
+    last_offset: Cell<Vec2>,
+}
+
+/// State of an incremental reverse search in the input history.
+struct HistorySearch {
+    /// Text searched for.
+    query: String,
+
+    /// Index of the current match in the history, if any.
+    found: Option<usize>,
 }
 
 new_default!(EditView);
@@ -120,13 +252,33 @@ impl EditView {
             cursor: 0,
             offset: 0,
             last_length: 0, // scrollable: false,
+            invalidated: true,
             on_edit: None,
             on_submit: None,
+            filter: None,
+            validator: None,
             max_content_width: None,
             secret: false,
             filler: "_".to_string(),
+            placeholder: StyledString::new(),
+            placeholder_when_focused: true,
             enabled: true,
             style: ColorStyle::secondary(),
+            history: EditHistory::new(),
+            selection_anchor: None,
+            keymap: EditKeymap::Default,
+            clipboard: Clipboard::global(),
+            killing: false,
+            vi: ViState::new(),
+            on_vi_mode_change: None,
+            input_history: None,
+            history_index: None,
+            history_draft: String::new(),
+            search: None,
+            completer: None,
+            completion_mode: CompletionMode::Prefix,
+            completions: Rc::new(RefCell::new(CompletionState::default())),
+            last_offset: Cell::new(Vec2::zero()),
         }
     }
 
@@ -201,6 +353,57 @@ impl EditView {
         self.with(|s| s.set_style(style))
     }
 
+    /// Sets a placeholder shown when the content is empty.
+    ///
+    /// Unstyled parts of the placeholder use the secondary palette color.
+    /// The placeholder is never part of the content.
+    pub fn set_placeholder<S: Into<StyledString>>(&mut self, placeholder: S) {
+        self.placeholder = placeholder.into();
+    }
+
+    /// Sets a placeholder shown when the content is empty.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::views::EditView;
+    /// let edit = EditView::new().placeholder("user@example.com");
+    /// assert_eq!(edit.get_content().as_str(), "");
+    /// ```
+    pub fn placeholder<S: Into<StyledString>>(self, placeholder: S) -> Self {
+        self.with(|s| s.set_placeholder(placeholder))
+    }
+
+    /// Returns the placeholder shown when the content is empty.
+    pub fn get_placeholder(&self) -> &StyledString {
+        &self.placeholder
+    }
+
+    /// Sets whether the placeholder stays visible while this view is
+    /// focused.
+    ///
+    /// Defaults to `true`.
+    pub fn set_placeholder_when_focused(&mut self, show: bool) {
+        self.placeholder_when_focused = show;
+    }
+
+    /// Sets whether the placeholder stays visible while this view is
+    /// focused.
+    ///
+    /// Chainable variant.
+    pub fn placeholder_when_focused(self, show: bool) -> Self {
+        self.with(|s| s.set_placeholder_when_focused(show))
+    }
+
+    /// Returns `true` if the placeholder should be drawn.
+    fn shows_placeholder(&self, focused: bool) -> bool {
+        self.content.is_empty()
+            && !self.placeholder.is_empty()
+            && (self.placeholder_when_focused || !focused)
+    }
+
     /// Sets a mutable callback to be called whenever the content is modified.
     ///
     /// `callback` will be called with the view
@@ -269,6 +472,433 @@ impl EditView {
         self.with(|v| v.set_on_edit(callback))
     }
 
+    /// Sets a filter for typed characters.
+    ///
+    /// Characters rejected by `filter` will be ignored when typing, and
+    /// removed from pasted text.
+    ///
+    /// Content set with [`set_content`](#method.set_content) is not
+    /// filtered.
+    pub fn set_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(char) -> bool + 'static,
+    {
+        self.filter = Some(Rc::new(filter));
+    }
+
+    /// Sets a filter for typed characters.
+    ///
+    /// Chainable variant. See [`set_filter`](#method.set_filter).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cursive_core::views::EditView;
+    /// // Only accept digits.
+    /// let edit = EditView::new().filter(|c| c.is_ascii_digit());
+    /// ```
+    pub fn filter<F>(self, filter: F) -> Self
+    where
+        F: Fn(char) -> bool + 'static,
+    {
+        self.with(|v| v.set_filter(filter))
+    }
+
+    /// Removes the character filter, if any.
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+    }
+
+    /// Sets a validator for the content.
+    ///
+    /// While `validator` returns an error, the view is drawn with the
+    /// `Invalid` palette color, and `<Enter>` does not submit the content.
+    ///
+    /// Use [`validate`](#method.validate) or
+    /// [`is_valid`](#method.is_valid) to check the content, for example
+    /// from an `on_edit` callback.
+    pub fn set_validator<F>(&mut self, validator: F)
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.validator = Some(Rc::new(validator));
+    }
+
+    /// Sets a validator for the content.
+    ///
+    /// Chainable variant. See [`set_validator`](#method.set_validator).
+    ///
+    /// # Examples
+    ///
+    /// Disable the `Ok` button of a dialog while the port is invalid:
+    ///
+    /// ```
+    /// use cursive_core::traits::Nameable;
+    /// use cursive_core::views::{Dialog, EditView};
+    ///
+    /// let port = EditView::new()
+    ///     .filter(|c| c.is_ascii_digit())
+    ///     .validator(|text| match text.parse::<u16>() {
+    ///         Ok(_) => Ok(()),
+    ///         Err(e) => Err(e.to_string()),
+    ///     })
+    ///     .on_edit(|s, _, _| {
+    ///         let valid = s
+    ///             .call_on_name("port", |v: &mut EditView| v.is_valid())
+    ///             .unwrap_or(false);
+    ///         s.call_on_name("dialog", |d: &mut Dialog| {
+    ///             d.buttons_mut().for_each(|b| b.set_enabled(valid));
+    ///         });
+    ///     });
+    ///
+    /// // The field starts empty, which is not a valid port.
+    /// let valid = port.is_valid();
+    /// let mut dialog =
+    ///     Dialog::around(port.with_name("port")).button("Ok", |s| s.quit());
+    /// dialog.buttons_mut().for_each(|b| b.set_enabled(valid));
+    /// let dialog = dialog.with_name("dialog");
+    /// ```
+    pub fn validator<F>(self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.with(|v| v.set_validator(validator))
+    }
+
+    /// Removes the validator, if any.
+    pub fn clear_validator(&mut self) {
+        self.validator = None;
+    }
+
+    /// Checks the current content with the validator.
+    ///
+    /// Returns `Ok(())` if there is no validator.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.validator {
+            Some(ref validator) => validator(&self.content),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the current content is valid.
+    ///
+    /// See [`validate`](#method.validate).
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Sets the history of submitted values.
+    ///
+    /// When the content is submitted with `<Enter>`, it is added to this
+    /// history. Up and Down then cycle through previous values, and Ctrl-R
+    /// starts an incremental reverse search.
+    ///
+    /// The same history can be given to several views.
+    pub fn set_input_history(&mut self, history: InputHistory) {
+        self.end_history_browsing();
+        self.input_history = Some(history);
+        self.search = None;
+    }
+
+    /// Sets the history of submitted values.
+    ///
+    /// Chainable variant.
+    pub fn input_history(self, history: InputHistory) -> Self {
+        self.with(|v| v.set_input_history(history))
+    }
+
+    /// Returns the history of submitted values, if any.
+    pub fn get_input_history(&self) -> Option<&InputHistory> {
+        self.input_history.as_ref()
+    }
+
+    /// Removes the history of submitted values, if any.
+    pub fn clear_input_history(&mut self) {
+        self.end_history_browsing();
+        self.input_history = None;
+        self.search = None;
+    }
+
+    /// Shows the history entry before the current one.
+    ///
+    /// Returns `None` if there is no such entry.
+    fn history_prev(&mut self) -> Option<Callback> {
+        let history = self.input_history.as_ref()?;
+        let index = match self.history_index {
+            Some(index) => index.checked_sub(1)?,
+            None => {
+                let index = history.len().checked_sub(1)?;
+                self.history_draft = self.content.to_string();
+                index
+            }
+        };
+        let entry = history.get(index)?;
+
+        self.history_index = Some(index);
+        Some(self.show_history_entry(&entry))
+    }
+
+    /// Shows the history entry after the current one.
+    ///
+    /// After the last entry, the content typed before browsing the history
+    /// is restored.
+    ///
+    /// Returns `None` if the history is not being browsed.
+    fn history_next(&mut self) -> Option<Callback> {
+        let history = self.input_history.as_ref()?;
+        let index = self.history_index?;
+        let entry = match history.get(index + 1) {
+            Some(entry) => {
+                self.history_index = Some(index + 1);
+                entry
+            }
+            None => {
+                self.history_index = None;
+                std::mem::take(&mut self.history_draft)
+            }
+        };
+
+        Some(self.show_history_entry(&entry))
+    }
+
+    /// Shows `entry` while browsing the history.
+    ///
+    /// This is not recorded in the undo history: browsing is recorded as a
+    /// single step when it ends.
+    fn show_history_entry(&mut self, entry: &str) -> Callback {
+        self.content = Rc::new(entry.to_string());
+        self.offset = 0;
+        self.selection_anchor = None;
+        self.set_cursor(entry.len());
+
+        self.make_edit_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Stops browsing the history, keeping the entry shown.
+    ///
+    /// Replacing the content typed before browsing with this entry is
+    /// recorded as a single undo step. This must be called before any
+    /// other change to the content.
+    fn end_history_browsing(&mut self) {
+        if self.history_index.take().is_none() {
+            return;
+        }
+
+        let draft = std::mem::take(&mut self.history_draft);
+        if draft != *self.content {
+            let cursor = draft.len();
+            let edit = TextEdit {
+                position: 0,
+                removed: draft,
+                inserted: self.content.to_string(),
+            };
+            self.history.record(edit, cursor, self.cursor, false);
+        }
+    }
+
+    /// Handles an event during a reverse search.
+    ///
+    /// Returns `None` if the event ends the search.
+    fn on_search_event(&mut self, event: &Event) -> Option<EventResult> {
+        let history = self.input_history.as_ref()?;
+        let search = self.search.as_mut()?;
+        match *event {
+            Event::Char(c) => {
+                search.query.push(c);
+                // The current match may still be valid.
+                let before = search.found.map_or(history.len(), |i| i + 1);
+                search.found = history.search(&search.query, before);
+            }
+            Event::CtrlChar('r') => {
+                let before = search.found.unwrap_or_else(|| history.len());
+                if let Some(found) = history.search(&search.query, before) {
+                    search.found = Some(found);
+                }
+            }
+            Event::Key(Key::Backspace) => {
+                search.query.pop();
+                search.found = history.search(&search.query, history.len());
+            }
+            Event::Key(Key::Esc) | Event::CtrlChar('g') => {
+                self.search = None;
+            }
+            _ => return None,
+        }
+        Some(EventResult::Consumed(None))
+    }
+
+    /// Ends the reverse search, replacing the content with the match.
+    fn accept_search(&mut self) -> Callback {
+        let search = self.search.take();
+        let entry = search.and_then(|search| {
+            self.input_history.as_ref()?.get(search.found?)
+        });
+        match entry {
+            Some(entry) => self.replace_range(0, self.content.len(), &entry),
+            None => Callback::dummy(),
+        }
+    }
+
+    /// Sets the completion provider.
+    ///
+    /// `completer` will be called with the view content and the cursor
+    /// position, and should return the candidates for the text before the
+    /// cursor.
+    pub fn set_completer<F>(&mut self, completer: F)
+    where
+        F: Fn(&str, usize) -> Vec<Completion> + 'static,
+    {
+        self.completer = Some(Rc::new(completer));
+    }
+
+    /// Sets the completion provider.
+    ///
+    /// Chainable variant. See [`set_completer`](#method.set_completer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cursive_core::views::{Completion, CompletionMode, EditView};
+    ///
+    /// const COLORS: &[&str] = &["red", "green", "blue", "black"];
+    ///
+    /// // Complete the word before the cursor.
+    /// let edit = EditView::new()
+    ///     .completer(|content, cursor| {
+    ///         let start = content[..cursor].rfind(' ').map_or(0, |i| i + 1);
+    ///         COLORS.iter().map(|c| Completion::new(start, *c)).collect()
+    ///     })
+    ///     .completion_mode(CompletionMode::Fuzzy);
+    /// ```
+    pub fn completer<F>(self, completer: F) -> Self
+    where
+        F: Fn(&str, usize) -> Vec<Completion> + 'static,
+    {
+        self.with(|v| v.set_completer(completer))
+    }
+
+    /// Removes the completion provider, if any.
+    pub fn clear_completer(&mut self) {
+        self.completer = None;
+        self.completions.borrow_mut().clear();
+    }
+
+    /// Sets how candidates are matched against the typed text.
+    ///
+    /// Defaults to `CompletionMode::Prefix`.
+    pub fn set_completion_mode(&mut self, mode: CompletionMode) {
+        self.completion_mode = mode;
+    }
+
+    /// Sets how candidates are matched against the typed text.
+    ///
+    /// Chainable variant.
+    pub fn completion_mode(self, mode: CompletionMode) -> Self {
+        self.with(|v| v.set_completion_mode(mode))
+    }
+
+    /// Asks the completion provider for candidates.
+    ///
+    /// Opens the popup if some candidates match. When `explicit` is `true`
+    /// (on Tab), a single candidate is accepted directly.
+    fn complete(&mut self, explicit: bool) -> EventResult {
+        let completer = match self.completer {
+            Some(ref completer) => Rc::clone(completer),
+            None => return EventResult::Ignored,
+        };
+
+        let candidates = completer(&self.content, self.cursor);
+        let mut candidates = self.completion_mode.filter(
+            &self.content,
+            self.cursor,
+            candidates,
+        );
+        if !explicit {
+            // Only offer candidates once something was typed, and which
+            // would change the content.
+            candidates.retain(|c| {
+                c.start < self.cursor
+                    && c.text != self.content[c.start..self.cursor]
+            });
+        }
+
+        match candidates.len() {
+            0 => {
+                return if explicit {
+                    EventResult::Ignored
+                } else {
+                    EventResult::Consumed(None)
+                }
+            }
+            1 if explicit => {
+                let completion = candidates.pop().unwrap();
+                return EventResult::Consumed(Some(
+                    self.accept_completion(completion),
+                ));
+            }
+            _ => (),
+        }
+
+        self.completions.borrow_mut().candidates = candidates;
+
+        // Show the popup right under the cursor, aligned with the text.
+        let x = self.content[self.offset..self.cursor].width();
+        let offset = self.last_offset.get() + (x, 1);
+        let offset = offset.saturating_sub((1, 0));
+
+        let state = Rc::clone(&self.completions);
+        EventResult::with_cb(move |s| {
+            let current_offset = s.screen().offset();
+            let offset = offset.signed() - current_offset;
+            s.screen_mut().add_layer_at(
+                Position::parent(offset),
+                CompletionPopup::new(Rc::clone(&state)),
+            );
+        })
+    }
+
+    /// Replaces the completed text with the given candidate.
+    fn accept_completion(&mut self, completion: Completion) -> Callback {
+        let start = completion.start;
+        if start > self.cursor || !self.content.is_char_boundary(start) {
+            return Callback::dummy();
+        }
+
+        if let Some(width) = self.max_content_width {
+            let removed = self.content[start..self.cursor].width();
+            if self.content.width() - removed + completion.text.width() > width
+            {
+                return Callback::dummy();
+            }
+        }
+
+        self.replace_range(start, self.cursor, &completion.text)
+    }
+
+    /// Returns the rest of the selected candidate, shown after the cursor.
+    fn completion_preview(&self) -> Option<String> {
+        if self.secret || self.cursor != self.content.len() {
+            return None;
+        }
+
+        let state = self.completions.borrow();
+        let completion = state.selection()?;
+        let typed = self.content.get(completion.start..self.cursor)?;
+        let preview = completion.text.strip_prefix(typed)?;
+        if preview.is_empty() {
+            None
+        } else {
+            Some(preview.to_string())
+        }
+    }
+
+    /// Returns `true` if `c` is accepted by the filter.
+    // `Option::is_none_or` needs Rust 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    fn accepts(&self, c: char) -> bool {
+        self.filter.as_ref().map_or(true, |filter| filter(c))
+    }
+
     /// Sets a mutable callback to be called when `<Enter>` is pressed.
     ///
     /// `callback` will be given the content of the view.
@@ -342,6 +972,8 @@ impl EditView {
 
     /// Replace the entire content of the view with the given one.
     ///
+    /// This clears the undo history.
+    ///
     /// Returns a callback in response to content change.
     ///
     /// You should run this callback with a `&mut Cursive`.
@@ -351,11 +983,31 @@ impl EditView {
 
         self.content = Rc::new(content);
         self.offset = 0;
+        self.history.clear();
+        self.history_index = None;
+        self.selection_anchor = None;
         self.set_cursor(len);
 
         self.make_edit_cb().unwrap_or_else(Callback::dummy)
     }
 
+    /// Replace the entire content of the view with the given one.
+    ///
+    /// Unlike [`set_content`](#method.set_content), this is recorded in the
+    /// undo history as a single step.
+    ///
+    /// Returns a callback in response to content change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn set_content_undoable<S: Into<String>>(
+        &mut self,
+        content: S,
+    ) -> Callback {
+        let len = self.content.len();
+        self.offset = 0;
+        self.replace_range(0, len, &content.into())
+    }
+
     /// Get the current text.
     pub fn get_content(&self) -> Rc<String> {
         Rc::clone(&self.content)
@@ -400,8 +1052,17 @@ impl EditView {
         // It means it'll just return a ref if no one else has a ref,
         // and it will clone it into `self.content` otherwise.
 
+        self.end_history_browsing();
         Rc::make_mut(&mut self.content).insert(self.cursor, ch);
+        self.selection_anchor = None;
+        let cursor = self.cursor;
         self.cursor += ch.len_utf8();
+        self.history.record(
+            TextEdit::insertion(cursor, ch.to_string()),
+            cursor,
+            self.cursor,
+            true,
+        );
 
         self.keep_cursor_in_view();
 
@@ -414,31 +1075,683 @@ impl EditView {
     ///
     /// You should run this callback with a `&mut Cursive`.
     pub fn remove(&mut self, len: usize) -> Callback {
+        let cursor = self.cursor;
+        self.remove_recorded(len, cursor)
+    }
+
+    /// Removes `len` bytes at the cursor, and records it in the history.
+    ///
+    /// `cursor_before` is the cursor position to restore when undoing.
+    fn remove_recorded(
+        &mut self,
+        len: usize,
+        cursor_before: usize,
+    ) -> Callback {
+        self.end_history_browsing();
         let start = self.cursor;
         let end = self.cursor + len;
-        for _ in Rc::make_mut(&mut self.content).drain(start..end) {}
+        let removed: String =
+            Rc::make_mut(&mut self.content).drain(start..end).collect();
+        self.selection_anchor = None;
+        self.history.record(
+            TextEdit::removal(start, removed),
+            cursor_before,
+            start,
+            false,
+        );
 
         self.keep_cursor_in_view();
 
         self.make_edit_cb().unwrap_or_else(Callback::dummy)
     }
 
-    fn make_edit_cb(&self) -> Option<Callback> {
-        self.on_edit.clone().map(|cb| {
-            // Get a new Rc on the content
-            let content = Rc::clone(&self.content);
-            let cursor = self.cursor;
-
-            Callback::from_fn(move |s| {
-                cb(s, &content, cursor);
-            })
-        })
+    /// Reverts the last modification of the content.
+    ///
+    /// Consecutive typed characters are reverted together.
+    ///
+    /// Returns a callback in response to content change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn undo(&mut self) -> Callback {
+        self.end_history_browsing();
+        match self.history.undo() {
+            Some((edits, cursor)) => self.apply_edits(&edits, cursor),
+            None => Callback::dummy(),
+        }
     }
 
-    fn keep_cursor_in_view(&mut self) {
-        // keep cursor in [offset, offset+last_length] by changing offset
-        // so keep offset in [last_length-cursor,cursor]
-        // Also call this on resize,
+    /// Re-applies the last modification reverted by [`undo`](#method.undo).
+    ///
+    /// Returns a callback in response to content change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn redo(&mut self) -> Callback {
+        self.end_history_browsing();
+        match self.history.redo() {
+            Some((edits, cursor)) => self.apply_edits(&edits, cursor),
+            None => Callback::dummy(),
+        }
+    }
+
+    /// Forgets the undo history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Sets the maximum number of undo steps to remember.
+    ///
+    /// A depth of `0` disables the undo history.
+    ///
+    /// Defaults to 100.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// Sets the maximum number of undo steps to remember.
+    ///
+    /// Chainable variant.
+    pub fn history_depth(self, depth: usize) -> Self {
+        self.with(|s| s.set_history_depth(depth))
+    }
+
+    fn apply_edits(&mut self, edits: &[TextEdit], cursor: usize) -> Callback {
+        let content = Rc::make_mut(&mut self.content);
+        for edit in edits {
+            edit.apply(content);
+        }
+        self.offset = 0;
+        self.selection_anchor = None;
+        self.set_cursor(cursor);
+
+        self.make_edit_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Replaces the text between `start` and `end` with `text`.
+    ///
+    /// This is recorded as a single step in the undo history. The cursor is
+    /// moved after the inserted text, and the selection is cleared.
+    ///
+    /// Returns a callback in response to content change.
+    fn replace_range(
+        &mut self,
+        start: usize,
+        end: usize,
+        text: &str,
+    ) -> Callback {
+        self.end_history_browsing();
+        let cursor = self.cursor;
+        let edit = TextEdit {
+            position: start,
+            removed: self.content[start..end].to_string(),
+            inserted: text.to_string(),
+        };
+        edit.apply(Rc::make_mut(&mut self.content));
+        self.history.record(edit, cursor, start + text.len(), false);
+
+        self.selection_anchor = None;
+        self.set_cursor(start + text.len());
+
+        self.make_edit_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Sets the clipboard and kill ring used by this view.
+    ///
+    /// By default, views use [`Clipboard::global()`].
+    pub fn set_clipboard(&mut self, clipboard: Clipboard) {
+        self.clipboard = clipboard;
+    }
+
+    /// Sets the clipboard and kill ring used by this view.
+    ///
+    /// Chainable variant.
+    pub fn clipboard(self, clipboard: Clipboard) -> Self {
+        self.with(|s| s.set_clipboard(clipboard))
+    }
+
+    /// Returns the clipboard and kill ring used by this view.
+    pub fn get_clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
+    /// Sets the key bindings used by this view.
+    ///
+    /// With [`EditKeymap::Vi`], the view starts in normal mode.
+    pub fn set_keymap(&mut self, keymap: EditKeymap) {
+        self.keymap = keymap;
+        self.vi = ViState::new();
+    }
+
+    /// Sets the key bindings used by this view.
+    ///
+    /// Chainable variant.
+    pub fn keymap(self, keymap: EditKeymap) -> Self {
+        self.with(|s| s.set_keymap(keymap))
+    }
+
+    /// Returns the key bindings used by this view.
+    pub fn get_keymap(&self) -> EditKeymap {
+        self.keymap
+    }
+
+    /// Returns the current vi mode, if the keymap is [`EditKeymap::Vi`].
+    pub fn get_vi_mode(&self) -> Option<ViMode> {
+        match self.keymap {
+            EditKeymap::Vi => Some(self.vi.mode()),
+            _ => None,
+        }
+    }
+
+    /// Sets a callback to be called when the vi mode changes.
+    ///
+    /// This can be used to show the mode in a status line.
+    pub fn set_on_vi_mode_change<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut Cursive, ViMode) + 'static,
+    {
+        self.on_vi_mode_change = Some(Rc::new(callback));
+    }
+
+    /// Sets a callback to be called when the vi mode changes.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cursive_core::view::EditKeymap;
+    /// use cursive_core::views::{EditView, TextContent};
+    /// // Show the mode in a status line.
+    /// let status = TextContent::new("-- NORMAL --");
+    ///
+    /// let edit = EditView::new()
+    ///     .keymap(EditKeymap::Vi)
+    ///     .on_vi_mode_change(move |_s, mode| {
+    ///         status.set_content(format!("-- {} --", mode));
+    ///     });
+    /// ```
+    pub fn on_vi_mode_change<F>(self, callback: F) -> Self
+    where
+        F: Fn(&mut Cursive, ViMode) + 'static,
+    {
+        self.with(|s| s.set_on_vi_mode_change(callback))
+    }
+
+    /// Runs a vi command, and switches to the resulting mode.
+    fn run_vi(&mut self, command: ViCommand, count: usize) -> EventResult {
+        self.history.seal();
+        let content = Rc::clone(&self.content);
+        let mode = run_vi_command(self, self.vi.mode(), command, count);
+
+        let mut result = EventResult::Consumed(None);
+        if content != self.content {
+            result = EventResult::Consumed(self.make_edit_cb());
+        }
+        if self.vi.set_mode(mode) {
+            let callback = self
+                .on_vi_mode_change
+                .clone()
+                .map(|cb| Callback::from_fn(move |s| cb(s, mode)));
+            result = result.and(EventResult::Consumed(callback));
+        }
+        result
+    }
+
+    /// Returns the selected byte range, if any.
+    ///
+    /// Returns `None` if the selection is empty.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor?;
+        if anchor == self.cursor {
+            None
+        } else {
+            Some(min(anchor, self.cursor)..max(anchor, self.cursor))
+        }
+    }
+
+    /// Selects the given byte range.
+    ///
+    /// The cursor is moved to the end of the range.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, or not on char boundaries.
+    pub fn set_selection(&mut self, selection: Range<usize>) {
+        assert!(self.content.is_char_boundary(selection.start));
+        assert!(self.content.is_char_boundary(selection.end));
+
+        self.selection_anchor = Some(selection.start);
+        self.set_cursor(selection.end);
+    }
+
+    /// Selects the given byte range.
+    ///
+    /// Chainable variant.
+    pub fn selected(self, selection: Range<usize>) -> Self {
+        self.with(|s| s.set_selection(selection))
+    }
+
+    /// Clears the selection, if any.
+    ///
+    /// The content is not modified.
+    pub fn clear_selection(&mut self) {
+        self.selection_anchor = None;
+    }
+
+    /// Returns the selected text, if any.
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|selection| &self.content[selection])
+    }
+
+    /// Copies the selected text to the clipboard.
+    ///
+    /// Does nothing if the selection is empty.
+    pub fn copy(&self) {
+        if let Some(text) = self.selected_text() {
+            self.clipboard.set_content(text);
+        }
+    }
+
+    /// Moves the selected text to the clipboard.
+    ///
+    /// Returns a callback in response to content change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn cut(&mut self) -> Callback {
+        match self.selection() {
+            Some(selection) => {
+                self.copy();
+                self.replace_range(selection.start, selection.end, "")
+            }
+            None => Callback::dummy(),
+        }
+    }
+
+    /// Inserts the clipboard content at the cursor position.
+    ///
+    /// Replaces the selection, if any. Newlines are removed.
+    ///
+    /// Returns a callback in response to content change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn paste(&mut self) -> Callback {
+        self.insert_text(&self.clipboard.get_content())
+    }
+
+    /// Inserts the last killed text at the cursor position.
+    fn yank(&mut self) -> Callback {
+        match self.clipboard.yank() {
+            Some(text) => self.insert_text(&text),
+            None => Callback::dummy(),
+        }
+    }
+
+    /// Removes the text between `start` and `end`, and adds it to the kill
+    /// ring.
+    ///
+    /// If `merge` is `true`, the text is added to the last kill ring entry.
+    fn kill(&mut self, start: usize, end: usize, merge: bool) -> Callback {
+        if start == end {
+            self.killing = merge;
+            return Callback::dummy();
+        }
+
+        let text = &self.content[start..end];
+        if !merge {
+            self.clipboard.kill(text);
+        } else if end == self.cursor {
+            self.clipboard.kill_prepend(text);
+        } else {
+            self.clipboard.kill_append(text);
+        }
+        self.killing = true;
+
+        self.replace_range(start, end, "")
+    }
+
+    /// Inserts `text` at the cursor position, replacing the selection.
+    ///
+    /// Newlines and characters rejected by the filter are removed.
+    fn insert_text(&mut self, text: &str) -> Callback {
+        let text: String = text
+            .chars()
+            .filter(|&c| c != '\n' && c != '\r' && self.accepts(c))
+            .collect();
+        let selection = self.selection().unwrap_or(self.cursor..self.cursor);
+
+        if let Some(width) = self.max_content_width {
+            let removed = self.content[selection.clone()].width();
+            if self.content.width() - removed + text.width() > width {
+                // ABORT
+                return Callback::dummy();
+            }
+        }
+
+        self.replace_range(selection.start, selection.end, &text)
+    }
+
+    /// Returns the new cursor position after pressing the given key.
+    ///
+    /// Returns `None` if `key` doesn't move the cursor.
+    fn cursor_motion(&self, key: Key) -> Option<usize> {
+        match key {
+            Key::Home => Some(0),
+            Key::End => Some(self.content.len()),
+            Key::Left if self.cursor > 0 => {
+                let len = self.content[..self.cursor]
+                    .graphemes(true)
+                    .last()
+                    .unwrap()
+                    .len();
+                Some(self.cursor - len)
+            }
+            Key::Right if self.cursor < self.content.len() => {
+                let len = self.content[self.cursor..]
+                    .graphemes(true)
+                    .next()
+                    .unwrap()
+                    .len();
+                Some(self.cursor + len)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the cursor position targeted by the given action.
+    ///
+    /// For kills, the text between the cursor and this position is removed.
+    fn action_target(&self, action: EditAction) -> usize {
+        let content = &self.content;
+        match action {
+            EditAction::WordLeft | EditAction::KillWordLeft => {
+                words::prev_word_start(content, self.cursor).unwrap_or(0)
+            }
+            EditAction::WordRight | EditAction::KillWordRight => {
+                words::next_word_end(content, self.cursor)
+                    .unwrap_or_else(|| content.len())
+            }
+            EditAction::LineStart | EditAction::KillLineStart => 0,
+            EditAction::LineEnd | EditAction::KillLineEnd => content.len(),
+            EditAction::Yank => self.cursor,
+        }
+    }
+
+    /// Returns the byte offset under the given position.
+    fn offset_at(&self, x: usize) -> usize {
+        self.offset + simple_prefix(&self.content[self.offset..], x).length
+    }
+
+    /// Handles an event, without completion.
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        if self.search.is_some() {
+            match self.on_search_event(&event) {
+                Some(result) => return result,
+                None => {
+                    // Any other event ends the search and is then processed
+                    // normally.
+                    let cb = self.accept_search();
+                    return EventResult::Consumed(Some(cb))
+                        .and(self.handle_event(event));
+                }
+            }
+        }
+
+        // Enter still submits in normal mode.
+        let event = match self.keymap {
+            EditKeymap::Vi if event != Event::Key(Key::Enter) => {
+                match self.vi.on_event(event) {
+                    ViInput::Pass(event) => event,
+                    ViInput::Consumed => return EventResult::Consumed(None),
+                    ViInput::Command(command, count) => {
+                        return self.run_vi(command, count);
+                    }
+                }
+            }
+            _ => event,
+        };
+
+        match event {
+            Event::Char(_) => (),
+            // Anything but typing ends the current undo group.
+            _ => self.history.seal(),
+        }
+
+        // Shift+motion extends the selection.
+        let (event, selecting) = match event {
+            Event::Shift(key) if is_motion_key(key) => (Event::Key(key), true),
+            Event::CtrlShift(key) if is_motion_key(key) => {
+                (Event::Ctrl(key), true)
+            }
+            event => (event, false),
+        };
+        let action = self.keymap.action(&event);
+        let merge_kill = std::mem::replace(&mut self.killing, false);
+
+        let motion = match (action, &event) {
+            (Some(action), _) if action.is_motion() => {
+                Some(self.action_target(action))
+            }
+            (None, &Event::Key(key)) => self.cursor_motion(key),
+            _ => None,
+        };
+        if let Some(cursor) = motion {
+            if !selecting {
+                self.selection_anchor = None;
+            } else if self.selection_anchor.is_none() {
+                self.selection_anchor = Some(self.cursor);
+            }
+            self.set_cursor(cursor);
+            return EventResult::Consumed(self.make_edit_cb());
+        }
+
+        match action {
+            Some(EditAction::Yank) => {
+                return EventResult::Consumed(Some(self.yank()));
+            }
+            Some(action) if action.is_kill() => {
+                let target = self.action_target(action);
+                let (start, end) =
+                    (min(self.cursor, target), max(self.cursor, target));
+                return EventResult::Consumed(Some(
+                    self.kill(start, end, merge_kill),
+                ));
+            }
+            _ => (),
+        }
+
+        match event {
+            Event::Char(ch) if !self.accepts(ch) => {
+                return EventResult::Consumed(None);
+            }
+            Event::Char(ch) => {
+                return EventResult::Consumed(Some(match self.selection() {
+                    Some(_) => self.insert_text(ch.encode_utf8(&mut [0; 4])),
+                    None => self.insert(ch),
+                }));
+            }
+            Event::CtrlChar('z') => {
+                return EventResult::Consumed(Some(self.undo()));
+            }
+            ref event if is_redo(event) => {
+                return EventResult::Consumed(Some(self.redo()));
+            }
+            Event::CtrlChar('x') => {
+                return EventResult::Consumed(Some(self.cut()));
+            }
+            Event::CtrlChar('c') => {
+                self.copy();
+                return EventResult::Consumed(None);
+            }
+            Event::CtrlChar('v') => {
+                return EventResult::Consumed(Some(self.paste()));
+            }
+            Event::Paste(text) => {
+                return EventResult::Consumed(Some(self.insert_text(&text)));
+            }
+            Event::Key(Key::Backspace) | Event::Key(Key::Del)
+                if self.selection().is_some() =>
+            {
+                let selection = self.selection().unwrap();
+                return EventResult::Consumed(Some(self.replace_range(
+                    selection.start,
+                    selection.end,
+                    "",
+                )));
+            }
+            Event::Key(Key::Backspace) if self.cursor > 0 => {
+                let len = self.content[..self.cursor]
+                    .graphemes(true)
+                    .last()
+                    .unwrap()
+                    .len();
+                let cursor = self.cursor;
+                self.cursor -= len;
+                return EventResult::Consumed(Some(
+                    self.remove_recorded(len, cursor),
+                ));
+            }
+            Event::Key(Key::Del) if self.cursor < self.content.len() => {
+                let len = self.content[self.cursor..]
+                    .graphemes(true)
+                    .next()
+                    .unwrap()
+                    .len();
+                return EventResult::Consumed(Some(self.remove(len)));
+            }
+            Event::Key(Key::Enter)
+                if self.on_submit.is_some() && !self.is_valid() =>
+            {
+                return EventResult::Consumed(None);
+            }
+            Event::Key(Key::Enter) if self.on_submit.is_some() => {
+                if let Some(ref history) = self.input_history {
+                    history.push(self.content.as_str());
+                }
+                self.end_history_browsing();
+
+                let cb = self.on_submit.clone().unwrap();
+                let content = Rc::clone(&self.content);
+                return EventResult::with_cb(move |s| {
+                    cb(s, &content);
+                });
+            }
+            Event::Key(Key::Up) => {
+                return match self.history_prev() {
+                    Some(cb) => EventResult::Consumed(Some(cb)),
+                    None => EventResult::Ignored,
+                };
+            }
+            Event::Key(Key::Down) => {
+                return match self.history_next() {
+                    Some(cb) => EventResult::Consumed(Some(cb)),
+                    None => EventResult::Ignored,
+                };
+            }
+            Event::CtrlChar('r') if self.input_history.is_some() => {
+                self.search = Some(HistorySearch {
+                    query: String::new(),
+                    found: None,
+                });
+                return EventResult::Consumed(None);
+            }
+            Event::Mouse {
+                event: MouseEvent::Press(button),
+                position,
+                offset,
+            } if position.fits_in_rect(offset, (self.last_length, 1)) => {
+                if let Some(position) = position.checked_sub(offset) {
+                    self.cursor = self.offset_at(position.x);
+                }
+
+                // This may be the start of a drag.
+                self.selection_anchor = match button {
+                    MouseButton::Left => Some(self.cursor),
+                    _ => None,
+                };
+            }
+            Event::Mouse {
+                event: MouseEvent::DoubleClick(MouseButton::Left),
+                position,
+                offset,
+            } if position.fits_in_rect(offset, (self.last_length, 1)) => {
+                // Select the word under the cursor.
+                let (start, end) = words::word_at(&self.content, self.cursor);
+                self.selection_anchor = Some(start);
+                self.cursor = end;
+            }
+            Event::Mouse {
+                event: MouseEvent::TripleClick(MouseButton::Left),
+                position,
+                offset,
+            } if position.fits_in_rect(offset, (self.last_length, 1)) => {
+                self.selection_anchor = Some(0);
+                self.cursor = self.content.len();
+            }
+            Event::Mouse {
+                event: MouseEvent::Hold(MouseButton::Left),
+                position,
+                offset,
+            } if self.selection_anchor.is_some() => {
+                let x = position.saturating_sub(offset).x;
+                let cursor = self.offset_at(x);
+                self.set_cursor(cursor);
+            }
+            Event::Mouse {
+                event: MouseEvent::Release(MouseButton::Left),
+                ..
+            } if self.selection_anchor.is_some() => {
+                if self.selection().is_none() {
+                    self.selection_anchor = None;
+                }
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        // self.keep_cursor_in_view();
+
+        EventResult::Consumed(self.make_edit_cb())
+    }
+
+    /// Draws the reverse search prompt and current match.
+    fn draw_search(&self, printer: &Printer, search: &HistorySearch) {
+        let entry = search
+            .found
+            .and_then(|i| self.input_history.as_ref()?.get(i))
+            .unwrap_or_default();
+        let prompt = format!("(reverse-i-search)`{}': ", search.query);
+
+        printer.with_color(self.style, |printer| {
+            printer.with_effect(Effect::Reverse, |printer| {
+                printer.print_hline((0, 0), printer.size.x, " ");
+                printer.print((0, 0), &prompt);
+                if self.secret {
+                    printer.print_hline(
+                        (prompt.width(), 0),
+                        entry.width(),
+                        "*",
+                    );
+                } else {
+                    printer.print((prompt.width(), 0), &entry);
+                }
+            });
+        });
+    }
+
+    fn make_edit_cb(&self) -> Option<Callback> {
+        self.on_edit.clone().map(|cb| {
+            // Get a new Rc on the content
+            let content = Rc::clone(&self.content);
+            let cursor = self.cursor;
+
+            Callback::from_fn(move |s| {
+                cb(s, &content, cursor);
+            })
+        })
+    }
+
+    fn keep_cursor_in_view(&mut self) {
+        // keep cursor in [offset, offset+last_length] by changing offset
+        // so keep offset in [last_length-cursor,cursor]
+        // Also call this on resize,
         // but right now it is an event like any other
         if self.cursor < self.offset {
             self.offset = self.cursor;
@@ -486,6 +1799,93 @@ impl EditView {
     }
 }
 
+impl ViEditor for EditView {
+    fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
+    fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn move_cursor(&mut self, cursor: usize) {
+        self.set_cursor(cursor);
+    }
+
+    fn anchor(&self) -> Option<usize> {
+        self.selection_anchor
+    }
+
+    fn set_anchor(&mut self, anchor: Option<usize>) {
+        self.selection_anchor = anchor;
+    }
+
+    fn is_multiline(&self) -> bool {
+        false
+    }
+
+    fn line_count(&self) -> usize {
+        1
+    }
+
+    fn line_at(&self, _: usize) -> usize {
+        0
+    }
+
+    fn line_range(&self, _: usize) -> (usize, usize) {
+        (0, self.content.len())
+    }
+
+    fn slice(&self, start: usize, end: usize) -> Cow<'_, str> {
+        Cow::Borrowed(&self.content[start..end])
+    }
+
+    fn row_target(&self, _: usize, _: bool) -> Option<usize> {
+        None
+    }
+
+    fn replace_text(&mut self, start: usize, end: usize, text: &str) {
+        self.replace_range(start, end, text);
+    }
+
+    fn begin_change(&mut self, start: usize, end: usize, text: &str) {
+        self.end_history_browsing();
+        let cursor = self.cursor;
+        let edit = TextEdit {
+            position: start,
+            removed: self.content[start..end].to_string(),
+            inserted: text.to_string(),
+        };
+        edit.apply(Rc::make_mut(&mut self.content));
+        self.history.record_change(edit, cursor, start + text.len());
+
+        self.selection_anchor = None;
+        self.set_cursor(start + text.len());
+    }
+
+    fn undo_step(&mut self) -> bool {
+        self.end_history_browsing();
+        match self.history.undo() {
+            Some((edits, cursor)) => {
+                self.apply_edits(&edits, cursor);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn redo_step(&mut self) -> bool {
+        self.end_history_browsing();
+        match self.history.redo() {
+            Some((edits, cursor)) => {
+                self.apply_edits(&edits, cursor);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 /// Returns a `&str` with `length` characters `*`.
 ///
 /// Only works for small `length` (1 or 2).
@@ -503,8 +1903,20 @@ impl View for EditView {
             self.last_length, printer.size.x
         );
 
+        self.last_offset.set(printer.offset);
+
+        if let Some(ref search) = self.search {
+            self.draw_search(printer, search);
+            return;
+        }
+
         let width = self.content.width();
-        printer.with_color(self.style, |printer| {
+        let style = if self.is_valid() {
+            self.style
+        } else {
+            ColorStyle::new(PaletteColor::Invalid, PaletteColor::View)
+        };
+        printer.with_color(style, |printer| {
             let effect = if self.enabled && printer.enabled {
                 Effect::Reverse
             } else {
@@ -562,10 +1974,65 @@ impl View for EditView {
                 }
             });
 
+            // Highlight the visible part of the selection.
+            if let Some(selection) = self.selection() {
+                let start = max(selection.start, self.offset);
+                let end = max(selection.end, start);
+                let x = self.content[self.offset..start].width();
+                let selected = &self.content[start..end];
+
+                let style = if printer.focused {
+                    ColorStyle::highlight()
+                } else {
+                    ColorStyle::highlight_inactive()
+                };
+                printer.with_color(style, |printer| {
+                    if self.secret {
+                        printer.print_hline((x, 0), selected.width(), "*");
+                    } else {
+                        printer.print((x, 0), selected);
+                    }
+                });
+            }
+
+            // Preview the selected completion after the content.
+            let preview = self.completion_preview();
+            if let Some(ref preview) = preview {
+                let x = self.content[self.offset..].width();
+                let style =
+                    ColorStyle::new(style.front, PaletteColor::Primary);
+                printer.with_color(style, |printer| {
+                    printer.with_effect(Effect::Reverse, |printer| {
+                        printer.print((x, 0), preview);
+                    });
+                });
+            }
+
+            // Like in `TextArea`, the placeholder uses the secondary color,
+            // so it doesn't look like real input.
+            let placeholder = self.shows_placeholder(printer.focused);
+            if placeholder {
+                draw_placeholder(
+                    printer,
+                    &self.placeholder,
+                    ColorStyle::secondary(),
+                );
+            }
+
             // Now print cursor
-            if printer.focused {
+            if printer.focused && placeholder {
+                // Show the cursor over the first character of the
+                // placeholder.
+                let c = self.placeholder.source().graphemes(true).next();
+                printer.print((0, 0), c.unwrap_or(&self.filler));
+            } else if printer.focused {
                 let c: &str = if self.cursor == self.content.len() {
-                    &self.filler
+                    match preview {
+                        Some(ref preview) => {
+                            preview.graphemes(true).next().unwrap()
+                        }
+                        None => &self.filler,
+                    }
                 } else {
                     // Get the char from the string... Is it so hard?
                     let selected = self.content[self.cursor..]
@@ -591,6 +2058,12 @@ impl View for EditView {
 
     fn layout(&mut self, size: Vec2) {
         self.last_length = size.x;
+        self.invalidated = false;
+    }
+
+    fn needs_relayout(&self) -> bool {
+        // Our size doesn't depend on the content.
+        self.invalidated
     }
 
     fn take_focus(&mut self, _: Direction) -> bool {
@@ -601,79 +2074,40 @@ impl View for EditView {
         if !self.enabled {
             return EventResult::Ignored;
         }
-        match event {
-            Event::Char(ch) => {
-                return EventResult::Consumed(Some(self.insert(ch)));
-            }
-            // TODO: handle ctrl-key?
-            Event::Key(Key::Home) => self.set_cursor(0),
-            Event::Key(Key::End) => {
-                // When possible, NLL to the rescue!
-                let len = self.content.len();
-                self.set_cursor(len);
-            }
-            Event::Key(Key::Left) if self.cursor > 0 => {
-                let len = self.content[..self.cursor]
-                    .graphemes(true)
-                    .last()
-                    .unwrap()
-                    .len();
-                let cursor = self.cursor - len;
-                self.set_cursor(cursor);
-            }
-            Event::Key(Key::Right) if self.cursor < self.content.len() => {
-                let len = self.content[self.cursor..]
-                    .graphemes(true)
-                    .next()
-                    .unwrap()
-                    .len();
-                let cursor = self.cursor + len;
-                self.set_cursor(cursor);
-            }
-            Event::Key(Key::Backspace) if self.cursor > 0 => {
-                let len = self.content[..self.cursor]
-                    .graphemes(true)
-                    .last()
-                    .unwrap()
-                    .len();
-                self.cursor -= len;
-                return EventResult::Consumed(Some(self.remove(len)));
-            }
-            Event::Key(Key::Del) if self.cursor < self.content.len() => {
-                let len = self.content[self.cursor..]
-                    .graphemes(true)
-                    .next()
-                    .unwrap()
-                    .len();
-                return EventResult::Consumed(Some(self.remove(len)));
-            }
-            Event::Key(Key::Enter) if self.on_submit.is_some() => {
-                let cb = self.on_submit.clone().unwrap();
-                let content = Rc::clone(&self.content);
-                return EventResult::with_cb(move |s| {
-                    cb(s, &content);
-                });
-            }
-            Event::Mouse {
-                event: MouseEvent::Press(_),
-                position,
-                offset,
-            } if position.fits_in_rect(offset, (self.last_length, 1)) => {
-                if let Some(position) = position.checked_sub(offset) {
-                    self.cursor = self.offset
-                        + simple_prefix(
-                            &self.content[self.offset..],
-                            position.x,
-                        )
-                        .length;
-                }
-            }
-            _ => return EventResult::Ignored,
+
+        // The completion popup closes before sending us any event.
+        let accepted = {
+            let mut state = self.completions.borrow_mut();
+            let accepted = state.accepted;
+            state.accepted = false;
+            accepted.then(|| state.selection().cloned()).flatten()
+        };
+        self.completions.borrow_mut().clear();
+        if let Some(completion) = accepted {
+            return EventResult::Consumed(Some(
+                self.accept_completion(completion),
+            ));
         }
 
-        // self.keep_cursor_in_view();
+        if event == Event::Key(Key::Tab) && self.completer.is_some() {
+            return self.complete(true);
+        }
 
-        EventResult::Consumed(self.make_edit_cb())
+        let typing = self.search.is_none()
+            && self.get_vi_mode().unwrap_or(ViMode::Insert) == ViMode::Insert
+            && matches!(
+                event,
+                Event::Char(_)
+                    | Event::Paste(_)
+                    | Event::Key(Key::Backspace)
+                    | Event::Key(Key::Del)
+            );
+        let result = self.handle_event(event);
+        if typing && result.is_consumed() {
+            result.and(self.complete(false))
+        } else {
+            result
+        }
     }
 
     fn important_area(&self, _: Vec2) -> Rect {
@@ -694,3 +2128,197 @@ impl View for EditView {
         Rect::from_size((x, 0), (char_width, 1))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::PrintBuffer;
+    use crate::theme::Theme;
+
+    #[test]
+    fn browse_and_search_history() {
+        let history = InputHistory::from(vec![
+            String::from("ls"),
+            String::from("cd /tmp"),
+            String::from("echo"),
+        ]);
+        let mut edit = EditView::new().input_history(history);
+        edit.on_event(Event::Char('x'));
+
+        edit.on_event(Event::Key(Key::Up));
+        assert_eq!(&*edit.get_content(), "echo");
+        edit.on_event(Event::Key(Key::Up));
+        assert_eq!(&*edit.get_content(), "cd /tmp");
+        edit.on_event(Event::Key(Key::Down));
+        edit.on_event(Event::Key(Key::Down));
+        assert_eq!(&*edit.get_content(), "x");
+        assert!(!edit.on_event(Event::Key(Key::Down)).is_consumed());
+
+        edit.on_event(Event::CtrlChar('r'));
+        edit.on_event(Event::Char('c'));
+        edit.on_event(Event::CtrlChar('r'));
+        assert_eq!(&*edit.get_content(), "x");
+        edit.on_event(Event::Key(Key::End));
+        assert_eq!(&*edit.get_content(), "cd /tmp");
+        assert_eq!(edit.cursor, 7);
+    }
+
+    #[test]
+    fn browsing_history_is_a_single_edit() {
+        let history = InputHistory::from(vec![
+            String::from("ls"),
+            String::from("cd /tmp"),
+        ]);
+        let mut edit = EditView::new().input_history(history);
+        edit.on_event(Event::Char('x'));
+
+        edit.on_event(Event::Key(Key::Up));
+        edit.on_event(Event::Key(Key::Up));
+        edit.on_event(Event::Key(Key::Down));
+        assert_eq!(&*edit.get_content(), "cd /tmp");
+
+        // Typing ends browsing: Up starts again from the last entry.
+        edit.on_event(Event::Char('!'));
+        assert_eq!(edit.history_index, None);
+        edit.on_event(Event::Key(Key::Up));
+        assert_eq!(&*edit.get_content(), "cd /tmp");
+        edit.on_event(Event::Key(Key::Down));
+        assert_eq!(&*edit.get_content(), "cd /tmp!");
+
+        edit.undo();
+        assert_eq!(&*edit.get_content(), "cd /tmp");
+        edit.undo();
+        assert_eq!(&*edit.get_content(), "x");
+        edit.undo();
+        assert_eq!(&*edit.get_content(), "");
+
+        // Going back to the draft leaves no undo step.
+        edit.redo();
+        edit.on_event(Event::Key(Key::Up));
+        edit.on_event(Event::Key(Key::Down));
+        edit.undo();
+        assert_eq!(&*edit.get_content(), "");
+    }
+
+    #[test]
+    fn complete_word_before_cursor() {
+        let mut edit = EditView::new().completer(|content, cursor| {
+            let start = content[..cursor].rfind(' ').map_or(0, |i| i + 1);
+            ["red", "green", "grey"]
+                .iter()
+                .map(|c| Completion::new(start, *c))
+                .collect()
+        });
+        edit.set_content("a g");
+
+        // Typing opens the popup.
+        assert!(edit.on_event(Event::Char('r')).has_callback());
+        assert_eq!(edit.completions.borrow().candidates.len(), 2);
+        assert_eq!(edit.completion_preview().as_deref(), Some("een"));
+
+        // The popup then accepts a candidate, and sends the event back.
+        {
+            let mut state = edit.completions.borrow_mut();
+            state.selected = 1;
+            state.accepted = true;
+        }
+        edit.on_event(Event::Key(Key::Enter));
+        assert_eq!(&*edit.get_content(), "a grey");
+        assert!(edit.completions.borrow().candidates.is_empty());
+
+        // Tab accepts a single candidate directly.
+        edit.on_event(Event::Char(' '));
+        edit.on_event(Event::Char('r'));
+        edit.completions.borrow_mut().clear();
+        edit.on_event(Event::Key(Key::Tab));
+        assert_eq!(&*edit.get_content(), "a grey red");
+    }
+
+    #[test]
+    fn typing_over_selection_keeps_max_width() {
+        let mut edit = EditView::new()
+            .content("abc")
+            .max_content_width(3)
+            .selected(1..2);
+
+        // A wide character would not fit in place of `b`.
+        edit.on_event(Event::Char('字'));
+        assert_eq!(&*edit.get_content(), "abc");
+
+        edit.on_event(Event::Char('x'));
+        assert_eq!(&*edit.get_content(), "axc");
+    }
+
+    #[test]
+    fn paste_is_a_single_edit() {
+        let mut edit = EditView::new().on_submit(|_, _| ());
+        edit.on_event(Event::Char('>'));
+
+        // Newlines don't submit the content.
+        let result = edit.on_event(Event::Paste("foo\r\nbar\n".into()));
+        assert!(result.is_consumed());
+        assert_eq!(&*edit.get_content(), ">foobar");
+
+        edit.undo();
+        assert_eq!(&*edit.get_content(), ">");
+    }
+
+    #[test]
+    fn placeholder_uses_secondary_color() {
+        let mut edit = EditView::new().placeholder("Name");
+        edit.layout(Vec2::new(8, 1));
+
+        let theme = Theme::default();
+        let mut buffer = PrintBuffer::new();
+        buffer.resize(Vec2::new(8, 1));
+        edit.draw(&Printer::new((8, 1), &theme, &buffer));
+        assert_eq!(buffer.row(0), "Name____");
+        assert!(edit.get_content().is_empty());
+
+        // Unlike the filler, the placeholder is not reversed.
+        assert!(!buffer.effects_at(Vec2::new(1, 0)).contains(Effect::Reverse));
+        assert!(buffer.effects_at(Vec2::new(4, 0)).contains(Effect::Reverse));
+    }
+
+    #[test]
+    fn filter_rejects_characters() {
+        let mut edit = EditView::new().filter(|c| c.is_ascii_digit());
+
+        edit.on_event(Event::Char('4'));
+        edit.on_event(Event::Char('x'));
+        edit.on_event(Event::Char('2'));
+        assert_eq!(&*edit.get_content(), "42");
+        edit.on_event(Event::Paste("1a2b".into()));
+        assert_eq!(&*edit.get_content(), "4212");
+
+        edit.clear_filter();
+        edit.on_event(Event::Char('x'));
+        assert_eq!(&*edit.get_content(), "4212x");
+    }
+
+    #[test]
+    fn validator_blocks_submit() {
+        let mut edit = EditView::new()
+            .validator(|text| match text.parse::<u16>() {
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            })
+            .on_submit(|_, _| ());
+        assert!(!edit.is_valid());
+
+        // Invalid content is not submitted.
+        let result = edit.on_event(Event::Key(Key::Enter));
+        assert!(result.is_consumed());
+        assert!(!result.has_callback());
+
+        edit.set_content("8080");
+        assert_eq!(edit.validate(), Ok(()));
+        assert!(edit.on_event(Event::Key(Key::Enter)).has_callback());
+
+        edit.set_content("99999");
+        assert!(edit.validate().is_err());
+
+        edit.clear_validator();
+        assert!(edit.is_valid());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::direction::Direction;
+use crate::event::{Event, EventResult};
+use crate::theme::{ColorStyle, PaletteColor};
+use crate::utils::FindOptions;
+use crate::view::View;
+use crate::views::EditView;
+use crate::{Printer, Vec2};
+
+const LABEL: &str = "Find: ";
+
+/// Width of the option toggles on the right.
+const TOGGLES_WIDTH: usize = 5;
+
+/// Search strip shown at the bottom of a `TextArea`.
+///
+/// Alt-C toggles case sensitivity and Alt-W toggles whole-word matching.
+/// Other events edit the query.
+pub(crate) struct FindBar {
+    edit: EditView,
+
+    pub options: FindOptions,
+
+    /// Byte offset in the `TextArea` where the search started.
+    ///
+    /// Each change to the query searches again from here.
+    pub origin: usize,
+
+    /// `false` if the query was not found.
+    pub found: bool,
+}
+
+impl FindBar {
+    /// Creates a bar for a search starting at `origin`.
+    pub fn new(query: &str, options: FindOptions, origin: usize) -> Self {
+        FindBar {
+            edit: EditView::new().content(query),
+            options,
+            origin,
+            found: true,
+        }
+    }
+
+    /// Returns the current query.
+    pub fn query(&self) -> String {
+        self.edit.get_content().to_string()
+    }
+}
+
+impl View for FindBar {
+    fn draw(&self, printer: &Printer) {
+        let label_style = if self.found {
+            ColorStyle::primary()
+        } else {
+            ColorStyle::new(PaletteColor::Invalid, PaletteColor::View)
+        };
+        printer.with_color(label_style, |printer| {
+            printer.print((0, 0), LABEL);
+        });
+
+        let width = printer.size.x.saturating_sub(LABEL.len() + TOGGLES_WIDTH);
+        self.edit
+            .draw(&printer.offset((LABEL.len(), 0)).cropped((width, 1)));
+
+        let toggles = [
+            (" Aa", self.options.case_sensitive),
+            (" W", self.options.whole_word),
+        ];
+        let mut x = LABEL.len() + width;
+        for &(text, active) in &toggles {
+            let style = if active {
+                ColorStyle::highlight()
+            } else {
+                ColorStyle::secondary()
+            };
+            printer.with_color(style, |printer| printer.print((x, 0), text));
+            x += text.len();
+        }
+    }
+
+    fn required_size(&mut self, constraint: Vec2) -> Vec2 {
+        Vec2::new(constraint.x, 1)
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        let width = size.x.saturating_sub(LABEL.len() + TOGGLES_WIDTH);
+        self.edit.layout(Vec2::new(width, 1));
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::AltChar('c') => {
+                self.options.case_sensitive = !self.options.case_sensitive;
+            }
+            Event::AltChar('w') => {
+                self.options.whole_word = !self.options.whole_word;
+            }
+            Event::Mouse {
+                offset,
+                position,
+                event,
+            } => {
+                let offset = offset + (LABEL.len(), 0);
+                return self.edit.on_event(Event::Mouse {
+                    offset,
+                    position,
+                    event,
+                });
+            }
+            event => return self.edit.on_event(event),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn take_focus(&mut self, _: Direction) -> bool {
+        true
+    }
+}
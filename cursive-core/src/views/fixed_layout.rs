@@ -1,8 +1,9 @@
 use crate::{
     direction::{Absolute, Direction, Relative},
     event::{AnyCb, Event, EventResult, Key},
+    keymap::{Conflict, Keymap},
     rect::Rect,
-    view::{IntoBoxedView, Selector},
+    view::{IntoBoxedView, KeyHelp, Selector},
     {Printer, Vec2, View, With},
 };
 
@@ -268,6 +269,18 @@ impl View for FixedLayout {
             return EventResult::Ignored;
         }
 
+        if event.is_mouse_move() {
+            // Every child needs to know where the mouse is.
+            return self
+                .children
+                .iter_mut()
+                .map(|child| {
+                    let offset = child.position.top_left();
+                    child.view.on_event(event.relativized(offset))
+                })
+                .fold(EventResult::Ignored, EventResult::and);
+        }
+
         self.check_focus_grab(&event);
 
         let child = &mut self.children[self.focus];
@@ -359,4 +372,20 @@ impl View for FixedLayout {
 
         Err(())
     }
+
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        if let Some(child) = self.children.get(self.focus) {
+            child.view.key_help(help);
+        }
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        if let Some(child) = self.children.get(self.focus) {
+            child.view.keymap_conflicts(keymap, conflicts);
+        }
+    }
 }
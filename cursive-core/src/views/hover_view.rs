@@ -0,0 +1,173 @@
+use crate::event::{Callback, Event, EventResult, MouseEvent};
+use crate::view::{View, ViewWrapper};
+use crate::{Cursive, Vec2, With};
+
+/// Wrapper view running callbacks when the mouse enters or leaves it.
+///
+/// This requires mouse motion to be enabled with
+/// [`Cursive::set_mouse_motion`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cursive_core::views::{HoverView, TextView};
+///
+/// let view = HoverView::new(TextView::new("Hover me"))
+///     .on_hover_enter(|s| {
+///         s.call_on_name("status", |v: &mut TextView| {
+///             v.set_content("Hovered")
+///         });
+///     })
+///     .on_hover_leave(|s| {
+///         s.call_on_name("status", |v: &mut TextView| v.set_content(""));
+///     });
+/// ```
+///
+/// [`Cursive::set_mouse_motion`]: crate::Cursive::set_mouse_motion
+pub struct HoverView<V> {
+    view: V,
+    on_enter: Option<Callback>,
+    on_leave: Option<Callback>,
+    hovered: bool,
+    last_size: Vec2,
+}
+
+impl<V> HoverView<V> {
+    /// Wraps the given view.
+    pub fn new(view: V) -> Self {
+        HoverView {
+            view,
+            on_enter: None,
+            on_leave: None,
+            hovered: false,
+            last_size: Vec2::zero(),
+        }
+    }
+
+    /// Sets a callback to run when the mouse enters this view.
+    pub fn set_on_hover_enter<F>(&mut self, cb: F)
+    where
+        F: 'static + Fn(&mut Cursive),
+    {
+        self.on_enter = Some(Callback::from_fn(cb));
+    }
+
+    /// Sets a callback to run when the mouse enters this view.
+    ///
+    /// Chainable variant.
+    pub fn on_hover_enter<F>(self, cb: F) -> Self
+    where
+        F: 'static + Fn(&mut Cursive),
+    {
+        self.with(|s| s.set_on_hover_enter(cb))
+    }
+
+    /// Sets a callback to run when the mouse leaves this view.
+    pub fn set_on_hover_leave<F>(&mut self, cb: F)
+    where
+        F: 'static + Fn(&mut Cursive),
+    {
+        self.on_leave = Some(Callback::from_fn(cb));
+    }
+
+    /// Sets a callback to run when the mouse leaves this view.
+    ///
+    /// Chainable variant.
+    pub fn on_hover_leave<F>(self, cb: F) -> Self
+    where
+        F: 'static + Fn(&mut Cursive),
+    {
+        self.with(|s| s.set_on_hover_leave(cb))
+    }
+
+    /// Returns `true` if the mouse is currently over this view.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    inner_getters!(self.view: V);
+}
+
+impl<V: View> ViewWrapper for HoverView<V> {
+    wrap_impl!(self.view: V);
+
+    fn wrap_layout(&mut self, size: Vec2) {
+        self.last_size = size;
+        self.view.layout(size);
+    }
+
+    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+        let hovered = match event {
+            Event::Mouse {
+                event: MouseEvent::Move,
+                position,
+                offset,
+            } => matches!(
+                position.checked_sub(offset),
+                Some(position) if position.strictly_lt(self.last_size)
+            ),
+            _ => return self.view.on_event(event),
+        };
+
+        let result = self.view.on_event(event);
+        if hovered == self.hovered {
+            return result;
+        }
+
+        self.hovered = hovered;
+        let cb = if hovered {
+            self.on_enter.clone()
+        } else {
+            self.on_leave.clone()
+        };
+        match cb {
+            Some(cb) => result.and(EventResult::Consumed(Some(cb))),
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::{DummyView, LinearLayout, ResizedView};
+
+    #[test]
+    fn hover_enter_and_leave() {
+        let hover = |name: &'static str| {
+            let view = ResizedView::with_fixed_size((4, 1), DummyView);
+            HoverView::new(view)
+                .on_hover_enter(move |s| {
+                    s.with_user_data(|log: &mut Vec<String>| {
+                        log.push(format!("enter {}", name))
+                    });
+                })
+                .on_hover_leave(move |s| {
+                    s.with_user_data(|log: &mut Vec<String>| {
+                        log.push(format!("leave {}", name))
+                    });
+                })
+        };
+        let mut layout =
+            LinearLayout::vertical().child(hover("a")).child(hover("b"));
+        layout.layout(Vec2::new(4, 2));
+
+        let mut siv = Cursive::new();
+        siv.set_user_data(Vec::<String>::new());
+        let mut move_to = |x, y| {
+            let event = Event::Mouse {
+                event: MouseEvent::Move,
+                position: Vec2::new(x, y),
+                offset: Vec2::zero(),
+            };
+            layout.on_event(event).process(&mut siv);
+        };
+        move_to(1, 0);
+        move_to(2, 0);
+        move_to(2, 1);
+        move_to(5, 1);
+
+        let log = siv.take_user_data::<Vec<String>>().unwrap();
+        assert_eq!(log, ["enter a", "leave a", "enter b", "leave b"]);
+    }
+}
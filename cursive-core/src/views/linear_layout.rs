@@ -1,7 +1,8 @@
 use crate::direction;
 use crate::event::{AnyCb, Event, EventResult, Key};
+use crate::keymap::{Conflict, Keymap};
 use crate::rect::Rect;
-use crate::view::{IntoBoxedView, Selector, SizeCache, View};
+use crate::view::{IntoBoxedView, KeyHelp, Selector, SizeCache, View};
 use crate::Printer;
 use crate::Vec2;
 use crate::With;
@@ -647,6 +648,21 @@ impl View for LinearLayout {
             return EventResult::Ignored;
         }
 
+        if event.is_mouse_move() {
+            // Every child needs to know where the mouse is.
+            let orientation = self.orientation;
+            return ChildIterator::new(
+                self.children.iter_mut(),
+                orientation,
+                usize::MAX,
+            )
+            .map(|item| {
+                let offset = orientation.make_vec(item.offset, 0);
+                item.child.view.on_event(event.relativized(offset))
+            })
+            .fold(EventResult::Ignored, EventResult::and);
+        }
+
         self.check_focus_grab(&event);
 
         let result = {
@@ -724,6 +740,22 @@ impl View for LinearLayout {
         Err(())
     }
 
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        if let Some(child) = self.children.get(self.focus) {
+            child.view.key_help(help);
+        }
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        if let Some(child) = self.children.get(self.focus) {
+            child.view.keymap_conflicts(keymap, conflicts);
+        }
+    }
+
     fn important_area(&self, _: Vec2) -> Rect {
         if self.is_empty() {
             // Return dummy area if we are empty.
@@ -1,7 +1,8 @@
 use crate::direction;
 use crate::event::{AnyCb, Callback, Event, EventResult, Key};
+use crate::keymap::{Conflict, Keymap};
 use crate::rect::Rect;
-use crate::view::{IntoBoxedView, Selector, View};
+use crate::view::{IntoBoxedView, KeyHelp, Selector, View};
 use crate::Cursive;
 use crate::Printer;
 use crate::Vec2;
@@ -42,6 +43,8 @@ pub struct ListView {
     // This callback is called when the selection is changed.
     on_select: Option<Rc<dyn Fn(&mut Cursive, &String)>>,
     last_size: Vec2,
+    // `true` when the rows changed since the last layout.
+    invalidated: bool,
 }
 
 new_default!(ListView);
@@ -55,6 +58,7 @@ impl ListView {
             focus: 0,
             on_select: None,
             last_size: Vec2::zero(),
+            invalidated: true,
         }
     }
 
@@ -86,6 +90,8 @@ impl ListView {
     ///
     /// Panics if `id >= self.len()`.
     pub fn row_mut(&mut self, id: usize) -> &mut ListChild {
+        // The label may change.
+        self.invalidated = true;
         &mut self.children[id]
     }
 
@@ -99,6 +105,7 @@ impl ListView {
         view.take_focus(direction::Direction::none());
         self.children.push(ListChild::Row(label.to_string(), view));
         self.children_heights.push(0);
+        self.invalidated = true;
     }
 
     /// Removes all children from this view.
@@ -106,6 +113,7 @@ impl ListView {
         self.children.clear();
         self.children_heights.clear();
         self.focus = 0;
+        self.invalidated = true;
     }
 
     /// Adds a view to the end of the list.
@@ -123,6 +131,7 @@ impl ListView {
     pub fn add_delimiter(&mut self) {
         self.children.push(ListChild::Delimiter);
         self.children_heights.push(0);
+        self.invalidated = true;
     }
 
     /// Adds a delimiter to the end of the list.
@@ -139,6 +148,7 @@ impl ListView {
     /// If `index >= self.len()`.
     pub fn remove_child(&mut self, index: usize) -> ListChild {
         self.children_heights.remove(index);
+        self.invalidated = true;
         self.children.remove(index)
     }
 
@@ -334,6 +344,7 @@ impl View for ListView {
 
     fn layout(&mut self, size: Vec2) {
         self.last_size = size;
+        self.invalidated = false;
 
         // We'll show 2 columns: the labels, and the views.
         let label_width = self
@@ -363,15 +374,41 @@ impl View for ListView {
         }
     }
 
+    fn needs_relayout(&self) -> bool {
+        self.invalidated
+            || self.children.iter().any(|child| match *child {
+                ListChild::Row(_, ref view) => view.needs_relayout(),
+                ListChild::Delimiter => false,
+            })
+    }
+
     fn on_event(&mut self, event: Event) -> EventResult {
         if self.children.is_empty() {
             return EventResult::Ignored;
         }
 
+        let labels_width = self.labels_width();
+
+        if event.is_mouse_move() {
+            // Every row needs to know where the mouse is.
+            let mut y = 0;
+            let mut result = EventResult::Ignored;
+            for (child, height) in
+                self.children.iter_mut().zip(&self.children_heights)
+            {
+                if let ListChild::Row(_, ref mut view) = child {
+                    let offset = (labels_width + 1, y);
+                    result =
+                        result.and(view.on_event(event.relativized(offset)));
+                }
+                y += height;
+            }
+            return result;
+        }
+
         self.check_focus_grab(&event);
 
         // Send the event to the focused child.
-        let labels_width = self.labels_width();
         if let ListChild::Row(_, ref mut view) = self.children[self.focus] {
             let y = self.children_heights[..self.focus].iter().sum();
             let offset = (labels_width + 1, y);
@@ -451,6 +488,26 @@ impl View for ListView {
         }
     }
 
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        if let Some(ListChild::Row(_, ref view)) =
+            self.children.get(self.focus)
+        {
+            view.key_help(help);
+        }
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        if let Some(ListChild::Row(_, ref view)) =
+            self.children.get(self.focus)
+        {
+            view.keymap_conflicts(keymap, conflicts);
+        }
+    }
+
     fn important_area(&self, size: Vec2) -> Rect {
         if self.children.is_empty() {
             return Rect::from((0, 0));
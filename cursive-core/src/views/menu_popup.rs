@@ -4,6 +4,7 @@ use crate::event::{
 };
 use crate::menu::{MenuItem, MenuTree};
 use crate::rect::Rect;
+use crate::theme::ColorStyle;
 use crate::view::scroll;
 use crate::view::{Position, View};
 use crate::views::OnEventView;
@@ -25,6 +26,8 @@ use unicode_width::UnicodeWidthStr;
 pub struct MenuPopup {
     menu: Rc<MenuTree>,
     focus: usize,
+    // Item under the mouse, if any.
+    hovered: Option<usize>,
     scroll_core: scroll::Core,
     align: Align,
     on_dismiss: Option<Callback>,
@@ -42,6 +45,7 @@ impl MenuPopup {
         MenuPopup {
             menu,
             focus: 0,
+            hovered: None,
             scroll_core: scroll::Core::new(),
             align: Align::top_left(),
             on_dismiss: None,
@@ -69,7 +73,7 @@ impl MenuPopup {
     fn item_width(item: &MenuItem) -> usize {
         match *item {
             MenuItem::Delimiter => 1,
-            MenuItem::Leaf(ref title, _) => title.width(),
+            MenuItem::Leaf(ref title, _, _) => title.width(),
             MenuItem::Subtree(ref title, _) => title.width() + 3,
         }
     }
@@ -157,7 +161,7 @@ impl MenuPopup {
 
     fn submit(&mut self) -> EventResult {
         match self.menu.children[self.focus] {
-            MenuItem::Leaf(_, ref cb) => {
+            MenuItem::Leaf(_, ref cb, _) => {
                 let cb = cb.clone();
                 let action_cb = self.on_action.clone();
                 EventResult::with_cb(move |s| {
@@ -266,6 +270,20 @@ impl MenuPopup {
                     }
                 }
             }
+            Event::Mouse {
+                event: MouseEvent::Move,
+                position,
+                offset,
+            } => {
+                self.hovered = position
+                    .checked_sub(offset)
+                    .map(|position| position.y)
+                    .filter(|&y| {
+                        y < self.menu.len()
+                            && !self.menu.children[y].is_delimiter()
+                    });
+                return EventResult::Ignored;
+            }
             Event::Mouse {
                 event: MouseEvent::Release(MouseButton::Left),
                 position,
@@ -335,7 +353,7 @@ impl View for MenuPopup {
         let printer = printer.shrinked_centered((2, 2));
 
         scroll::draw_lines(self, &printer, |s, printer, i| {
-            printer.with_selection(i == s.focus, |printer| {
+            let draw_item = |printer: &Printer| {
                 let item = &s.menu.children[i];
                 match *item {
                     MenuItem::Delimiter => {
@@ -351,7 +369,7 @@ impl View for MenuPopup {
                         let x = printer.size.x.saturating_sub(3);
                         printer.print((x, 0), ">>");
                     }
-                    MenuItem::Leaf(ref label, _) => {
+                    MenuItem::Leaf(ref label, _, _) => {
                         if printer.size.x < 2 {
                             return;
                         }
@@ -359,7 +377,14 @@ impl View for MenuPopup {
                         printer.print((1, 0), label);
                     }
                 }
-            });
+            };
+
+            if i != s.focus && s.hovered == Some(i) {
+                printer
+                    .with_color(ColorStyle::highlight_inactive(), draw_item);
+            } else {
+                printer.with_selection(i == s.focus, draw_item);
+            }
         });
     }
 
@@ -3,7 +3,7 @@ use crate::event::*;
 use crate::menu::{MenuItem, MenuTree};
 use crate::rect::Rect;
 use crate::theme::ColorStyle;
-use crate::view::{Position, View};
+use crate::view::{KeyHelp, Position, View};
 use crate::views::{MenuPopup, OnEventView};
 use crate::Cursive;
 use crate::Printer;
@@ -40,6 +40,9 @@ pub struct Menubar {
     pub autohide: bool,
     focus: usize,
 
+    // Item under the mouse, if any.
+    hovered: Option<usize>,
+
     // TODO: make Menubar impl View and take out the State management
     state: State,
 }
@@ -54,6 +57,7 @@ impl Menubar {
             autohide: true,
             state: State::Inactive,
             focus: 0,
+            hovered: None,
         }
     }
 
@@ -179,6 +183,11 @@ impl Menubar {
         self.root.remove(i);
     }
 
+    /// Lists the described menu items, for the help layer.
+    pub(crate) fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        self.root.key_help("", help);
+    }
+
     fn child_at(&self, x: usize) -> Option<usize> {
         if x == 0 {
             return None;
@@ -197,7 +206,7 @@ impl Menubar {
 
     fn select_child(&mut self, open_only: bool) -> EventResult {
         match self.root.children[self.focus] {
-            MenuItem::Leaf(_, ref cb) if !open_only => {
+            MenuItem::Leaf(_, ref cb, _) if !open_only => {
                 // Go inactive after an action.
                 self.state = State::Inactive;
                 EventResult::Consumed(Some(cb.clone()))
@@ -281,9 +290,15 @@ impl View for Menubar {
             // because it's ugly on the menubar.
             let selected =
                 (self.state != State::Inactive) && (i == self.focus);
-            printer.with_selection(selected, |printer| {
+            let print_title = |printer: &Printer| {
                 printer.print((offset, 0), &format!(" {} ", title));
-            });
+            };
+            if !selected && self.hovered == Some(i) {
+                printer
+                    .with_color(ColorStyle::highlight_inactive(), print_title);
+            } else {
+                printer.with_selection(selected, print_title);
+            }
             offset += title.width() + 2;
         }
     }
@@ -354,6 +369,20 @@ impl View for Menubar {
                     }
                 }
             }
+            Event::Mouse {
+                event: MouseEvent::Move,
+                position,
+                offset,
+            } => {
+                self.hovered = position
+                    .checked_sub(offset)
+                    .filter(|pos| pos.y == 0)
+                    .and_then(|pos| self.child_at(pos.x))
+                    .filter(|&child| {
+                        !self.root.children[child].is_delimiter()
+                    });
+                return EventResult::Ignored;
+            }
             Event::Mouse {
                 event: MouseEvent::Press(_),
                 ..
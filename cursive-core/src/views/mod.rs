@@ -64,13 +64,16 @@ mod button;
 mod canvas;
 mod checkbox;
 mod circular_focus;
+mod completion_popup;
 mod debug_view;
 mod dialog;
 mod dummy;
 mod edit_view;
 mod enableable_view;
+mod find_bar;
 mod fixed_layout;
 mod hideable_view;
+mod hover_view;
 mod last_size_view;
 mod layer;
 mod linear_layout;
@@ -100,6 +103,7 @@ pub use self::button::Button;
 pub use self::canvas::Canvas;
 pub use self::checkbox::Checkbox;
 pub use self::circular_focus::CircularFocus;
+pub use self::completion_popup::{Completion, CompletionMode};
 pub use self::debug_view::DebugView;
 pub use self::dialog::{Dialog, DialogFocus};
 pub use self::dummy::DummyView;
@@ -107,6 +111,7 @@ pub use self::edit_view::EditView;
 pub use self::enableable_view::EnableableView;
 pub use self::fixed_layout::FixedLayout;
 pub use self::hideable_view::HideableView;
+pub use self::hover_view::HoverView;
 pub use self::last_size_view::LastSizeView;
 pub use self::layer::Layer;
 pub use self::linear_layout::LinearLayout;
@@ -127,7 +132,7 @@ pub use self::select_view::SelectView;
 pub use self::shadow_view::ShadowView;
 pub use self::slider_view::SliderView;
 pub use self::stack_view::{LayerPosition, StackView};
-pub use self::text_area::TextArea;
+pub use self::text_area::{LineNumbers, TextArea};
 pub use self::text_view::{TextContent, TextContentRef, TextView};
 pub use self::tracked_view::TrackedView;
 
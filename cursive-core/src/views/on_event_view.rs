@@ -1,8 +1,13 @@
 use crate::event::{Callback, Event, EventResult, EventTrigger};
-use crate::view::{View, ViewWrapper};
+use crate::keymap::{Conflict, Keymap};
+use crate::view::{key_names, KeyHelp, View, ViewWrapper};
 use crate::Cursive;
 use crate::With;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Default time to wait for the next key of a sequence.
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// A wrapper view that can react to events.
 ///
@@ -29,6 +34,21 @@ use std::rc::Rc;
 /// [`on_event_inner`]: OnEventView::on_event_inner
 /// [`on_pre_event_inner`]: OnEventView::on_pre_event_inner
 ///
+/// Callbacks can also be tied to a sequence of keys, like Ctrl-X Ctrl-S,
+/// with [`on_sequence`]. Sequences are checked before the child view: keys
+/// starting a sequence are held until it completes. If the next key doesn't
+/// continue it, or if it times out, held keys are given to the child view as
+/// usual.
+///
+/// [`on_sequence`]: OnEventView::on_sequence
+///
+/// Bindings can be given a description with [`description`] or
+/// [`sequence_description`], to be listed in the help layer while this view
+/// is focused.
+///
+/// [`description`]: OnEventView::description
+/// [`sequence_description`]: OnEventView::sequence_description
+///
 /// # Examples
 ///
 /// ```
@@ -41,6 +61,17 @@ use std::rc::Rc;
 pub struct OnEventView<T> {
     view: T,
     callbacks: Vec<(EventTrigger, Action<T>)>,
+
+    sequences: Vec<(Vec<Event>, Callback)>,
+    sequence_timeout: Option<Duration>,
+
+    // Keys held while they may start a sequence.
+    pending: Vec<Event>,
+    pending_since: Option<Instant>,
+
+    // Descriptions for the help layer, for single keys and sequences.
+    descriptions: Vec<(Vec<Event>, String)>,
+    help_source: String,
 }
 
 new_default!(OnEventView<T: Default>);
@@ -73,6 +104,12 @@ impl<T> OnEventView<T> {
         OnEventView {
             view,
             callbacks: Vec::new(),
+            sequences: Vec::new(),
+            sequence_timeout: Some(SEQUENCE_TIMEOUT),
+            pending: Vec::new(),
+            pending_since: None,
+            descriptions: Vec::new(),
+            help_source: String::from("View"),
         }
     }
 
@@ -83,8 +120,10 @@ impl<T> OnEventView<T> {
     {
         let event = event.into();
         self.callbacks
-            .retain(move |&(ref trigger, _)| !trigger.has_tag(&event));
+            .retain(|&(ref trigger, _)| !trigger.has_tag(&event));
+        self.remove_description(&[event]);
     }
+
     /// Registers a callback when the given event is ignored by the child.
     ///
     /// Chainable variant.
@@ -257,18 +296,166 @@ impl<T> OnEventView<T> {
         ));
     }
 
+    /// Registers a callback for a sequence of keys.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::views::{OnEventView, DummyView};
+    /// # use cursive_core::event::Event;
+    /// let ctrl_x_ctrl_c = vec![Event::CtrlChar('x'), Event::CtrlChar('c')];
+    /// let view = OnEventView::new(DummyView)
+    ///     .on_sequence(ctrl_x_ctrl_c, |s| s.quit())
+    ///     .on_sequence(vec!['g', 'g'], |s| {
+    ///         s.pop_layer();
+    ///     });
+    /// ```
+    pub fn on_sequence<I, E, F>(self, keys: I, cb: F) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+        F: Fn(&mut Cursive) + 'static,
+    {
+        self.with(|s| s.set_on_sequence(keys, cb))
+    }
+
+    /// Registers a callback for a sequence of keys.
+    ///
+    /// The keys are held by this view until the sequence completes, fails,
+    /// or times out.
+    pub fn set_on_sequence<I, E, F>(&mut self, keys: I, cb: F)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+        F: Fn(&mut Cursive) + 'static,
+    {
+        let keys: Vec<Event> = keys.into_iter().map(Into::into).collect();
+        if !keys.is_empty() {
+            self.sequences.push((keys, Callback::from_fn(cb)));
+        }
+    }
+
+    /// Removes all callbacks for the given sequence.
+    pub fn clear_sequence<I, E>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+    {
+        let keys: Vec<Event> = keys.into_iter().map(Into::into).collect();
+        self.sequences.retain(|(sequence, _)| *sequence != keys);
+        self.remove_description(&keys);
+    }
+
+    /// Sets how long to wait for the next key of a sequence.
+    ///
+    /// With `None`, waits forever. Defaults to one second.
+    pub fn set_sequence_timeout(&mut self, timeout: Option<Duration>) {
+        self.sequence_timeout = timeout;
+    }
+
+    /// Sets how long to wait for the next key of a sequence.
+    ///
+    /// Chainable variant.
+    pub fn sequence_timeout(self, timeout: Option<Duration>) -> Self {
+        self.with(|s| s.set_sequence_timeout(timeout))
+    }
+
+    /// Returns the keys held while waiting for the rest of a sequence.
+    pub fn pending_keys(&self) -> &[Event] {
+        &self.pending
+    }
+
+    /// Describes what the given key does, for the help layer.
+    ///
+    /// The description is removed with the callbacks for this key.
+    pub fn set_description<E, S>(&mut self, event: E, description: S)
+    where
+        E: Into<Event>,
+        S: Into<String>,
+    {
+        self.set_sequence_description(Some(event), description);
+    }
+
+    /// Describes what the given key does, for the help layer.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use cursive_core::views::{OnEventView, DummyView};
+    /// let view = OnEventView::new(DummyView)
+    ///     .on_event('q', |s| s.quit())
+    ///     .description('q', "Quit");
+    /// ```
+    pub fn description<E, S>(self, event: E, description: S) -> Self
+    where
+        E: Into<Event>,
+        S: Into<String>,
+    {
+        self.with(|s| s.set_description(event, description))
+    }
+
+    /// Describes what the given sequence does, for the help layer.
+    pub fn set_sequence_description<I, E, S>(
+        &mut self,
+        keys: I,
+        description: S,
+    ) where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+        S: Into<String>,
+    {
+        let keys: Vec<Event> = keys.into_iter().map(Into::into).collect();
+        self.remove_description(&keys);
+        self.descriptions.push((keys, description.into()));
+    }
+
+    /// Describes what the given sequence does, for the help layer.
+    ///
+    /// Chainable variant.
+    pub fn sequence_description<I, E, S>(self, keys: I, description: S) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+        S: Into<String>,
+    {
+        self.with(|s| s.set_sequence_description(keys, description))
+    }
+
+    /// Sets the name under which bindings are listed in the help layer.
+    ///
+    /// Defaults to `"View"`.
+    pub fn set_help_source<S: Into<String>>(&mut self, source: S) {
+        self.help_source = source.into();
+    }
+
+    /// Sets the name under which bindings are listed in the help layer.
+    ///
+    /// Chainable variant.
+    pub fn help_source<S: Into<String>>(self, source: S) -> Self {
+        self.with(|s| s.set_help_source(source))
+    }
+
+    fn remove_description(&mut self, keys: &[Event]) {
+        self.descriptions.retain(|(k, _)| k != keys);
+    }
+
     /// Remove any callbacks defined for this view.
     pub fn clear_callbacks(&mut self) {
         self.callbacks.clear();
+        self.sequences.clear();
+        self.descriptions.clear();
     }
 
     inner_getters!(self.view: T);
 }
 
-impl<T: View> ViewWrapper for OnEventView<T> {
-    wrap_impl!(self.view: T);
-
-    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+impl<T: View> OnEventView<T> {
+    /// Handles an event with the registered callbacks and the child view.
+    fn dispatch(&mut self, event: Event) -> EventResult {
         // Until we have better closure capture, define captured members separately.
         let callbacks = &self.callbacks;
         let view = &mut self.view;
@@ -309,4 +496,225 @@ impl<T: View> ViewWrapper for OnEventView<T> {
                     .fold(EventResult::Ignored, EventResult::and)
             })
     }
+
+    /// Feeds a key event to the sequences.
+    fn on_sequence_event(&mut self, event: Event) -> EventResult {
+        let mut keys = std::mem::take(&mut self.pending);
+        keys.push(event);
+
+        if let Some((_, cb)) = self
+            .sequences
+            .iter()
+            .find(|(sequence, _)| *sequence == keys)
+        {
+            return EventResult::Consumed(Some(cb.clone()));
+        }
+
+        let is_prefix = self.sequences.iter().any(|(sequence, _)| {
+            sequence.len() > keys.len() && sequence.starts_with(&keys)
+        });
+        if is_prefix {
+            self.pending = keys;
+            self.pending_since = Some(Instant::now());
+            return EventResult::Consumed(None);
+        }
+
+        // Not a sequence after all: the first key goes through as usual, and
+        // the next ones may still start another sequence.
+        let mut keys = keys.into_iter();
+        let first = keys.next().unwrap();
+        let mut result = self.dispatch(first);
+        for key in keys {
+            result = result.and(self.on_sequence_event(key));
+        }
+        result
+    }
+
+    /// Gives the held keys to the child view.
+    fn flush_pending(&mut self) -> EventResult {
+        let mut keys = std::mem::take(&mut self.pending).into_iter();
+        let mut result = match keys.next() {
+            Some(first) => self.dispatch(first),
+            None => return EventResult::Ignored,
+        };
+        for key in keys {
+            result = result.and(self.on_sequence_event(key));
+        }
+        result
+    }
+
+    fn timed_out(&self) -> bool {
+        if self.pending.is_empty() {
+            return false;
+        }
+        match (self.pending_since, self.sequence_timeout) {
+            (Some(since), Some(timeout)) => since.elapsed() >= timeout,
+            _ => false,
+        }
+    }
+}
+
+impl<T: View> ViewWrapper for OnEventView<T> {
+    wrap_impl!(self.view: T);
+
+    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+        if self.sequences.is_empty() && self.pending.is_empty() {
+            return self.dispatch(event);
+        }
+
+        let mut result = EventResult::Ignored;
+        if self.timed_out() {
+            result = self.flush_pending();
+        }
+
+        result = result.and(match event {
+            // Refresh events, mouse motion and key releases don't interrupt
+            // a sequence.
+            event
+                if event == Event::Refresh
+                    || event.is_mouse_move()
+                    || matches!(event, Event::KeyRelease(_)) =>
+            {
+                self.dispatch(event)
+            }
+            event if event.key_combo().is_some() => {
+                self.on_sequence_event(event)
+            }
+            event => self.flush_pending().and(self.dispatch(event)),
+        });
+
+        if self.pending.is_empty() {
+            return result;
+        }
+
+        // Tell the root which keys are pending, and when they time out.
+        let text = describe_keys(&self.pending);
+        let deadline = self
+            .sequence_timeout
+            .and_then(|timeout| Some(self.pending_since? + timeout));
+        result.and(EventResult::with_cb(move |s| {
+            s.set_pending_keys(text.clone(), deadline)
+        }))
+    }
+
+    fn wrap_key_help(&self, help: &mut Vec<KeyHelp>) {
+        for (keys, description) in &self.descriptions {
+            help.push(KeyHelp::new(
+                self.help_source.as_str(),
+                key_names(keys),
+                description.as_str(),
+            ));
+        }
+        self.view.key_help(help);
+    }
+
+    fn wrap_keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        for binding in keymap.effective() {
+            let event = Event::from(binding.key);
+            let starts = |keys: &[Event]| keys.first() == Some(&event);
+            // Sequences starting with this key hold it, too.
+            let handled = self
+                .callbacks
+                .iter()
+                .any(|(trigger, _)| trigger.apply(&event))
+                || self.sequences.iter().any(|(keys, _)| starts(keys));
+            if !handled {
+                continue;
+            }
+
+            let description = self
+                .descriptions
+                .iter()
+                .find(|(keys, _)| starts(keys))
+                .map_or("callback", |(_, description)| description.as_str());
+            conflicts.push(Conflict {
+                key: binding.key,
+                shadowed: binding.action.clone(),
+                action: description.to_string(),
+                source: Some(self.help_source.clone()),
+            });
+        }
+        self.view.keymap_conflicts(keymap, conflicts);
+    }
+}
+
+/// Describes held keys, like `"ctrl+x -"`.
+fn describe_keys(keys: &[Event]) -> String {
+    format!("{} -", key_names(keys))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::TextArea;
+    use crate::Vec2;
+
+    #[test]
+    fn held_keys_fall_through() {
+        let mut view = OnEventView::new(TextArea::new())
+            .on_sequence(vec!['g', 'g'], |_| ())
+            .sequence_timeout(None);
+
+        assert!(View::on_event(&mut view, Event::Char('g')).is_consumed());
+        assert_eq!(view.pending_keys(), &[Event::Char('g')]);
+        assert!(view.get_inner().get_content().is_empty());
+
+        View::on_event(&mut view, Event::Char('o'));
+        View::on_event(&mut view, Event::Char('g'));
+        assert!(View::on_event(&mut view, Event::Char('g')).has_callback());
+        assert!(view.pending_keys().is_empty());
+        assert_eq!(&*view.get_inner().get_content(), "go");
+
+        // A mouse event interrupts the sequence.
+        View::on_event(&mut view, Event::Char('g'));
+        View::on_event(
+            &mut view,
+            Event::Mouse {
+                offset: Vec2::zero(),
+                position: Vec2::zero(),
+                event: crate::event::MouseEvent::WheelUp,
+            },
+        );
+        assert_eq!(&*view.get_inner().get_content(), "gog");
+    }
+
+    #[test]
+    fn key_help_follows_focus() {
+        use crate::views::{Dialog, DummyView, LinearLayout};
+
+        let editor = OnEventView::new(TextArea::new())
+            .on_event(Event::CtrlChar('s'), |_| ())
+            .description(Event::CtrlChar('s'), "Save")
+            .on_sequence(vec!['g', 'g'], |_| ())
+            .sequence_description(vec!['g', 'g'], "Go to top")
+            .help_source("Editor");
+        let mut view = OnEventView::new(Dialog::around(
+            LinearLayout::vertical().child(DummyView).child(editor),
+        ))
+        .on_event('q', |_| ())
+        .description('q', "Quit")
+        .help_source("Global");
+        view.layout(Vec2::new(20, 10));
+        view.take_focus(crate::direction::Direction::none());
+
+        let mut help = Vec::new();
+        view.key_help(&mut help);
+        assert_eq!(
+            help,
+            [
+                KeyHelp::new("Global", "q", "Quit"),
+                KeyHelp::new("Editor", "ctrl+s", "Save"),
+                KeyHelp::new("Editor", "g g", "Go to top"),
+            ]
+        );
+
+        view.clear_event('q');
+        help.clear();
+        view.key_help(&mut help);
+        assert_eq!(help.len(), 2);
+    }
 }
@@ -23,12 +23,17 @@ use crate::Vec2;
 pub struct PaddedView<V> {
     view: V,
     margins: Margins,
+    invalidated: bool,
 }
 
 impl<V> PaddedView<V> {
     /// Wraps `view` in a new `PaddedView` with the given margins.
     pub fn new(margins: Margins, view: V) -> Self {
-        PaddedView { view, margins }
+        PaddedView {
+            view,
+            margins,
+            invalidated: true,
+        }
     }
 
     /// Wraps `view` in a new `PaddedView` with the given margins.
@@ -44,8 +49,8 @@ impl<V> PaddedView<V> {
 
     /// Sets the margins for this view.
     pub fn set_margins(&mut self, margins: Margins) {
-        // TODO: invalidate? wrap_needs_relayout?
         self.margins = margins;
+        self.invalidated = true;
     }
 
     inner_getters!(self.view: V);
@@ -60,10 +65,15 @@ impl<V: View> ViewWrapper for PaddedView<V> {
     }
 
     fn wrap_layout(&mut self, size: Vec2) {
+        self.invalidated = false;
         let margins = self.margins.combined();
         self.view.layout(size.saturating_sub(margins));
     }
 
+    fn wrap_needs_relayout(&self) -> bool {
+        self.invalidated || self.view.needs_relayout()
+    }
+
     fn wrap_on_event(&mut self, event: Event) -> EventResult {
         let padding = self.margins.top_left();
         self.view.on_event(event.relativized(padding))
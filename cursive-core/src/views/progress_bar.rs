@@ -2,7 +2,7 @@ use crate::align::HAlign;
 use crate::theme::{ColorStyle, ColorType, Effect};
 use crate::utils::Counter;
 use crate::view::View;
-use crate::{Printer, With};
+use crate::{Printer, Vec2, With};
 use std::cmp;
 use std::thread;
 
@@ -38,6 +38,11 @@ pub struct ProgressBar {
     min: usize,
     max: usize,
     value: Counter,
+    // Value during the last layout.
+    //
+    // The counter can change from another thread, so this tells when the
+    // bar needs to be drawn again.
+    last_value: Option<usize>,
     color: ColorType,
     // TODO: use a Promise instead?
     label_maker: Box<dyn Fn(usize, (usize, usize)) -> String>,
@@ -89,6 +94,7 @@ impl ProgressBar {
             min: 0,
             max: 100,
             value: Counter::new(0),
+            last_value: None,
             color: ColorStyle::highlight().back,
             label_maker: Box::new(make_percentage),
         }
@@ -272,6 +278,14 @@ fn sub_block(extra: usize) -> &'static str {
 }
 
 impl View for ProgressBar {
+    fn layout(&mut self, _: Vec2) {
+        self.last_value = Some(self.value.get());
+    }
+
+    fn needs_relayout(&self) -> bool {
+        self.last_value != Some(self.value.get())
+    }
+
     fn draw(&self, printer: &Printer) {
         // Now, the bar itself...
         let available = printer.size.x;
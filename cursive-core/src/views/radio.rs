@@ -192,6 +192,11 @@ impl<T: 'static> View for RadioButton<T> {
         self.req_size()
     }
 
+    fn needs_relayout(&self) -> bool {
+        // The label cannot change.
+        false
+    }
+
     fn take_focus(&mut self, _: Direction) -> bool {
         self.enabled
     }
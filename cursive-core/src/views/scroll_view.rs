@@ -1,7 +1,8 @@
 use crate::{
     direction::Direction,
     event::{AnyCb, Event, EventResult},
-    view::{scroll, ScrollStrategy, Selector, View},
+    keymap::{Conflict, Keymap},
+    view::{scroll, KeyHelp, ScrollStrategy, Selector, View},
     Cursive, Printer, Rect, Vec2, With,
 };
 
@@ -386,4 +387,16 @@ where
     fn important_area(&self, size: Vec2) -> Rect {
         scroll::important_area(self, size, |s, si| s.inner.important_area(si))
     }
+
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        self.inner.key_help(help);
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        self.inner.keymap_conflicts(keymap, conflicts);
+    }
 }
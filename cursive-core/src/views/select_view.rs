@@ -76,6 +76,9 @@ pub struct SelectView<T = String> {
     // We "cache" it during the draw, so we need interior mutability.
     last_offset: Cell<Vec2>,
     last_size: Vec2,
+
+    // `true` when the items changed since the last layout.
+    invalidated: bool,
 }
 
 impl<T: 'static> Default for SelectView<T> {
@@ -100,6 +103,7 @@ impl<T: 'static> SelectView<T> {
             autojump: false,
             last_offset: Cell::new(Vec2::zero()),
             last_size: Vec2::zero(),
+            invalidated: true,
         }
     }
 
@@ -131,6 +135,7 @@ impl<T: 'static> SelectView<T> {
     /// Turns `self` into a popup select view.
     pub fn set_popup(&mut self, popup: bool) {
         self.popup = popup;
+        self.invalidated = true;
     }
 
     /// Sets a callback to be used when an item is selected.
@@ -277,6 +282,7 @@ impl<T: 'static> SelectView<T> {
     pub fn clear(&mut self) {
         self.items.clear();
         self.focus.set(0);
+        self.invalidated = true;
     }
 
     /// Adds a item to the list, with given label and value.
@@ -293,6 +299,7 @@ impl<T: 'static> SelectView<T> {
     /// ```
     pub fn add_item<S: Into<StyledString>>(&mut self, label: S, value: T) {
         self.items.push(Item::new(label.into(), value));
+        self.invalidated = true;
     }
 
     /// Gets an item at given idx or None.
@@ -316,6 +323,8 @@ impl<T: 'static> SelectView<T> {
         if i >= self.items.len() {
             None
         } else {
+            // The label may change.
+            self.invalidated = true;
             let item = &mut self.items[i];
             if let Some(t) = Rc::get_mut(&mut item.value) {
                 let label = &mut item.label;
@@ -340,6 +349,7 @@ impl<T: 'static> SelectView<T> {
     where
         T: Clone,
     {
+        self.invalidated = true;
         self.items
             .iter_mut()
             .map(|item| (&mut item.label, Rc::make_mut(&mut item.value)))
@@ -354,6 +364,7 @@ impl<T: 'static> SelectView<T> {
     pub fn try_iter_mut(
         &mut self,
     ) -> impl Iterator<Item = (&mut StyledString, Option<&mut T>)> {
+        self.invalidated = true;
         self.items
             .iter_mut()
             .map(|item| (&mut item.label, Rc::get_mut(&mut item.value)))
@@ -375,6 +386,7 @@ impl<T: 'static> SelectView<T> {
     /// You should run this callback with a `&mut Cursive`.
     pub fn remove_item(&mut self, id: usize) -> Callback {
         self.items.remove(id);
+        self.invalidated = true;
         let focus = self.focus();
         if focus >= id && focus > 0 {
             self.focus.set(focus - 1);
@@ -390,6 +402,7 @@ impl<T: 'static> SelectView<T> {
         S: Into<StyledString>,
     {
         self.items.insert(index, Item::new(label.into(), value));
+        self.invalidated = true;
     }
 
     /// Chainable variant of add_item
@@ -971,6 +984,11 @@ impl<T: 'static> View for SelectView<T> {
 
     fn layout(&mut self, size: Vec2) {
         self.last_size = size;
+        self.invalidated = false;
+    }
+
+    fn needs_relayout(&self) -> bool {
+        self.invalidated
     }
 
     fn important_area(&self, size: Vec2) -> Rect {
@@ -997,6 +1015,25 @@ impl<T> Item<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn needs_relayout() {
+        let mut view = SelectView::new().item_str("A");
+        assert!(view.needs_relayout());
+        view.layout(Vec2::new(5, 5));
+        assert!(!view.needs_relayout());
+
+        // Moving the selection keeps the same size.
+        view.on_event(Event::Key(Key::Down));
+        assert!(!view.needs_relayout());
+
+        view.add_item_str("Longer");
+        assert!(view.needs_relayout());
+        view.layout(Vec2::new(5, 5));
+
+        view.remove_item(0);
+        assert!(view.needs_relayout());
+    }
+
     #[test]
     fn select_view_sorting() {
         // We add items in no particular order, from going by their label.
@@ -165,6 +165,11 @@ impl View for SliderView {
         self.req_size()
     }
 
+    fn needs_relayout(&self) -> bool {
+        // Neither the orientation nor the maximum value can change.
+        false
+    }
+
     fn on_event(&mut self, event: Event) -> EventResult {
         match event {
             Event::Key(Key::Left)
@@ -1,8 +1,9 @@
 use crate::direction::Direction;
 use crate::event::{AnyCb, Event, EventResult};
+use crate::keymap::{Conflict, Keymap};
 use crate::theme::ColorStyle;
 use crate::view::{
-    IntoBoxedView, Offset, Position, Selector, View, ViewWrapper,
+    IntoBoxedView, KeyHelp, Offset, Position, Selector, View, ViewWrapper,
 };
 use crate::views::{BoxedView, CircularFocus, Layer, ShadowView};
 use crate::Printer;
@@ -163,6 +164,14 @@ impl<T: View> View for ChildWrapper<T> {
         }
     }
 
+    fn needs_relayout(&self) -> bool {
+        match *self {
+            ChildWrapper::Shadow(ref v) => v.needs_relayout(),
+            ChildWrapper::Backfilled(ref v) => v.needs_relayout(),
+            ChildWrapper::Plain(ref v) => v.needs_relayout(),
+        }
+    }
+
     fn take_focus(&mut self, source: Direction) -> bool {
         match *self {
             ChildWrapper::Shadow(ref mut v) => v.take_focus(source),
@@ -196,6 +205,32 @@ impl<T: View> View for ChildWrapper<T> {
             ChildWrapper::Plain(ref mut v) => v.focus_view(selector),
         }
     }
+
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        match *self {
+            ChildWrapper::Shadow(ref v) => v.key_help(help),
+            ChildWrapper::Backfilled(ref v) => v.key_help(help),
+            ChildWrapper::Plain(ref v) => v.key_help(help),
+        }
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        match *self {
+            ChildWrapper::Shadow(ref v) => {
+                v.keymap_conflicts(keymap, conflicts)
+            }
+            ChildWrapper::Backfilled(ref v) => {
+                v.keymap_conflicts(keymap, conflicts)
+            }
+            ChildWrapper::Plain(ref v) => {
+                v.keymap_conflicts(keymap, conflicts)
+            }
+        }
+    }
 }
 
 struct Child {
@@ -514,6 +549,39 @@ impl StackView {
         }
     }
 
+    /// Sends a mouse move to every layer, starting from the top.
+    ///
+    /// Layers only see the mouse where no layer above covers them.
+    fn on_mouse_move(&mut self, event: &Event) -> EventResult {
+        let position = match *event {
+            Event::Mouse {
+                position, offset, ..
+            } => position.checked_sub(offset),
+            _ => None,
+        };
+
+        let layers: Vec<_> =
+            StackPositionIterator::new(self.layers.iter_mut(), self.last_size)
+                .collect();
+
+        let mut covered = false;
+        let mut result = EventResult::Ignored;
+        for (layer, offset) in layers.into_iter().rev() {
+            let event = if covered {
+                Event::mouse_moved_away()
+            } else {
+                event.relativized(offset)
+            };
+            result = result.and(layer.view.on_event(event));
+            covered = covered
+                || matches!(
+                    position,
+                    Some(p) if p.fits_in_rect(offset, layer.size)
+                );
+        }
+        result
+    }
+
     /// Background drawing
     ///
     /// Drawing functions are split into forground and background to
@@ -617,6 +685,9 @@ impl View for StackView {
         if event == Event::WindowResize {
             self.bg_dirty.set(true);
         }
+        if event.is_mouse_move() {
+            return self.on_mouse_move(&event);
+        }
         // Use the stack position iterator to get the offset of the top layer.
         // TODO: save it instead when drawing?
         match StackPositionIterator::new(
@@ -654,6 +725,13 @@ impl View for StackView {
         }
     }
 
+    fn needs_relayout(&self) -> bool {
+        // New layers were never laid out.
+        self.layers
+            .iter()
+            .any(|layer| layer.virgin || layer.view.needs_relayout())
+    }
+
     fn required_size(&mut self, size: Vec2) -> Vec2 {
         // The min size is the max of all children's
 
@@ -689,6 +767,24 @@ impl View for StackView {
 
         Err(())
     }
+
+    fn key_help(&self, help: &mut Vec<KeyHelp>) {
+        // Only the top layer gets key events.
+        if let Some(layer) = self.layers.last() {
+            layer.view.key_help(help);
+        }
+    }
+
+    fn keymap_conflicts(
+        &self,
+        keymap: &Keymap,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        // Only the top layer gets key events.
+        if let Some(layer) = self.layers.last() {
+            layer.view.keymap_conflicts(keymap, conflicts);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -714,6 +810,24 @@ mod tests {
         assert_eq!(text.get_content().source(), "1");
     }
 
+    #[test]
+    fn needs_relayout() {
+        let mut stack = StackView::new().layer(TextView::new("1"));
+        assert!(stack.needs_relayout());
+
+        stack.layout(Vec2::new(10, 10));
+        assert!(!stack.needs_relayout());
+
+        // New layers and changed content need a layout.
+        stack.add_layer(TextView::new("2"));
+        assert!(stack.needs_relayout());
+        stack.layout(Vec2::new(10, 10));
+
+        let layer = stack.get_mut(LayerPosition::FromFront(0)).unwrap();
+        layer.downcast_mut::<TextView>().unwrap().set_content("3");
+        assert!(stack.needs_relayout());
+    }
+
     #[test]
     fn move_layer_works() {
         let mut stack = StackView::new()
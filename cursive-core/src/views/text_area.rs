@@ -1,22 +1,68 @@
 use crate::direction::Direction;
-use crate::event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use crate::event::{
+    Callback, Event, EventResult, Key, MouseButton, MouseEvent,
+};
 use crate::rect::Rect;
-use crate::theme::{ColorStyle, Effect};
+use crate::theme::{ColorStyle, Effect, PaletteColor, Style};
+use crate::utils::clipboard::Clipboard;
 use crate::utils::lines::simple::{prefix, simple_prefix, LinesIterator, Row};
-use crate::view::{ScrollBase, SizeCache, View};
+use crate::utils::markup::StyledString;
+use crate::utils::{
+    draw_placeholder, span_range, words, EditHistory, FindOptions,
+    HighlightCache, Highlighter, LineCache, TextEdit,
+};
+use crate::view::{
+    is_motion_key, is_redo, run_vi_command, scroll, EditAction, EditKeymap,
+    OnViModeChange, SizeCache, ViCommand, ViEditor, ViInput, ViMode, ViState,
+    View,
+};
+use crate::views::find_bar::FindBar;
 use crate::Vec2;
-use crate::{Printer, With, XY};
+use crate::{Cursive, Printer, With, XY};
 use log::debug;
-use std::cmp::min;
+use ropey::Rope;
+use std::borrow::Cow;
+use std::cell::{Ref, RefCell};
+use std::cmp::{max, min};
+use std::ops::Range;
+use std::rc::Rc;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+/// Line numbers shown in the gutter of a [`TextArea`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineNumbers {
+    /// No gutter is shown.
+    #[default]
+    Hidden,
+
+    /// Each line shows its number, starting at 1.
+    Absolute,
+
+    /// Each line shows its distance to the cursor line, which shows its
+    /// absolute number.
+    Relative,
+}
+
 /// Multi-lines text editor.
 ///
 /// A `TextArea` will attempt to grow vertically and horizontally
 /// dependent on the content.  Wrap it in a `ResizedView` to
 /// constrain its size.
 ///
+/// The content is stored in a rope, so editing large documents stays cheap.
+///
+/// Text can be selected with Shift+arrows, or with the mouse, and then cut,
+/// copied or pasted with Ctrl-X, Ctrl-C and Ctrl-V. By default, the clipboard
+/// is shared between all editing views (see [`clipboard`]). Note that Ctrl-C
+/// quits the application by default; clear this global callback to use it
+/// here.
+///
+/// Ctrl-Left/Right move by word. More readline-style bindings are available
+/// with [`EditKeymap::Emacs`]; there, a "line" is a paragraph of the text.
+///
+/// [`clipboard`]: crate::utils::clipboard
+///
 /// # Examples
 ///
 /// ```
@@ -30,8 +76,12 @@ use unicode_width::UnicodeWidthStr;
 ///     .min_height(5);
 /// ```
 pub struct TextArea {
-    // TODO: use a smarter data structure (rope?)
-    content: String,
+    content: Rope,
+
+    /// Flat copy of `content`, built on demand by `get_content`.
+    ///
+    /// Reset whenever the content changes.
+    flat_content: RefCell<Option<String>>,
 
     /// Byte offsets within `content` representing text rows
     ///
@@ -42,43 +92,166 @@ pub struct TextArea {
     enabled: bool,
 
     /// Base for scrolling features
-    scrollbase: ScrollBase,
+    scroll_core: scroll::Core,
 
     /// Cache to avoid re-computing layout on no-op events
+    ///
+    /// The size excludes the gutter.
     size_cache: Option<XY<SizeCache>>,
-    last_size: Vec2,
+
+    /// `true` if the rows, the cursor or the scrolling changed since the
+    /// last layout.
+    needs_layout: bool,
+
+    /// When `false`, long lines scroll horizontally instead of wrapping.
+    wrap: bool,
+
+    /// Line numbers shown on the left.
+    line_numbers: LineNumbers,
 
     /// Byte offset of the currently selected grapheme.
     cursor: usize,
+
+    /// Undo/redo history.
+    history: EditHistory,
+
+    /// Other end of the selection, if any.
+    ///
+    /// The selection spans from here to the cursor.
+    selection_anchor: Option<usize>,
+
+    /// Key bindings for editing commands.
+    keymap: EditKeymap,
+
+    /// Clipboard and kill ring used by cut, copy, paste, kill and yank.
+    clipboard: Clipboard,
+
+    /// `true` if the last event killed some text.
+    ///
+    /// Consecutive kills are merged in the kill ring.
+    killing: bool,
+
+    /// Mode and pending keys of the vi keymap.
+    vi: ViState,
+
+    /// Callback when the vi mode changes.
+    on_vi_mode_change: Option<Rc<OnViModeChange>>,
+
+    /// Syntax highlighting for each paragraph, if any.
+    highlighter: Option<Box<dyn HighlightCache>>,
+
+    /// Query and options of the current search, to highlight matches.
+    search: Option<(String, FindOptions)>,
+
+    /// When `true`, Ctrl-F opens the find bar.
+    find_bar_enabled: bool,
+
+    /// The find bar, while it is open.
+    find_bar: Option<FindBar>,
+
+    /// Text shown when the content is empty.
+    placeholder: StyledString,
+
+    /// When `false`, the placeholder is hidden while focused.
+    placeholder_when_focused: bool,
 }
 
-fn make_rows(text: &str, width: usize) -> Vec<Row> {
+/// Computes the rows for the paragraphs `first_line..=last_line`.
+///
+/// Paragraphs are wrapped independently, so re-computing a single
+/// paragraph gives the same rows as re-computing the entire text.
+fn make_rows(
+    content: &Rope,
+    first_line: usize,
+    last_line: usize,
+    width: usize,
+) -> Vec<Row> {
     // We can't make rows with width=0, so force at least width=1.
     let width = usize::max(width, 1);
-    LinesIterator::new(text, width).show_spaces().collect()
+
+    let mut rows = Vec::new();
+    for line in first_line..=last_line {
+        let (start, end) = paragraph_range(content, line);
+        let text: Cow<str> = content.byte_slice(start..end).into();
+
+        let len = rows.len();
+        rows.extend(
+            LinesIterator::new(&text, width)
+                .show_spaces()
+                .map(|row| row.shifted(start)),
+        );
+
+        if rows.len() == len {
+            // An empty paragraph still takes a row.
+            rows.push(Row {
+                start,
+                end: start,
+                width: 0,
+                is_wrapped: false,
+            });
+        }
+    }
+    rows
+}
+
+/// Returns the byte range of the given paragraph, excluding the newline.
+fn paragraph_range(content: &Rope, line: usize) -> (usize, usize) {
+    let start = content.line_to_byte(line);
+    let mut end = content.line_to_byte(line + 1);
+    if line + 1 < content.len_lines() {
+        // Skip the newline.
+        end -= 1;
+    }
+    (start, end)
 }
 
 new_default!(TextArea);
 
+impl_scroller!(TextArea::scroll_core);
+
 impl TextArea {
     /// Creates a new, empty TextArea.
     pub fn new() -> Self {
         TextArea {
-            content: String::new(),
+            content: Rope::new(),
+            flat_content: RefCell::new(None),
             rows: Vec::new(),
             enabled: true,
-            scrollbase: ScrollBase::new().right_padding(0),
+            scroll_core: scroll::Core::new().scrollbar_padding((0, 0)),
             size_cache: None,
-            last_size: Vec2::zero(),
+            needs_layout: true,
+            wrap: true,
+            line_numbers: LineNumbers::Hidden,
             cursor: 0,
+            history: EditHistory::new(),
+            selection_anchor: None,
+            keymap: EditKeymap::Default,
+            clipboard: Clipboard::global(),
+            killing: false,
+            vi: ViState::new(),
+            on_vi_mode_change: None,
+            highlighter: None,
+            search: None,
+            find_bar_enabled: false,
+            find_bar: None,
+            placeholder: StyledString::new(),
+            placeholder_when_focused: true,
         }
-        .with(|area| area.compute_rows(Vec2::new(1, 1)))
+        .with(Self::reset_rows)
         // Make sure we have valid rows, even for empty text.
     }
 
     /// Retrieves the content of the view.
-    pub fn get_content(&self) -> &str {
-        &self.content
+    ///
+    /// For large documents, the first call after a modification is
+    /// expensive, as the entire content needs to be copied.
+    pub fn get_content(&self) -> Ref<'_, str> {
+        if self.flat_content.borrow().is_none() {
+            self.flat_content.replace(Some(self.content.to_string()));
+        }
+        Ref::map(self.flat_content.borrow(), |content| {
+            content.as_deref().unwrap()
+        })
     }
 
     /// Ensures next layout call re-computes the rows.
@@ -86,6 +259,19 @@ impl TextArea {
         self.size_cache = None;
     }
 
+    /// Drops the current rows until the next layout.
+    ///
+    /// A single empty row is kept, so `rows` is never empty.
+    fn reset_rows(&mut self) {
+        self.invalidate();
+        self.rows = vec![Row {
+            start: 0,
+            end: 0,
+            width: 0,
+            is_wrapped: false,
+        }];
+    }
+
     /// Returns the position of the cursor in the content string.
     ///
     /// This is a byte index.
@@ -101,30 +287,52 @@ impl TextArea {
     /// the content string.
     pub fn set_cursor(&mut self, cursor: usize) {
         self.cursor = cursor;
+        self.needs_layout = true;
 
-        let focus = self.selected_row();
-        self.scrollbase.scroll_to(focus);
+        let area = self.inner_important_area(Vec2::zero());
+        self.scroll_core.scroll_to_rect(area);
     }
 
     /// Sets the content of the view.
+    ///
+    /// This clears the undo history.
     pub fn set_content<S: Into<String>>(&mut self, content: S) {
-        self.content = content.into();
+        self.content = Rope::from_str(&content.into());
+        *self.flat_content.get_mut() = None;
+        self.history.clear();
+        self.selection_anchor = None;
+        self.needs_layout = true;
+        if let Some(highlighter) = &mut self.highlighter {
+            highlighter.reset(self.content.len_lines());
+        }
 
         // First, make sure we are within the bounds.
-        self.cursor = min(self.cursor, self.content.len());
+        self.cursor = min(self.cursor, self.content.len_bytes());
 
         // We have no guarantee cursor is now at a correct UTF8 location.
-        // So look backward until we find a valid grapheme start.
-        while !self.content.is_char_boundary(self.cursor) {
-            self.cursor -= 1;
-        }
+        // So look backward until we find a valid char start.
+        self.cursor = self
+            .content
+            .char_to_byte(self.content.byte_to_char(self.cursor));
 
         if let Some(size) = self.size_cache.map(|s| s.map(|s| s.value)) {
             self.invalidate();
-            self.compute_rows(size);
+            self.soft_compute_rows(size);
+        } else {
+            // Don't wrap the new content until we know our size.
+            self.reset_rows();
         }
     }
 
+    /// Sets the content of the view.
+    ///
+    /// Unlike [`set_content`](#method.set_content), this is recorded in the
+    /// undo history as a single step.
+    pub fn set_content_undoable<S: Into<String>>(&mut self, content: S) {
+        let len = self.content.len_bytes();
+        self.replace_recorded(0, len, &content.into());
+    }
+
     /// Sets the content of the view.
     ///
     /// Chainable variant.
@@ -132,417 +340,1359 @@ impl TextArea {
         self.with(|s| s.set_content(content))
     }
 
-    /// Disables this view.
+    /// Reverts the last modification of the content.
     ///
-    /// A disabled view cannot be selected.
-    pub fn disable(&mut self) {
-        self.enabled = false;
+    /// Consecutive typed characters are reverted together.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.history.undo() {
+            Some((edits, cursor)) => {
+                self.apply_edits(&edits, cursor);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Disables this view.
+    /// Re-applies the last modification reverted by [`undo`](#method.undo).
     ///
-    /// Chainable variant.
-    pub fn disabled(self) -> Self {
-        self.with(Self::disable)
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.history.redo() {
+            Some((edits, cursor)) => {
+                self.apply_edits(&edits, cursor);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Re-enables this view.
-    pub fn enable(&mut self) {
-        self.enabled = true;
+    /// Forgets the undo history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
     }
 
-    /// Re-enables this view.
+    /// Sets the maximum number of undo steps to remember.
+    ///
+    /// A depth of `0` disables the undo history.
+    ///
+    /// Defaults to 100.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// Sets the maximum number of undo steps to remember.
     ///
     /// Chainable variant.
-    pub fn enabled(self) -> Self {
-        self.with(Self::enable)
+    pub fn history_depth(self, depth: usize) -> Self {
+        self.with(|s| s.set_history_depth(depth))
     }
 
-    /// Returns `true` if this view is enabled.
-    pub fn is_enabled(&self) -> bool {
-        self.enabled
+    fn apply_edits(&mut self, edits: &[TextEdit], cursor: usize) {
+        for edit in edits {
+            let end = edit.position + edit.removed.len();
+            self.replace_range(edit.position, end, &edit.inserted);
+        }
+        self.set_cursor(cursor);
     }
 
-    /// Finds the row containing the grapheme at the given offset
-    fn row_at(&self, byte_offset: usize) -> usize {
-        debug!("Offset: {}", byte_offset);
+    /// Sets the clipboard and kill ring used by this view.
+    ///
+    /// By default, views use [`Clipboard::global()`].
+    pub fn set_clipboard(&mut self, clipboard: Clipboard) {
+        self.clipboard = clipboard;
+    }
 
-        assert!(!self.rows.is_empty());
-        assert!(byte_offset >= self.rows[0].start);
+    /// Sets the clipboard and kill ring used by this view.
+    ///
+    /// Chainable variant.
+    pub fn clipboard(self, clipboard: Clipboard) -> Self {
+        self.with(|s| s.set_clipboard(clipboard))
+    }
 
-        self.rows
-            .iter()
-            .enumerate()
-            .take_while(|&(_, row)| row.start <= byte_offset)
-            .map(|(i, _)| i)
-            .last()
-            .unwrap()
+    /// Returns the clipboard and kill ring used by this view.
+    pub fn get_clipboard(&self) -> &Clipboard {
+        &self.clipboard
     }
 
-    fn col_at(&self, byte_offset: usize) -> usize {
-        let row_id = self.row_at(byte_offset);
-        let row = self.rows[row_id];
-        // Number of cells to the left of the cursor
-        self.content[row.start..byte_offset].width()
+    /// Sets the key bindings used by this view.
+    ///
+    /// With [`EditKeymap::Vi`], the view starts in normal mode.
+    pub fn set_keymap(&mut self, keymap: EditKeymap) {
+        self.keymap = keymap;
+        self.vi = ViState::new();
     }
 
-    /// Finds the row containing the cursor
-    fn selected_row(&self) -> usize {
-        assert!(!self.rows.is_empty(), "Rows should never be empty.");
-        self.row_at(self.cursor)
+    /// Sets the key bindings used by this view.
+    ///
+    /// Chainable variant.
+    pub fn keymap(self, keymap: EditKeymap) -> Self {
+        self.with(|s| s.set_keymap(keymap))
     }
 
-    fn selected_col(&self) -> usize {
-        self.col_at(self.cursor)
+    /// Returns the key bindings used by this view.
+    pub fn get_keymap(&self) -> EditKeymap {
+        self.keymap
     }
 
-    fn page_up(&mut self) {
-        for _ in 0..5 {
-            self.move_up();
+    /// Returns the current vi mode, if the keymap is [`EditKeymap::Vi`].
+    pub fn get_vi_mode(&self) -> Option<ViMode> {
+        match self.keymap {
+            EditKeymap::Vi => Some(self.vi.mode()),
+            _ => None,
         }
     }
 
-    fn page_down(&mut self) {
-        for _ in 0..5 {
-            self.move_down();
-        }
+    /// Sets a callback to be called when the vi mode changes.
+    ///
+    /// This can be used to show the mode in a status line.
+    pub fn set_on_vi_mode_change<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut Cursive, ViMode) + 'static,
+    {
+        self.on_vi_mode_change = Some(Rc::new(callback));
     }
 
-    fn move_up(&mut self) {
-        let row_id = self.selected_row();
-        if row_id == 0 {
-            return;
+    /// Sets a callback to be called when the vi mode changes.
+    ///
+    /// Chainable variant.
+    pub fn on_vi_mode_change<F>(self, callback: F) -> Self
+    where
+        F: Fn(&mut Cursive, ViMode) + 'static,
+    {
+        self.with(|s| s.set_on_vi_mode_change(callback))
+    }
+
+    /// Runs a vi command, and switches to the resulting mode.
+    fn run_vi(&mut self, command: ViCommand, count: usize) -> EventResult {
+        self.history.seal();
+        let mode = run_vi_command(self, self.vi.mode(), command, count);
+        if !self.vi.set_mode(mode) {
+            return EventResult::Consumed(None);
         }
+        let callback = self
+            .on_vi_mode_change
+            .clone()
+            .map(|cb| Callback::from_fn(move |s| cb(s, mode)));
+        EventResult::Consumed(callback)
+    }
 
-        // Number of cells to the left of the cursor
-        let x = self.col_at(self.cursor);
+    /// Sets a syntax highlighter for the content.
+    ///
+    /// Each paragraph is given to the highlighter when it first becomes
+    /// visible, and again only after it (or the state it starts with)
+    /// changes.
+    ///
+    /// Colors from the highlighter are used for the text, over the usual
+    /// background of the view.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cursive_core::theme::{BaseColor, Color, Style};
+    /// use cursive_core::utils::span::IndexedSpan;
+    /// use cursive_core::views::TextArea;
+    ///
+    /// // Show comments in blue.
+    /// let text_area = TextArea::new()
+    ///     .content("key = value # comment")
+    ///     .highlighter(|line: &str| match line.find('#') {
+    ///         Some(start) => {
+    ///             let style = Style::from(Color::Dark(BaseColor::Blue));
+    ///             let mut span =
+    ///                 IndexedSpan::simple_borrowed(&line[start..], style);
+    ///             span.content.offset(start);
+    ///             vec![span]
+    ///         }
+    ///         None => Vec::new(),
+    ///     });
+    /// ```
+    pub fn set_highlighter<H>(&mut self, highlighter: H)
+    where
+        H: Highlighter + 'static,
+    {
+        let mut cache = LineCache::new(highlighter);
+        cache.reset(self.content.len_lines());
+        self.highlighter = Some(Box::new(cache));
+        self.needs_layout = true;
+    }
 
-        let prev_row = self.rows[row_id - 1];
-        let prev_text = &self.content[prev_row.start..prev_row.end];
-        let offset = prefix(prev_text.graphemes(true), x, "").length;
-        self.cursor = prev_row.start + offset;
+    /// Sets a syntax highlighter for the content.
+    ///
+    /// Chainable variant.
+    pub fn highlighter<H>(self, highlighter: H) -> Self
+    where
+        H: Highlighter + 'static,
+    {
+        self.with(|s| s.set_highlighter(highlighter))
     }
 
-    fn move_down(&mut self) {
-        let row_id = self.selected_row();
-        if row_id + 1 == self.rows.len() {
-            return;
-        }
-        let x = self.col_at(self.cursor);
+    /// Removes the syntax highlighter, if any.
+    pub fn clear_highlighter(&mut self) {
+        self.highlighter = None;
+    }
 
-        let next_row = self.rows[row_id + 1];
-        let next_text = &self.content[next_row.start..next_row.end];
-        let offset = prefix(next_text.graphemes(true), x, "").length;
-        self.cursor = next_row.start + offset;
+    /// Controls content wrap for this view.
+    ///
+    /// If `true` (the default), long lines are wrapped to the available
+    /// width. Otherwise, the view scrolls horizontally to follow the cursor.
+    pub fn set_content_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+        self.scroll_core.set_scroll_x(!wrap);
+        self.invalidate();
     }
 
-    /// Moves the cursor to the left.
+    /// Disables content wrap for this view.
     ///
-    /// Wraps the previous line if required.
-    fn move_left(&mut self) {
-        let len = {
-            // We don't want to utf8-parse the entire content.
-            // So only consider the last row.
-            let mut row = self.selected_row();
-            if self.rows[row].start == self.cursor {
-                row = row.saturating_sub(1);
-            }
+    /// Chainable variant.
+    pub fn no_wrap(self) -> Self {
+        self.with(|s| s.set_content_wrap(false))
+    }
 
-            let text = &self.content[self.rows[row].start..self.cursor];
-            text.graphemes(true).last().unwrap().len()
-        };
-        self.cursor -= len;
+    /// Returns `true` if long lines are wrapped.
+    pub fn get_content_wrap(&self) -> bool {
+        self.wrap
     }
 
-    /// Moves the cursor to the right.
+    /// Sets the line numbers shown in a gutter on the left.
     ///
-    /// Jumps to the next line is required.
-    fn move_right(&mut self) {
-        let len = self.content[self.cursor..]
-            .graphemes(true)
-            .next()
-            .unwrap()
-            .len();
-        self.cursor += len;
+    /// The line with the cursor is also highlighted when numbers are shown.
+    pub fn set_line_numbers(&mut self, line_numbers: LineNumbers) {
+        self.line_numbers = line_numbers;
+        self.invalidate();
     }
 
-    fn is_cache_valid(&self, size: Vec2) -> bool {
-        match self.size_cache {
-            None => false,
-            Some(ref last) => last.x.accept(size.x) && last.y.accept(size.y),
-        }
+    /// Sets the line numbers shown in a gutter on the left.
+    ///
+    /// Chainable variant.
+    pub fn line_numbers(self, line_numbers: LineNumbers) -> Self {
+        self.with(|s| s.set_line_numbers(line_numbers))
     }
 
-    // If we are editing the text, we add a fake "space" character for the
-    // cursor to indicate where the next character will appear.
-    // If the current line is full, adding a character will overflow into the
-    // next line. To show that, we need to add a fake "ghost" row, just for
-    // the cursor.
-    fn fix_ghost_row(&mut self) {
-        if self.rows.is_empty()
-            || self.rows.last().unwrap().end != self.content.len()
-        {
-            // Add a fake, empty row at the end.
-            self.rows.push(Row {
-                start: self.content.len(),
-                end: self.content.len(),
-                width: 0,
-                is_wrapped: false,
-            });
-        }
+    /// Returns the line numbers shown in the gutter.
+    pub fn get_line_numbers(&self) -> LineNumbers {
+        self.line_numbers
     }
 
-    fn soft_compute_rows(&mut self, size: Vec2) {
-        if self.is_cache_valid(size) {
-            debug!("Cache is still valid.");
-            return;
+    /// Returns the width of the gutter, including a space after the numbers.
+    fn gutter_width(&self) -> usize {
+        match self.line_numbers {
+            LineNumbers::Hidden => 0,
+            _ => self.content.len_lines().to_string().len() + 1,
         }
-        debug!("Computing! Oh yeah!");
-
-        let mut available = size.x;
+    }
 
-        self.rows = make_rows(&self.content, available);
-        self.fix_ghost_row();
+    /// Selects the next match of `query`, after the cursor or selection.
+    ///
+    /// The search wraps around the end of the content. All matches are
+    /// highlighted until [`clear_search`](#method.clear_search) is called.
+    ///
+    /// Returns `false` if there is no match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cursive_core::utils::FindOptions;
+    /// use cursive_core::views::TextArea;
+    ///
+    /// let mut text_area = TextArea::new().content("Foo bar foo");
+    /// let options = FindOptions::new().case_sensitive(true);
+    ///
+    /// assert!(text_area.find_next("foo", options));
+    /// assert_eq!(text_area.selection(), Some(8..11));
+    /// ```
+    pub fn find_next(&mut self, query: &str, options: FindOptions) -> bool {
+        let from = self.selection().map_or(self.cursor, |s| s.end);
+        self.find_from(query, options, from)
+    }
 
-        if self.rows.len() > size.y {
-            available = available.saturating_sub(1);
-            // Apparently we'll need a scrollbar. Doh :(
-            self.rows = make_rows(&self.content, available);
-            self.fix_ghost_row();
-        }
+    /// Selects the previous match of `query`, before the cursor or
+    /// selection.
+    ///
+    /// The search wraps around the start of the content.
+    ///
+    /// Returns `false` if there is no match.
+    pub fn find_prev(&mut self, query: &str, options: FindOptions) -> bool {
+        self.search = Some((query.to_string(), options));
+
+        let before = self.selection().map_or(self.cursor, |s| s.start);
+        let found = options
+            .find_backward_in_rope(&self.content, query, before)
+            .or_else(|| {
+                let len = self.content.len_bytes();
+                options.find_backward_in_rope(&self.content, query, len)
+            });
 
-        if !self.rows.is_empty() {
-            self.size_cache = Some(SizeCache::build(size, size));
+        match found {
+            Some(found) => {
+                self.select_match(found);
+                true
+            }
+            None => false,
         }
     }
 
-    fn compute_rows(&mut self, size: Vec2) {
-        self.soft_compute_rows(size);
-        self.scrollbase.set_heights(size.y, self.rows.len());
+    /// Replaces the selected match of `query`, then selects the next one.
+    ///
+    /// If the selection is not a match, only selects the next one.
+    ///
+    /// Returns `true` if a match was replaced.
+    pub fn replace(
+        &mut self,
+        query: &str,
+        replacement: &str,
+        options: FindOptions,
+    ) -> bool {
+        let replaced = match self.selection() {
+            Some(selection)
+                if options.is_match_in_rope(
+                    &self.content,
+                    query,
+                    selection.clone(),
+                ) =>
+            {
+                self.replace_recorded(
+                    selection.start,
+                    selection.end,
+                    replacement,
+                );
+                true
+            }
+            _ => false,
+        };
+        self.find_next(query, options);
+        replaced
     }
 
-    fn backspace(&mut self) {
-        self.move_left();
-        self.delete();
-    }
+    /// Replaces every match of `query`.
+    ///
+    /// This is recorded as a single step in the undo history.
+    ///
+    /// Returns the number of replaced matches.
+    pub fn replace_all(
+        &mut self,
+        query: &str,
+        replacement: &str,
+        options: FindOptions,
+    ) -> usize {
+        let matches = options.find_all_in_rope(&self.content, query);
+        let (start, end) = match (matches.first(), matches.last()) {
+            (Some(first), Some(last)) => (first.start, last.end),
+            _ => return 0,
+        };
 
-    fn delete(&mut self) {
-        if self.cursor == self.content.len() {
-            return;
+        let mut text = String::new();
+        let mut position = start;
+        for found in &matches {
+            text.push_str(&self.text(position, found.start));
+            text.push_str(replacement);
+            position = found.end;
         }
-        debug!("Rows: {:?}", self.rows);
-        let len = self.content[self.cursor..]
-            .graphemes(true)
-            .next()
-            .unwrap()
-            .len();
-        let start = self.cursor;
-        let end = self.cursor + len;
-        debug!("Start/end: {}/{}", start, end);
-        debug!("Content: `{}`", self.content);
-        for _ in self.content.drain(start..end) {}
-        debug!("Content: `{}`", self.content);
 
-        let selected_row = self.selected_row();
-        debug!("Selected row: {}", selected_row);
-        if self.cursor == self.rows[selected_row].end {
-            // We're removing an (implicit) newline.
-            // This means merging two rows.
-            let new_end = self.rows[selected_row + 1].end;
-            self.rows[selected_row].end = new_end;
-            self.rows.remove(selected_row + 1);
-        }
-        self.rows[selected_row].end -= len;
+        self.replace_recorded(start, end, &text);
+        matches.len()
+    }
 
-        // update all the rows downstream
-        for row in &mut self.rows.iter_mut().skip(1 + selected_row) {
-            row.rev_shift(len);
-        }
-        debug!("Rows: {:?}", self.rows);
+    /// Stops highlighting the matches of the last search.
+    pub fn clear_search(&mut self) {
+        self.search = None;
+    }
 
-        self.fix_damages();
-        debug!("Rows: {:?}", self.rows);
+    /// Enables or disables the find bar.
+    ///
+    /// When enabled, Ctrl-F opens a search bar at the bottom of the view.
+    /// There, Enter or Down select the next match, Up selects the previous
+    /// one, Alt-C toggles case sensitivity, Alt-W toggles whole-word
+    /// matching, and Esc or Ctrl-F close the bar.
+    ///
+    /// Disabled by default.
+    pub fn set_find_bar(&mut self, enabled: bool) {
+        self.find_bar_enabled = enabled;
+        if !enabled && self.find_bar.take().is_some() {
+            self.needs_layout = true;
+        }
     }
 
-    fn insert(&mut self, ch: char) {
-        // First, we inject the data, but keep the cursor unmoved
-        // (So the cursor is to the left of the injected char)
-        self.content.insert(self.cursor, ch);
+    /// Enables or disables the find bar.
+    ///
+    /// Chainable variant.
+    pub fn find_bar(self, enabled: bool) -> Self {
+        self.with(|s| s.set_find_bar(enabled))
+    }
 
-        // Then, we shift the indexes of every row after this one.
-        let shift = ch.len_utf8();
+    /// Returns `true` if Ctrl-F opens the find bar.
+    pub fn get_find_bar(&self) -> bool {
+        self.find_bar_enabled
+    }
 
-        // The current row grows, every other is just shifted.
-        let selected_row = self.selected_row();
-        self.rows[selected_row].end += shift;
+    /// Sets a placeholder shown when the content is empty.
+    ///
+    /// The placeholder is wrapped to the width of the view. Unstyled parts
+    /// use the secondary palette color. It is never part of the content.
+    pub fn set_placeholder<S: Into<StyledString>>(&mut self, placeholder: S) {
+        self.placeholder = placeholder.into();
+    }
 
-        for row in &mut self.rows.iter_mut().skip(1 + selected_row) {
-            row.shift(shift);
-        }
-        self.cursor += shift;
+    /// Sets a placeholder shown when the content is empty.
+    ///
+    /// Chainable variant.
+    pub fn placeholder<S: Into<StyledString>>(self, placeholder: S) -> Self {
+        self.with(|s| s.set_placeholder(placeholder))
+    }
 
-        // Finally, rows may not have the correct width anymore, so fix them.
-        self.fix_damages();
+    /// Returns the placeholder shown when the content is empty.
+    pub fn get_placeholder(&self) -> &StyledString {
+        &self.placeholder
     }
 
-    /// Fix a damage located at the cursor.
+    /// Sets whether the placeholder stays visible while this view is
+    /// focused.
     ///
-    /// The only damages are assumed to have occured around the cursor.
+    /// Defaults to `true`.
+    pub fn set_placeholder_when_focused(&mut self, show: bool) {
+        self.placeholder_when_focused = show;
+    }
+
+    /// Sets whether the placeholder stays visible while this view is
+    /// focused.
     ///
-    /// This is an optimization to not re-compute the entire rows when an
-    /// insert happened.
-    fn fix_damages(&mut self) {
-        if self.size_cache.is_none() {
-            // If we don't know our size, we'll get a layout command soon.
-            // So no need to do that here.
-            return;
+    /// Chainable variant.
+    pub fn placeholder_when_focused(self, show: bool) -> Self {
+        self.with(|s| s.set_placeholder_when_focused(show))
+    }
+
+    /// Selects the first match of `query` after `from`, wrapping around.
+    fn find_from(
+        &mut self,
+        query: &str,
+        options: FindOptions,
+        from: usize,
+    ) -> bool {
+        self.search = Some((query.to_string(), options));
+
+        let found = options
+            .find_forward_in_rope(&self.content, query, from)
+            .or_else(|| options.find_forward_in_rope(&self.content, query, 0));
+
+        match found {
+            Some(found) => {
+                self.select_match(found);
+                true
+            }
+            None => false,
         }
+    }
 
-        let size = self.size_cache.unwrap().map(|s| s.value);
+    /// Selects the given range, and scrolls to show it.
+    fn select_match(&mut self, found: Range<usize>) {
+        self.selection_anchor = Some(found.start);
+        self.cursor = found.end;
+        self.needs_layout = true;
+
+        let start =
+            Vec2::new(self.col_at(found.start), self.row_at(found.start));
+        let end = Vec2::new(self.col_at(found.end), self.row_at(found.end));
+        self.scroll_core.scroll_to_rect(Rect::from_corners(
+            Vec2::min(start, end),
+            Vec2::max(start, end),
+        ));
+    }
 
-        // Find affected text.
-        // We know the damage started at this row, so it'll need to go.
-        //
-        // Actually, if possible, also re-compute the previous row.
-        // Indeed, the previous row may have been cut short, and if we now
-        // break apart a big word, maybe the first half can go up one level.
-        let first_row = self.selected_row().saturating_sub(1);
-
-        let first_byte = self.rows[first_row].start;
-
-        // We don't need to go beyond a newline.
-        // If we don't find one, end of the text it is.
-        debug!("Cursor: {}", self.cursor);
-        let last_byte = self.content[self.cursor..]
-            .find('\n')
-            .map(|i| 1 + i + self.cursor);
-        let last_row = last_byte
-            .map_or(self.rows.len(), |last_byte| self.row_at(last_byte));
-        let last_byte = last_byte.unwrap_or_else(|| self.content.len());
-
-        debug!("Content: `{}` (len={})", self.content, self.content.len());
-        debug!("start/end: {}/{}", first_byte, last_byte);
-        debug!("start/end rows: {}/{}", first_row, last_row);
+    /// Returns the matches of the current search around the visible rows.
+    fn visible_matches(&self) -> Vec<Range<usize>> {
+        let (query, options) = match self.search {
+            Some((ref query, options)) => (query, options),
+            None => return Vec::new(),
+        };
 
-        // Do we have access to the entire width?...
-        let mut available = size.x;
+        let viewport = self.scroll_core.content_viewport();
+        let first = min(viewport.top(), self.rows.len() - 1);
+        let last = min(viewport.bottom(), self.rows.len() - 1);
+
+        // Search entire paragraphs, to find matches crossing rows.
+        let line = self.content.byte_to_line(self.rows[first].start);
+        let start = self.content.line_to_byte(line);
+        let end = self.paragraph_end(self.rows[last].end);
+        let text = self.text(start, end);
+
+        options
+            .find_all(&text, query)
+            .into_iter()
+            .map(|found| found.start + start..found.end + start)
+            .collect()
+    }
 
-        let scrollable = self.rows.len() > size.y;
-        if scrollable {
-            // ... not if a scrollbar is there
-            available = available.saturating_sub(1);
+    /// Opens the find bar, starting with the selected text.
+    fn open_find_bar(&mut self) {
+        let query = match self.selected_text() {
+            Some(text) if !text.contains('\n') => text.to_string(),
+            _ => String::new(),
+        };
+        let options = self
+            .search
+            .as_ref()
+            .map_or_else(FindOptions::default, |&(_, options)| options);
+        let origin = self.selection().map_or(self.cursor, |s| s.start);
+
+        if !query.is_empty() {
+            self.search = Some((query.clone(), options));
         }
+        self.find_bar = Some(FindBar::new(&query, options, origin));
+    }
 
-        // First attempt, if scrollbase status didn't change.
-        debug!("Rows: {:?}", self.rows);
+    /// Handles an event while the find bar is open.
+    ///
+    /// Returns `None` if the event is for the content instead.
+    fn on_find_bar_event(&mut self, event: Event) -> Option<EventResult> {
+        let bar = self.find_bar.as_mut()?;
+        let query = bar.query();
+        let options = bar.options;
+
+        match event {
+            Event::Key(Key::Esc) | Event::CtrlChar('f') => {
+                self.find_bar = None;
+                self.search = None;
+            }
+            Event::Key(Key::Enter) | Event::Key(Key::Down) => {
+                let found = self.find_next(&query, options);
+                self.find_bar.as_mut()?.found = found;
+            }
+            Event::Key(Key::Up) => {
+                let found = self.find_prev(&query, options);
+                self.find_bar.as_mut()?.found = found;
+            }
+            Event::Mouse {
+                offset, position, ..
+            } if position.y
+                != offset.y + self.scroll_core.last_outer_size().y =>
+            {
+                return None;
+            }
+            event => {
+                let event = match event {
+                    Event::Mouse {
+                        offset,
+                        position,
+                        event,
+                    } => Event::Mouse {
+                        offset: offset
+                            + (0, self.scroll_core.last_outer_size().y),
+                        position,
+                        event,
+                    },
+                    event => event,
+                };
+                let result = bar.on_event(event);
+
+                // Search again as the query changes.
+                let (new_query, new_options) = (bar.query(), bar.options);
+                if (&new_query, new_options) != (&query, options) {
+                    let origin = bar.origin;
+                    self.selection_anchor = None;
+                    self.cursor = origin;
+                    let found = new_query.is_empty()
+                        || self.find_from(&new_query, new_options, origin);
+                    if new_query.is_empty() {
+                        self.search = None;
+                    }
+                    self.find_bar.as_mut()?.found = found;
+                }
+                return Some(result);
+            }
+        }
+        Some(EventResult::Consumed(None))
+    }
+
+    /// Returns the selected byte range, if any.
+    ///
+    /// Returns `None` if the selection is empty.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor?;
+        if anchor == self.cursor {
+            None
+        } else {
+            Some(min(anchor, self.cursor)..max(anchor, self.cursor))
+        }
+    }
+
+    /// Selects the given byte range.
+    ///
+    /// The cursor is moved to the end of the range.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, or not on char boundaries.
+    pub fn set_selection(&mut self, selection: Range<usize>) {
+        // Panics if out of bounds or not on a char boundary.
+        self.content.byte_slice(selection.clone());
+
+        self.selection_anchor = Some(selection.start);
+        self.set_cursor(selection.end);
+    }
+
+    /// Selects the given byte range.
+    ///
+    /// Chainable variant.
+    pub fn selected(self, selection: Range<usize>) -> Self {
+        self.with(|s| s.set_selection(selection))
+    }
+
+    /// Clears the selection, if any.
+    ///
+    /// The content is not modified.
+    pub fn clear_selection(&mut self) {
+        self.selection_anchor = None;
+    }
+
+    /// Returns the selected text, if any.
+    ///
+    /// The text is only copied if the selection spans several chunks of
+    /// the underlying rope.
+    pub fn selected_text(&self) -> Option<Cow<'_, str>> {
+        self.selection()
+            .map(|selection| self.text(selection.start, selection.end))
+    }
+
+    /// Copies the selected text to the clipboard.
+    ///
+    /// Does nothing if the selection is empty.
+    pub fn copy(&self) {
+        if let Some(selection) = self.selection() {
+            self.clipboard
+                .set_content(self.text(selection.start, selection.end));
+        }
+    }
+
+    /// Moves the selected text to the clipboard.
+    pub fn cut(&mut self) {
+        if let Some(selection) = self.selection() {
+            self.copy();
+            self.replace_recorded(selection.start, selection.end, "");
+        }
+    }
+
+    /// Inserts the clipboard content at the cursor position.
+    ///
+    /// Replaces the selection, if any.
+    pub fn paste(&mut self) {
+        self.insert_text(&self.clipboard.get_content());
+    }
+
+    /// Inserts the last killed text at the cursor position.
+    fn yank(&mut self) {
+        if let Some(text) = self.clipboard.yank() {
+            self.insert_text(&text);
+        }
+    }
+
+    /// Removes the text between `start` and `end`, and adds it to the kill
+    /// ring.
+    ///
+    /// If `merge` is `true`, the text is added to the last kill ring entry.
+    fn kill(&mut self, start: usize, end: usize, merge: bool) {
+        if start == end {
+            self.killing = merge;
+            return;
+        }
+
+        let text = self.text(start, end);
+        if !merge {
+            self.clipboard.kill(text);
+        } else if end == self.cursor {
+            self.clipboard.kill_prepend(&text);
+        } else {
+            self.clipboard.kill_append(&text);
+        }
+        self.killing = true;
+
+        self.replace_recorded(start, end, "");
+    }
+
+    /// Inserts `text` at the cursor position, replacing the selection.
+    fn insert_text(&mut self, text: &str) {
+        let selection = self.selection().unwrap_or(self.cursor..self.cursor);
+        self.replace_recorded(selection.start, selection.end, text);
+    }
+
+    /// Returns the cursor position targeted by the given action.
+    ///
+    /// For kills, the text between the cursor and this position is removed.
+    fn action_target(&self, action: EditAction) -> usize {
+        let line = self.content.byte_to_line(self.cursor);
+        let (start, end) = paragraph_range(&self.content, line);
+        match action {
+            EditAction::WordLeft | EditAction::KillWordLeft => {
+                self.prev_word_start()
+            }
+            EditAction::WordRight | EditAction::KillWordRight => {
+                self.next_word_end()
+            }
+            EditAction::LineStart | EditAction::KillLineStart => start,
+            // At the end of a line, kill the newline itself.
+            EditAction::KillLineEnd if self.cursor == end => {
+                self.paragraph_end(self.cursor)
+            }
+            EditAction::LineEnd | EditAction::KillLineEnd => end,
+            EditAction::Yank => self.cursor,
+        }
+    }
+
+    /// Returns the start of the word before the cursor.
+    ///
+    /// Looks into previous paragraphs if needed.
+    fn prev_word_start(&self) -> usize {
+        let mut line = self.content.byte_to_line(self.cursor);
+        let mut end = self.cursor;
+        loop {
+            let start = self.content.line_to_byte(line);
+            let text = self.text(start, end);
+            if let Some(word) = words::prev_word_start(&text, text.len()) {
+                return start + word;
+            }
+            if line == 0 {
+                return 0;
+            }
+            line -= 1;
+            end = paragraph_range(&self.content, line).1;
+        }
+    }
+
+    /// Returns the end of the word after the cursor.
+    ///
+    /// Looks into next paragraphs if needed.
+    fn next_word_end(&self) -> usize {
+        let mut line = self.content.byte_to_line(self.cursor);
+        let mut start = self.cursor;
+        loop {
+            let end = paragraph_range(&self.content, line).1;
+            let text = self.text(start, end);
+            if let Some(word) = words::next_word_end(&text, 0) {
+                return start + word;
+            }
+            line += 1;
+            if line >= self.content.len_lines() {
+                return self.content.len_bytes();
+            }
+            start = self.content.line_to_byte(line);
+        }
+    }
+
+    /// Returns the byte offset under the given position.
+    ///
+    /// `position` is relative to the top-left corner of the content.
+    fn offset_at(&self, position: Vec2) -> usize {
+        let y = position.y;
+        let row = self.rows[min(y, self.rows.len() - 1)];
+        let content = self.text(row.start, row.end);
+
+        row.start + simple_prefix(&content, position.x).length
+    }
+
+    /// Selects the word under the cursor.
+    fn select_word(&mut self) {
+        let line = self.content.byte_to_line(self.cursor);
+        let (start, end) = paragraph_range(&self.content, line);
+        let text = self.text(start, end);
+
+        let (word_start, word_end) =
+            words::word_at(&text, self.cursor - start);
+        self.selection_anchor = Some(start + word_start);
+        self.cursor = start + word_end;
+    }
+
+    /// Selects the line under the cursor, with its newline.
+    fn select_line(&mut self) {
+        let line = self.content.byte_to_line(self.cursor);
+        let (start, end) = paragraph_range(&self.content, line);
+        self.selection_anchor = Some(start);
+        self.cursor = min(end + 1, self.content.len_bytes());
+    }
+
+    /// Disables this view.
+    ///
+    /// A disabled view cannot be selected.
+    pub fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    /// Disables this view.
+    ///
+    /// Chainable variant.
+    pub fn disabled(self) -> Self {
+        self.with(Self::disable)
+    }
+
+    /// Re-enables this view.
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// Re-enables this view.
+    ///
+    /// Chainable variant.
+    pub fn enabled(self) -> Self {
+        self.with(Self::enable)
+    }
+
+    /// Returns `true` if this view is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the text between the given byte offsets.
+    ///
+    /// Only borrows from the rope when the range is contiguous in memory.
+    fn text(&self, start: usize, end: usize) -> Cow<'_, str> {
+        self.content.byte_slice(start..end).into()
+    }
+
+    /// Returns the byte offset of the end of the paragraph containing
+    /// `byte_offset`, including the newline.
+    fn paragraph_end(&self, byte_offset: usize) -> usize {
+        let line = self.content.byte_to_line(byte_offset);
+        self.content.line_to_byte(line + 1)
+    }
+
+    /// Returns the length of the grapheme starting at `byte_offset`.
+    fn next_grapheme_len(&self, byte_offset: usize) -> usize {
+        let end = self.paragraph_end(byte_offset);
+        self.text(byte_offset, end)
+            .graphemes(true)
+            .next()
+            .unwrap()
+            .len()
+    }
+
+    /// Finds the row containing the grapheme at the given offset
+    fn row_at(&self, byte_offset: usize) -> usize {
+        assert!(!self.rows.is_empty());
+        assert!(byte_offset >= self.rows[0].start);
+
+        self.rows.partition_point(|row| row.start <= byte_offset) - 1
+    }
+
+    fn col_at(&self, byte_offset: usize) -> usize {
+        let row_id = self.row_at(byte_offset);
+        let row = self.rows[row_id];
+        // Number of cells to the left of the cursor
+        self.text(row.start, byte_offset).width()
+    }
+
+    /// Finds the row containing the cursor
+    fn selected_row(&self) -> usize {
+        assert!(!self.rows.is_empty(), "Rows should never be empty.");
+        self.row_at(self.cursor)
+    }
+
+    fn selected_col(&self) -> usize {
+        self.col_at(self.cursor)
+    }
+
+    fn page_up(&mut self) {
+        for _ in 0..5 {
+            self.move_up();
+        }
+    }
+
+    fn page_down(&mut self) {
+        for _ in 0..5 {
+            self.move_down();
+        }
+    }
+
+    fn move_up(&mut self) {
+        if let Some(cursor) = self.row_target(self.cursor, false) {
+            self.cursor = cursor;
+        }
+    }
+
+    fn move_down(&mut self) {
+        if let Some(cursor) = self.row_target(self.cursor, true) {
+            self.cursor = cursor;
+        }
+    }
+
+    /// Returns the offset in the row above or below `byte_offset`, in the
+    /// same column.
+    ///
+    /// Returns `None` on the first or last row.
+    fn row_target(&self, byte_offset: usize, down: bool) -> Option<usize> {
+        let row_id = self.row_at(byte_offset);
+        let target_id = if down {
+            Some(row_id + 1).filter(|&id| id < self.rows.len())
+        } else {
+            row_id.checked_sub(1)
+        }?;
+
+        // Number of cells to the left of the cursor
+        let x = self.col_at(byte_offset);
+
+        let row = self.rows[target_id];
+        let text = self.text(row.start, row.end);
+        let offset = prefix(text.graphemes(true), x, "").length;
+        Some(row.start + offset)
+    }
+
+    /// Moves the cursor to the left.
+    ///
+    /// Wraps the previous line if required.
+    fn move_left(&mut self) {
+        let len = {
+            // We don't want to utf8-parse the entire content.
+            // So only consider the last row.
+            let mut row = self.selected_row();
+            if self.rows[row].start == self.cursor {
+                row = row.saturating_sub(1);
+            }
+
+            let text = self.text(self.rows[row].start, self.cursor);
+            text.graphemes(true).last().unwrap().len()
+        };
+        self.cursor -= len;
+    }
+
+    /// Moves the cursor to the right.
+    ///
+    /// Jumps to the next line is required.
+    fn move_right(&mut self) {
+        self.cursor += self.next_grapheme_len(self.cursor);
+    }
+
+    fn is_cache_valid(&self, size: Vec2) -> bool {
+        match self.size_cache {
+            None => false,
+            // Without wrapping, rows don't depend on the size.
+            Some(_) if !self.wrap => true,
+            Some(ref last) => last.x.accept(size.x) && last.y.accept(size.y),
+        }
+    }
+
+    fn soft_compute_rows(&mut self, size: Vec2) {
+        if self.is_cache_valid(size) {
+            debug!("Cache is still valid.");
+            return;
+        }
+        debug!("Computing! Oh yeah!");
+
+        let mut available = size.x;
+        let last_line = self.content.len_lines() - 1;
+
+        if !self.wrap {
+            // Each paragraph is a single row.
+            available = usize::MAX;
+        }
+        self.rows = make_rows(&self.content, 0, last_line, available);
+
+        if self.wrap && self.rows.len() > size.y {
+            available = available.saturating_sub(1);
+            // Apparently we'll need a scrollbar. Doh :(
+            self.rows = make_rows(&self.content, 0, last_line, available);
+        }
+
+        if !self.rows.is_empty() {
+            self.size_cache = Some(SizeCache::build(size, size));
+        }
+    }
+
+    /// Gives the size of the content to the scroll core.
+    ///
+    /// `size` is the size available for the content and the scrollbars.
+    fn update_scroller(&mut self, size: Vec2) {
+        let width = if self.wrap {
+            0
+        } else {
+            // Keep a cell for the cursor at the end of the longest row.
+            self.rows.iter().map(|row| row.width).max().unwrap_or(0) + 1
+        };
+        let content = Vec2::new(width, self.rows.len());
+
+        // A scrollbar on one axis takes room from the other one.
+        let bars = |scrolling: XY<bool>| {
+            scrolling.swap().select_or(Vec2::new(1, 1), Vec2::zero())
+        };
+        let scrolling = content.zip_map(size, |c, s| c > s);
+        let scrolling = content
+            .zip_map(size.saturating_sub(bars(scrolling)), |c, s| c > s);
+        let available = size.saturating_sub(bars(scrolling));
+
+        self.scroll_core.set_last_size(size, scrolling);
+        self.scroll_core
+            .set_inner_size(Vec2::new(max(width, available.x), content.y));
+        self.scroll_core.update_offset();
+    }
+
+    /// Highlights the visible paragraphs that changed.
+    fn update_highlights(&mut self) {
+        if let Some(highlighter) = &mut self.highlighter {
+            let last_row = min(
+                self.scroll_core.content_viewport().bottom() + 1,
+                self.rows.len(),
+            );
+            let last_row = &self.rows[last_row.saturating_sub(1)];
+            let last_line = self.content.byte_to_line(last_row.start);
+            highlighter.update(&self.content, last_line);
+        }
+    }
+
+    /// Draws the highlighted parts of `row`, whose text is `text`.
+    ///
+    /// `background` is the palette color behind the row.
+    fn draw_highlights(
+        &self,
+        printer: &Printer,
+        row: &Row,
+        text: &str,
+        effect: Effect,
+        background: PaletteColor,
+    ) {
+        let highlighter = match self.highlighter {
+            Some(ref highlighter) => highlighter,
+            None => return,
+        };
+
+        let line = self.content.byte_to_line(row.start);
+        let line_start = self.content.line_to_byte(line);
+        for span in highlighter.spans(line) {
+            let (start, end) = match span_range(span) {
+                Some((start, end)) => (start + line_start, end + line_start),
+                None => continue,
+            };
+
+            // Part of the span on this row.
+            let start = max(start, row.start) - row.start;
+            let end = min(end, row.end).saturating_sub(row.start);
+            let part = match text.get(start..end) {
+                Some(part) if start < end => part,
+                _ => continue,
+            };
+
+            // The view is drawn reversed: swap the colors back so the
+            // highlighted text stays on the usual background.
+            let style = match span.attr.color {
+                Some(color) if effect == Effect::Reverse => Style {
+                    color: Some(ColorStyle::new(background, color.front)),
+                    ..span.attr
+                },
+                _ => span.attr,
+            };
+
+            let x = text[..start].width();
+            printer.with_effect(effect, |printer| {
+                printer.with_style(style, |printer| {
+                    printer.print((x, 0), part);
+                });
+            });
+        }
+    }
+
+    fn backspace(&mut self) {
+        let cursor = self.cursor;
+        self.move_left();
+        self.delete_recorded(cursor);
+    }
+
+    fn delete(&mut self) {
+        let cursor = self.cursor;
+        self.delete_recorded(cursor);
+    }
+
+    /// Deletes the grapheme under the cursor.
+    ///
+    /// `cursor_before` is the cursor position to restore when undoing.
+    fn delete_recorded(&mut self, cursor_before: usize) {
+        if self.cursor == self.content.len_bytes() {
+            return;
+        }
+        let start = self.cursor;
+        let end = start + self.next_grapheme_len(start);
+        debug!("Start/end: {}/{}", start, end);
+
+        let removed = self.replace_range(start, end, "");
+        self.history.record(
+            TextEdit::removal(start, removed),
+            cursor_before,
+            start,
+            false,
+        );
+    }
+
+    fn insert(&mut self, ch: char) {
+        let mut buf = [0; 4];
+        let text = ch.encode_utf8(&mut buf);
+
+        if let Some(selection) = self.selection() {
+            // Typing replaces the selection.
+            self.replace_recorded(selection.start, selection.end, text);
+            return;
+        }
+
+        let cursor = self.cursor;
+        self.replace_range(cursor, cursor, text);
+        self.cursor += text.len();
+
+        self.history.record(
+            TextEdit::insertion(cursor, &*text),
+            cursor,
+            self.cursor,
+            true,
+        );
+    }
+
+    /// Replaces the text between `start` and `end` with `text`.
+    ///
+    /// This is recorded as a single step in the undo history, and the cursor
+    /// is moved after the inserted text.
+    fn replace_recorded(&mut self, start: usize, end: usize, text: &str) {
+        let cursor = self.cursor;
+        let removed = self.replace_range(start, end, text);
+        self.cursor = start + text.len();
+
+        let edit = TextEdit {
+            position: start,
+            removed,
+            inserted: text.to_string(),
+        };
+        self.history.record(edit, cursor, self.cursor, false);
+    }
+
+    /// Replaces the text between `start` and `end` with `text`.
+    ///
+    /// Returns the removed text.
+    ///
+    /// Rows are updated and the selection is cleared, but the cursor is left
+    /// unchanged.
+    fn replace_range(
+        &mut self,
+        start: usize,
+        end: usize,
+        text: &str,
+    ) -> String {
+        self.selection_anchor = None;
+        let removed = self.text(start, end).into_owned();
+
+        if let Some(highlighter) = &mut self.highlighter {
+            let first = self.content.byte_to_line(start);
+            let removed = removed.matches('\n').count() + 1;
+            let inserted = text.matches('\n').count() + 1;
+            highlighter.edit(first, removed, inserted);
+        }
+
+        let char_start = self.content.byte_to_char(start);
+        let char_end = self.content.byte_to_char(end);
+        self.content.remove(char_start..char_end);
+        self.content.insert(char_start, text);
+        *self.flat_content.get_mut() = None;
+        self.needs_layout = true;
+
+        self.fix_damages(start, removed.len(), text.len());
+
+        removed
+    }
+
+    /// Fix a damage located at `position`.
+    ///
+    /// `removed` bytes were replaced with `inserted` bytes at `position`.
+    ///
+    /// This is an optimization to not re-compute the entire rows when an
+    /// edit happened: only the affected paragraphs are wrapped again.
+    fn fix_damages(
+        &mut self,
+        position: usize,
+        removed: usize,
+        inserted: usize,
+    ) {
+        if self.size_cache.is_none() {
+            // If we don't know our size, we'll get a layout command soon.
+            // So no need to do that here.
+            return;
+        }
+
+        let size = self.size_cache.unwrap().map(|s| s.value);
+
+        // Find affected paragraphs, in the new content.
+        let first_line = self.content.byte_to_line(position);
+        let last_line = self.content.byte_to_line(position + inserted);
+        let first_byte = self.content.line_to_byte(first_line);
+        let (_, last_byte) = paragraph_range(&self.content, last_line);
+
+        // Rows for these paragraphs, in the old content.
+        let old_last_byte = last_byte + removed - inserted;
+        let first_row =
+            self.rows.partition_point(|row| row.start < first_byte);
+        let last_row =
+            self.rows.partition_point(|row| row.start <= old_last_byte);
+
+        debug!("start/end: {}/{}", first_byte, last_byte);
+        debug!("start/end rows: {}/{}", first_row, last_row);
+
+        // Do we have access to the entire width?...
+        let mut available = size.x;
+
+        let scrollable = self.rows.len() > size.y;
+        if scrollable {
+            // ... not if a scrollbar is there
+            available = available.saturating_sub(1);
+        }
+        if !self.wrap {
+            // ... and it doesn't matter if we don't wrap.
+            available = usize::MAX;
+        }
+
+        // First attempt, if scrollbase status didn't change.
         let new_rows =
-            make_rows(&self.content[first_byte..last_byte], available);
+            make_rows(&self.content, first_line, last_line, available);
         // How much did this add?
-        debug!("New rows: {:?}", new_rows);
-        debug!("{}-{}", first_row, last_row);
         let new_row_count =
             self.rows.len() + new_rows.len() + first_row - last_row;
-        if !scrollable && new_row_count > size.y {
+        if self.wrap && !scrollable && new_row_count > size.y {
             // We just changed scrollable status.
             // This changes everything.
-            // TODO: compute_rows() currently makes a scroll-less attempt.
+            // TODO: soft_compute_rows() currently makes a scroll-less attempt.
             // Here, we know it's just no gonna happen.
             self.invalidate();
-            self.compute_rows(size);
+            self.soft_compute_rows(size);
             return;
         }
 
         // Otherwise, replace stuff.
-        let affected_rows = first_row..last_row;
-        let replacement_rows =
-            new_rows.into_iter().map(|row| row.shifted(first_byte));
-        self.rows.splice(affected_rows, replacement_rows);
-        self.fix_ghost_row();
-        self.scrollbase.set_heights(size.y, self.rows.len());
+        let shifted_rows = first_row + new_rows.len();
+        self.rows.splice(first_row..last_row, new_rows);
+
+        // And shift every row downstream.
+        for row in &mut self.rows[shifted_rows..] {
+            if inserted >= removed {
+                row.shift(inserted - removed);
+            } else {
+                row.rev_shift(removed - inserted);
+            }
+        }
     }
-}
 
-impl View for TextArea {
-    fn required_size(&mut self, constraint: Vec2) -> Vec2 {
-        // Make sure our structure is up to date
-        self.soft_compute_rows(constraint);
+    /// Handles an event, before the gutter and scrolling are accounted for.
+    fn outer_on_event(&mut self, event: Event) -> EventResult {
+        if !self.enabled {
+            return EventResult::Ignored;
+        }
 
-        // Ideally, we'd want x = the longest row + 1
-        // (we always keep a space at the end)
-        // And y = number of rows
-        debug!("{:?}", self.rows);
-        let scroll_width = if self.rows.len() > constraint.y { 1 } else { 0 };
+        if self.find_bar.is_some() {
+            if let Some(result) = self.on_find_bar_event(event.clone()) {
+                return result;
+            }
+        } else if self.find_bar_enabled && event == Event::CtrlChar('f') {
+            self.open_find_bar();
+            return EventResult::Consumed(None);
+        }
 
-        let content_width = if self.rows.iter().any(|row| row.is_wrapped) {
-            // If any row has been wrapped, we want to take the full width.
-            constraint.x.saturating_sub(1 + scroll_width)
-        } else {
-            self.rows.iter().map(|r| r.width).max().unwrap_or(1)
+        // Mouse events are relative to the content, right of the gutter.
+        let event = match event {
+            Event::Mouse {
+                offset,
+                position,
+                event,
+            } => Event::Mouse {
+                offset: offset + (self.gutter_width(), 0),
+                position,
+                event,
+            },
+            event => event,
         };
 
-        Vec2::new(scroll_width + 1 + content_width, self.rows.len())
+        scroll::on_event(
+            self,
+            event,
+            Self::inner_on_event,
+            Self::inner_important_area,
+        )
     }
 
-    fn draw(&self, printer: &Printer) {
-        printer.with_color(ColorStyle::secondary(), |printer| {
-            let effect = if self.enabled && printer.enabled {
-                Effect::Reverse
-            } else {
-                Effect::Simple
-            };
-
-            let w = if self.scrollbase.scrollable() {
-                printer.size.x.saturating_sub(1)
-            } else {
-                printer.size.x
-            };
-            printer.with_effect(effect, |printer| {
-                for y in 0..printer.size.y {
-                    printer.print_hline((0, y), w, " ");
+    /// Handles an event, relative to the scrolled content.
+    fn inner_on_event(&mut self, event: Event) -> EventResult {
+        let event = match self.keymap {
+            EditKeymap::Vi => match self.vi.on_event(event) {
+                ViInput::Pass(event) => event,
+                ViInput::Consumed => return EventResult::Consumed(None),
+                ViInput::Command(command, count) => {
+                    return self.run_vi(command, count);
                 }
-            });
+            },
+            _ => event,
+        };
 
-            debug!("Content: `{}`", &self.content);
-            self.scrollbase.draw(printer, |printer, i| {
-                debug!("Drawing row {}", i);
-                let row = &self.rows[i];
-                debug!("row: {:?}", row);
-                let text = &self.content[row.start..row.end];
-                debug!("row text: `{}`", text);
-                printer.with_effect(effect, |printer| {
-                    printer.print((0, 0), text);
-                });
+        match event {
+            Event::Char(_) | Event::Key(Key::Enter) => (),
+            // Anything but typing ends the current undo group.
+            _ => self.history.seal(),
+        }
 
-                if printer.focused && i == self.selected_row() {
-                    let cursor_offset = self.cursor - row.start;
-                    let c = if cursor_offset == text.len() {
-                        "_"
-                    } else {
-                        text[cursor_offset..]
-                            .graphemes(true)
-                            .next()
-                            .expect("Found no char!")
-                    };
-                    let offset = text[..cursor_offset].width();
-                    printer.print((offset, 0), c);
-                }
-            });
-        });
-    }
+        // Shift+motion extends the selection, other motions clear it.
+        let (event, selecting) = match event {
+            Event::Shift(key) if is_motion_key(key) => (Event::Key(key), true),
+            Event::CtrlShift(key) if is_motion_key(key) => {
+                (Event::Ctrl(key), true)
+            }
+            event => (event, false),
+        };
+        let action = self.keymap.action(&event);
+        let merge_kill = std::mem::replace(&mut self.killing, false);
 
-    fn on_event(&mut self, event: Event) -> EventResult {
-        if !self.enabled {
-            return EventResult::Ignored;
+        let is_motion = match (action, &event) {
+            (Some(action), _) => action.is_motion(),
+            (None, &Event::Key(key)) | (None, &Event::Ctrl(key)) => {
+                is_motion_key(key)
+            }
+            _ => false,
+        };
+        if is_motion {
+            if !selecting {
+                self.selection_anchor = None;
+            } else if self.selection_anchor.is_none() {
+                self.selection_anchor = Some(self.cursor);
+            }
+        }
+
+        if let Some(action) = action {
+            match action {
+                EditAction::Yank => self.yank(),
+                _ if action.is_motion() => {
+                    self.cursor = self.action_target(action)
+                }
+                _ => {
+                    let target = self.action_target(action);
+                    let (start, end) =
+                        (min(self.cursor, target), max(self.cursor, target));
+                    self.kill(start, end, merge_kill);
+                }
+            }
+            return EventResult::Consumed(None);
         }
 
-        let mut fix_scroll = true;
+        let len = self.content.len_bytes();
         match event {
             Event::Char(ch) => self.insert(ch),
             Event::Key(Key::Enter) => self.insert('\n'),
-            Event::Key(Key::Backspace) if self.cursor > 0 => self.backspace(),
-            Event::Key(Key::Del) if self.cursor < self.content.len() => {
-                self.delete()
+            Event::CtrlChar('z') => {
+                self.undo();
+            }
+            ref event if is_redo(event) => {
+                self.redo();
+            }
+            Event::CtrlChar('x') => self.cut(),
+            Event::CtrlChar('c') => self.copy(),
+            Event::CtrlChar('v') => self.paste(),
+            Event::Paste(text) => {
+                // Terminals usually send newlines as carriage returns.
+                let text = text.replace("\r\n", "\n").replace('\r', "\n");
+                self.insert_text(&text);
             }
+            Event::Key(Key::Backspace) | Event::Key(Key::Del)
+                if self.selection().is_some() =>
+            {
+                let selection = self.selection().unwrap();
+                self.replace_recorded(selection.start, selection.end, "");
+            }
+            Event::Key(Key::Backspace) if self.cursor > 0 => self.backspace(),
+            Event::Key(Key::Del) if self.cursor < len => self.delete(),
 
             Event::Key(Key::End) => {
                 let row = self.selected_row();
@@ -554,7 +1704,7 @@ impl View for TextArea {
                 }
             }
             Event::Ctrl(Key::Home) => self.cursor = 0,
-            Event::Ctrl(Key::End) => self.cursor = self.content.len(),
+            Event::Ctrl(Key::End) => self.cursor = len,
             Event::Key(Key::Home) => {
                 self.cursor = self.rows[self.selected_row()].start
             }
@@ -567,95 +1717,64 @@ impl View for TextArea {
             Event::Key(Key::PageUp) => self.page_up(),
             Event::Key(Key::PageDown) => self.page_down(),
             Event::Key(Key::Left) if self.cursor > 0 => self.move_left(),
-            Event::Key(Key::Right) if self.cursor < self.content.len() => {
-                self.move_right()
-            }
+            Event::Key(Key::Right) if self.cursor < len => self.move_right(),
             Event::Mouse {
-                event: MouseEvent::WheelUp,
-                ..
-            } if self.scrollbase.can_scroll_up() => {
-                fix_scroll = false;
-                self.scrollbase.scroll_up(5);
-            }
-            Event::Mouse {
-                event: MouseEvent::WheelDown,
-                ..
-            } if self.scrollbase.can_scroll_down() => {
-                fix_scroll = false;
-                self.scrollbase.scroll_down(5);
-            }
-            Event::Mouse {
-                event: MouseEvent::Press(MouseButton::Left),
+                event: MouseEvent::Hold(MouseButton::Left),
                 position,
                 offset,
-            } if position
-                .checked_sub(offset)
-                .map(|position| {
-                    self.scrollbase.start_drag(position, self.last_size.x)
-                })
-                .unwrap_or(false) =>
-            {
-                fix_scroll = false;
+            } if self.selection_anchor.is_some() => {
+                // Extend the selection.
+                self.cursor = self.offset_at(position.saturating_sub(offset));
             }
             Event::Mouse {
-                event: MouseEvent::Hold(MouseButton::Left),
-                position,
-                offset,
+                event: MouseEvent::Release(MouseButton::Left),
+                ..
             } => {
-                fix_scroll = false;
-                let position = position.saturating_sub(offset);
-                self.scrollbase.drag(position);
+                if self.selection().is_none() {
+                    self.selection_anchor = None;
+                }
+                // Let the scroll core release the scrollbar, if needed.
+                return EventResult::Ignored;
             }
             Event::Mouse {
-                event: MouseEvent::Press(_),
+                event: MouseEvent::Press(button),
                 position,
                 offset,
-            } if !self.rows.is_empty()
-                && position.fits_in_rect(offset, self.last_size) =>
-            {
+            } if !self.rows.is_empty() => {
                 if let Some(position) = position.checked_sub(offset) {
-                    let y = position.y + self.scrollbase.start_line;
-                    let y = min(y, self.rows.len() - 1);
-                    let x = position.x;
-                    let row = &self.rows[y];
-                    let content = &self.content[row.start..row.end];
-
-                    self.cursor = row.start + simple_prefix(content, x).length;
+                    self.cursor = self.offset_at(position);
                 }
+
+                // This may be the start of a drag.
+                self.selection_anchor = match button {
+                    MouseButton::Left => Some(self.cursor),
+                    _ => None,
+                };
             }
+            Event::Mouse {
+                event: MouseEvent::DoubleClick(MouseButton::Left),
+                ..
+            } if !self.rows.is_empty() => self.select_word(),
+            Event::Mouse {
+                event: MouseEvent::TripleClick(MouseButton::Left),
+                ..
+            } if !self.rows.is_empty() => self.select_line(),
             _ => return EventResult::Ignored,
         }
 
-        debug!("Rows: {:?}", self.rows);
-        if fix_scroll {
-            let focus = self.selected_row();
-            self.scrollbase.scroll_to(focus);
-        }
-
         EventResult::Consumed(None)
     }
 
-    fn take_focus(&mut self, _: Direction) -> bool {
-        self.enabled
-    }
-
-    fn layout(&mut self, size: Vec2) {
-        self.last_size = size;
-        self.compute_rows(size);
-    }
-
-    fn important_area(&self, _: Vec2) -> Rect {
+    /// Returns the area around the cursor, in the content.
+    fn inner_important_area(&self, _: Vec2) -> Rect {
         // The important area is a single character
-        let char_width = if self.cursor >= self.content.len() {
+        let char_width = if self.cursor >= self.content.len_bytes() {
             // If we're are the end of the content, it'll be a space
             1
         } else {
             // Otherwise it's the selected grapheme
-            self.content[self.cursor..]
-                .graphemes(true)
-                .next()
-                .unwrap()
-                .width()
+            let len = self.next_grapheme_len(self.cursor);
+            self.text(self.cursor, self.cursor + len).width()
         };
 
         Rect::from_size(
@@ -663,4 +1782,653 @@ impl View for TextArea {
             (char_width, 1),
         )
     }
+
+    /// Draws the line numbers for the visible rows.
+    fn draw_gutter(&self, printer: &Printer) {
+        let viewport = self.scroll_core.content_viewport();
+        let first = min(viewport.top(), self.rows.len());
+        let last = min(viewport.bottom() + 1, self.rows.len());
+        let cursor_line = self.content.byte_to_line(self.cursor);
+        let digits = printer.size.x.saturating_sub(1);
+
+        for (y, row) in self.rows[first..last].iter().enumerate() {
+            printer.print_hline((0, y), printer.size.x, " ");
+
+            let line = self.content.byte_to_line(row.start);
+            if row.start != self.content.line_to_byte(line) {
+                // Wrapped rows have no number.
+                continue;
+            }
+
+            let number = match self.line_numbers {
+                LineNumbers::Relative if line != cursor_line => {
+                    max(line, cursor_line) - min(line, cursor_line)
+                }
+                _ => line + 1,
+            };
+            let effect = if line == cursor_line {
+                Effect::Bold
+            } else {
+                Effect::Simple
+            };
+            printer.with_effect(effect, |printer| {
+                printer.print((0, y), &format!("{:>1$}", number, digits));
+            });
+        }
+    }
+
+    /// Draws the row `i`.
+    ///
+    /// `matches` are the search matches to highlight.
+    fn draw_row(
+        &self,
+        printer: &Printer,
+        i: usize,
+        effect: Effect,
+        matches: &[Range<usize>],
+    ) {
+        let row = match self.rows.get(i) {
+            Some(row) => row,
+            None => return,
+        };
+        let text = self.text(row.start, row.end);
+
+        let current = self.line_numbers != LineNumbers::Hidden
+            && effect == Effect::Reverse
+            && self.content.byte_to_line(row.start)
+                == self.content.byte_to_line(self.cursor);
+        let background = if current {
+            PaletteColor::CurrentLine
+        } else {
+            PaletteColor::Secondary
+        };
+
+        let style = ColorStyle::new(background, PaletteColor::View);
+        printer.with_color(style, |printer| {
+            printer.with_effect(effect, |printer| {
+                if current {
+                    printer.print_hline((0, 0), printer.size.x, " ");
+                }
+                printer.print((0, 0), &text);
+            });
+            self.draw_highlights(printer, row, &text, effect, background);
+        });
+
+        // Search matches are shown with the usual colors, not reversed.
+        for found in matches {
+            let start = max(found.start, row.start) - row.start;
+            let end = min(found.end, row.end).saturating_sub(row.start);
+            if start < end {
+                let x = text[..start].width();
+                printer.with_effect(Effect::Underline, |printer| {
+                    printer.print((x, 0), &text[start..end]);
+                });
+            }
+        }
+
+        // Highlight the selected part of this row.
+        if let Some(selection) = self.selection() {
+            let start = max(selection.start, row.start) - row.start;
+            let end = min(selection.end, row.end).saturating_sub(row.start);
+            if start < end {
+                let style = if printer.focused {
+                    ColorStyle::highlight()
+                } else {
+                    ColorStyle::highlight_inactive()
+                };
+                let x = text[..start].width();
+                printer.with_color(style, |printer| {
+                    printer.print((x, 0), &text[start..end]);
+                });
+            }
+        }
+
+        if printer.focused && i == self.selected_row() {
+            let cursor_offset = self.cursor - row.start;
+            let c = if cursor_offset == text.len() {
+                "_"
+            } else {
+                text[cursor_offset..]
+                    .graphemes(true)
+                    .next()
+                    .expect("Found no char!")
+            };
+            let offset = text[..cursor_offset].width();
+            printer.print((offset, 0), c);
+        }
+    }
+}
+
+impl View for TextArea {
+    fn required_size(&mut self, constraint: Vec2) -> Vec2 {
+        let gutter = self.gutter_width();
+        let bar_height = if self.find_bar.is_some() { 1 } else { 0 };
+        let constraint = constraint.saturating_sub((gutter, bar_height));
+
+        // Make sure our structure is up to date
+        self.soft_compute_rows(constraint);
+
+        // Ideally, we'd want x = the longest row + 1
+        // (we always keep a space at the end)
+        // And y = number of rows
+        let scroll_width = if self.rows.len() > constraint.y { 1 } else { 0 };
+
+        let longest = self.rows.iter().map(|r| r.width).max().unwrap_or(1);
+        let content_width =
+            if self.wrap && self.rows.iter().any(|row| row.is_wrapped) {
+                // If any row has been wrapped, we want to take the full width.
+                constraint.x.saturating_sub(1 + scroll_width)
+            } else {
+                longest
+            };
+
+        // Without wrapping, long rows need a horizontal scrollbar.
+        let scroll_height =
+            if !self.wrap && scroll_width + 1 + longest > constraint.x {
+                1
+            } else {
+                0
+            };
+
+        Vec2::new(
+            gutter + scroll_width + 1 + content_width,
+            self.rows.len() + scroll_height + bar_height,
+        )
+    }
+
+    fn draw(&self, printer: &Printer) {
+        let printer = match self.find_bar {
+            Some(ref bar) => {
+                let height = printer.size.y.saturating_sub(1);
+                bar.draw(&printer.offset((0, height)));
+                // The find bar has the focus.
+                printer.cropped((printer.size.x, height)).focused(false)
+            }
+            None => printer.clone(),
+        };
+
+        let gutter = self.gutter_width();
+        let matches = self.visible_matches();
+        printer.with_color(ColorStyle::secondary(), |printer| {
+            self.draw_gutter(&printer.cropped((gutter, printer.size.y)));
+
+            let printer = printer.offset((gutter, 0));
+            let effect = if self.enabled && printer.enabled {
+                Effect::Reverse
+            } else {
+                Effect::Simple
+            };
+
+            let size = printer
+                .size
+                .saturating_sub(self.scroll_core.scrollbar_size());
+            printer.with_effect(effect, |printer| {
+                for y in 0..size.y {
+                    printer.print_hline((0, y), size.x, " ");
+                }
+            });
+
+            scroll::draw_lines(self, &printer, |s, printer, i| {
+                s.draw_row(printer, i, effect, &matches)
+            });
+
+            let focused = printer.focused;
+            if self.content.len_bytes() == 0
+                && !self.placeholder.is_empty()
+                && (self.placeholder_when_focused || !focused)
+            {
+                draw_placeholder(
+                    &printer.cropped(size),
+                    &self.placeholder,
+                    ColorStyle::secondary(),
+                );
+                if focused {
+                    // The cursor stays visible on the first character.
+                    let c = self.placeholder.source().graphemes(true).next();
+                    printer.with_effect(Effect::Reverse, |printer| {
+                        printer.print((0, 0), c.unwrap_or(" "));
+                    });
+                }
+            }
+        });
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        let result = self.outer_on_event(event);
+        if result.is_consumed() {
+            // The content, the cursor or the scrolling may have changed.
+            self.needs_layout = true;
+        }
+        result
+    }
+
+    fn needs_relayout(&self) -> bool {
+        self.needs_layout || self.size_cache.is_none()
+    }
+
+    fn take_focus(&mut self, _: Direction) -> bool {
+        self.enabled
+    }
+
+    fn layout(&mut self, mut size: Vec2) {
+        self.needs_layout = false;
+        if let Some(ref mut bar) = self.find_bar {
+            bar.layout(Vec2::new(size.x, 1));
+            size.y = size.y.saturating_sub(1);
+        }
+
+        let size = size.saturating_sub((self.gutter_width(), 0));
+        self.soft_compute_rows(size);
+        self.update_scroller(size);
+        self.update_highlights();
+    }
+
+    fn important_area(&self, size: Vec2) -> Rect {
+        if self.find_bar.is_some() {
+            let y = size.y.saturating_sub(1);
+            return Rect::from_size((0, y), (size.x, 1));
+        }
+
+        let gutter = self.gutter_width();
+        let size = size.saturating_sub((gutter, 0));
+        scroll::important_area(self, size, Self::inner_important_area)
+            + (gutter, 0)
+    }
+}
+
+impl ViEditor for TextArea {
+    fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
+    fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn move_cursor(&mut self, cursor: usize) {
+        self.set_cursor(cursor);
+    }
+
+    fn anchor(&self) -> Option<usize> {
+        self.selection_anchor
+    }
+
+    fn set_anchor(&mut self, anchor: Option<usize>) {
+        self.selection_anchor = anchor;
+    }
+
+    fn is_multiline(&self) -> bool {
+        true
+    }
+
+    fn line_count(&self) -> usize {
+        self.content.len_lines()
+    }
+
+    fn line_at(&self, offset: usize) -> usize {
+        self.content.byte_to_line(offset)
+    }
+
+    fn line_range(&self, line: usize) -> (usize, usize) {
+        paragraph_range(&self.content, line)
+    }
+
+    fn slice(&self, start: usize, end: usize) -> Cow<'_, str> {
+        self.text(start, end)
+    }
+
+    fn row_target(&self, offset: usize, down: bool) -> Option<usize> {
+        TextArea::row_target(self, offset, down)
+    }
+
+    fn replace_text(&mut self, start: usize, end: usize, text: &str) {
+        self.replace_recorded(start, end, text);
+    }
+
+    fn begin_change(&mut self, start: usize, end: usize, text: &str) {
+        let cursor = self.cursor;
+        let removed = self.replace_range(start, end, text);
+        self.set_cursor(start + text.len());
+
+        let edit = TextEdit {
+            position: start,
+            removed,
+            inserted: text.to_string(),
+        };
+        self.history.record_change(edit, cursor, self.cursor);
+    }
+
+    fn undo_step(&mut self) -> bool {
+        self.undo()
+    }
+
+    fn redo_step(&mut self) -> bool {
+        self.redo()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::PrintBuffer;
+    use crate::theme::Theme;
+    use crate::utils::span::IndexedSpan;
+
+    fn full_rows(area: &TextArea, width: usize) -> Vec<Row> {
+        let last_line = area.content.len_lines() - 1;
+        make_rows(&area.content, 0, last_line, width)
+    }
+
+    #[test]
+    fn incremental_rows_match_full_layout() {
+        let mut area = TextArea::new().content("abc def\n\nghi jkl mno");
+        area.layout(Vec2::new(6, 20));
+
+        for ch in "xyz uvw\nrst".chars() {
+            area.on_event(Event::Char(ch));
+            assert_eq!(area.rows, full_rows(&area, 6));
+        }
+
+        area.on_event(Event::Key(Key::Up));
+        for _ in 0..5 {
+            area.on_event(Event::Key(Key::Backspace));
+            assert_eq!(area.rows, full_rows(&area, 6));
+        }
+
+        while area.undo() {
+            assert_eq!(area.rows, full_rows(&area, 6));
+        }
+        assert_eq!(&*area.get_content(), "abc def\n\nghi jkl mno");
+    }
+
+    #[test]
+    fn needs_relayout_after_changes() {
+        let mut area = TextArea::new().content("abc");
+        assert!(area.needs_relayout());
+        area.layout(Vec2::new(6, 3));
+        assert!(!area.needs_relayout());
+
+        // Ignored events change nothing.
+        area.on_event(Event::Key(Key::Left));
+        area.on_event(Event::Refresh);
+        assert!(!area.needs_relayout());
+
+        area.on_event(Event::Char('d'));
+        assert!(area.needs_relayout());
+        area.layout(Vec2::new(6, 3));
+
+        area.set_cursor(0);
+        assert!(area.needs_relayout());
+        area.layout(Vec2::new(6, 3));
+
+        area.set_content("xyz");
+        assert!(area.needs_relayout());
+    }
+
+    #[test]
+    fn undo_redo_keys() {
+        let mut area = TextArea::new();
+        area.on_event(Event::Char('a'));
+        area.on_event(Event::CtrlChar('z'));
+        assert_eq!(&*area.get_content(), "");
+
+        area.on_event(Event::CtrlChar('y'));
+        assert_eq!(&*area.get_content(), "a");
+        area.on_event(Event::CtrlChar('z'));
+        area.on_event(Event::Combo("ctrl+shift+z".parse().unwrap()));
+        assert_eq!(&*area.get_content(), "a");
+
+        // The Emacs keymap yanks instead.
+        let mut area = TextArea::new()
+            .keymap(EditKeymap::Emacs)
+            .clipboard(Clipboard::new());
+        area.on_event(Event::Char('a'));
+        area.on_event(Event::CtrlChar('w'));
+        assert_eq!(&*area.get_content(), "");
+        area.on_event(Event::CtrlChar('y'));
+        area.on_event(Event::CtrlChar('y'));
+        assert_eq!(&*area.get_content(), "aa");
+    }
+
+    #[test]
+    fn get_content_follows_edits() {
+        let mut area = TextArea::new().content("héllo");
+        assert_eq!(&*area.get_content(), "héllo");
+
+        let len = area.get_content().len();
+        area.set_cursor(len);
+        area.on_event(Event::Key(Key::Left));
+        area.on_event(Event::Key(Key::Left));
+        area.on_event(Event::Key(Key::Left));
+        area.on_event(Event::Key(Key::Backspace));
+        assert_eq!(&*area.get_content(), "hllo");
+        assert_eq!(area.cursor(), 1);
+    }
+
+    #[test]
+    fn placeholder_is_not_content() {
+        let mut area = TextArea::new().placeholder("Type here");
+        area.layout(Vec2::new(20, 3));
+        assert_eq!(&*area.get_content(), "");
+        assert_eq!(area.get_placeholder().source(), "Type here");
+
+        let theme = Theme::default();
+        let mut buffer = PrintBuffer::new();
+        buffer.resize(Vec2::new(20, 3));
+        area.draw(&Printer::new((20, 3), &theme, &buffer));
+        assert_eq!(buffer.row(0), "Type here           ");
+
+        area.on_event(Event::Char('a'));
+        assert_eq!(&*area.get_content(), "a");
+
+        area.layout(Vec2::new(20, 3));
+        let mut buffer = PrintBuffer::new();
+        buffer.resize(Vec2::new(20, 3));
+        area.draw(&Printer::new((20, 3), &theme, &buffer));
+        assert_eq!(buffer.row(0), "a_                  ");
+    }
+
+    #[test]
+    fn selected_text_slices_the_rope() {
+        let text = "0123456789\n".repeat(1000);
+        let area = TextArea::new().content(text.as_str()).selected(5005..5020);
+
+        assert_eq!(area.selected_text().as_deref(), Some(&text[5005..5020]));
+        assert!(area.flat_content.borrow().is_none());
+    }
+
+    #[test]
+    fn shift_selection_cut_paste() {
+        let mut area = TextArea::new()
+            .content("hello world")
+            .clipboard(Clipboard::new());
+        let len = area.get_content().len();
+        area.set_cursor(len);
+
+        for _ in 0..5 {
+            area.on_event(Event::Shift(Key::Left));
+        }
+        assert_eq!(area.selected_text().as_deref(), Some("world"));
+
+        area.on_event(Event::CtrlChar('x'));
+        assert_eq!(&*area.get_content(), "hello ");
+        assert_eq!(area.selection(), None);
+
+        area.on_event(Event::Key(Key::Home));
+        area.on_event(Event::CtrlChar('v'));
+        assert_eq!(&*area.get_content(), "worldhello ");
+        assert_eq!(area.cursor(), 5);
+
+        area.undo();
+        area.undo();
+        assert_eq!(&*area.get_content(), "hello world");
+    }
+
+    #[test]
+    fn emacs_kill_and_yank() {
+        let mut area = TextArea::new()
+            .content("foo bar baz\nqux")
+            .keymap(EditKeymap::Emacs)
+            .clipboard(Clipboard::new());
+        area.set_cursor(11);
+
+        area.on_event(Event::CtrlChar('w'));
+        area.on_event(Event::CtrlChar('w'));
+        assert_eq!(&*area.get_content(), "foo \nqux");
+
+        area.on_event(Event::CtrlChar('a'));
+        area.on_event(Event::CtrlChar('y'));
+        assert_eq!(&*area.get_content(), "bar bazfoo \nqux");
+
+        // Killing at the end of a line joins the next one.
+        area.on_event(Event::CtrlChar('k'));
+        area.on_event(Event::CtrlChar('k'));
+        assert_eq!(&*area.get_content(), "bar bazqux");
+        assert_eq!(area.get_clipboard().yank().as_deref(), Some("foo \n"));
+
+        area.on_event(Event::CtrlChar('a'));
+        area.on_event(Event::AltChar('f'));
+        area.on_event(Event::AltChar('f'));
+        assert_eq!(area.cursor(), 10);
+    }
+
+    #[test]
+    fn vi_modal_editing() {
+        let mut area = TextArea::new()
+            .content("foo bar\nbaz qux quux")
+            .keymap(EditKeymap::Vi);
+        area.layout(Vec2::new(20, 5));
+        assert_eq!(area.get_vi_mode(), Some(ViMode::Normal));
+
+        fn keys(area: &mut TextArea, keys: &str) {
+            for c in keys.chars() {
+                area.on_event(Event::Char(c));
+            }
+        }
+        // The last word of a line is deleted without joining lines.
+        keys(&mut area, "wdw");
+        keys(&mut area, "j0d2w");
+        assert_eq!(&*area.get_content(), "foo \nquux");
+
+        keys(&mut area, "ggcwbar");
+        assert_eq!(area.get_vi_mode(), Some(ViMode::Insert));
+        area.on_event(Event::Key(Key::Esc));
+        assert_eq!(area.get_vi_mode(), Some(ViMode::Normal));
+        assert_eq!(&*area.get_content(), "bar \nquux");
+        assert_eq!(area.cursor(), 2);
+
+        area.on_event(Event::Char('u'));
+        assert_eq!(&*area.get_content(), "foo \nquux");
+
+        keys(&mut area, "Gdd");
+        assert_eq!(&*area.get_content(), "foo ");
+    }
+
+    #[test]
+    fn highlights_follow_edits() {
+        // Highlights the whole line when it starts with '#'.
+        let comments = |line: &str| {
+            if line.starts_with('#') {
+                vec![IndexedSpan::simple_borrowed(line, Style::none())]
+            } else {
+                Vec::new()
+            }
+        };
+        let mut area =
+            TextArea::new().content("a\n# b\nc").highlighter(comments);
+        area.layout(Vec2::new(10, 5));
+
+        let highlighted = |area: &TextArea| -> Vec<bool> {
+            let highlighter = area.highlighter.as_ref().unwrap();
+            (0..area.content.len_lines())
+                .map(|line| !highlighter.spans(line).is_empty())
+                .collect()
+        };
+        assert_eq!(highlighted(&area), vec![false, true, false]);
+
+        // Split the first line: the comment moves down.
+        area.set_cursor(1);
+        area.on_event(Event::Key(Key::Enter));
+        area.on_event(Event::Char('#'));
+        area.layout(Vec2::new(10, 5));
+        assert_eq!(&*area.get_content(), "a\n#\n# b\nc");
+        assert_eq!(highlighted(&area), vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn no_wrap_scrolls_to_cursor() {
+        let long = "x".repeat(50);
+        let mut area = TextArea::new()
+            .content(format!("short\n{}", long))
+            .no_wrap()
+            .line_numbers(LineNumbers::Absolute);
+        area.layout(Vec2::new(12, 5));
+        assert_eq!(area.rows.len(), 2);
+
+        area.on_event(Event::Ctrl(Key::End));
+        let viewport = area.scroll_core.content_viewport();
+        assert_eq!(viewport.left(), 41);
+
+        // Clicks are relative to the scrolled content, right of the gutter.
+        area.on_event(Event::Mouse {
+            offset: Vec2::zero(),
+            position: Vec2::new(2, 1),
+            event: MouseEvent::Press(MouseButton::Right),
+        });
+        assert_eq!(area.cursor(), 6 + 41);
+    }
+
+    #[test]
+    fn find_and_replace() {
+        let mut area = TextArea::new().content("one two One\ntwo one");
+        let options = FindOptions::new();
+
+        assert!(area.find_next("one", options));
+        assert_eq!(area.selection(), Some(0..3));
+        assert!(area.find_next("one", options));
+        assert_eq!(area.selection(), Some(8..11));
+        assert!(area.find_prev("one", options));
+        assert_eq!(area.selection(), Some(0..3));
+        assert!(area.find_prev("one", options));
+        assert_eq!(area.selection(), Some(16..19));
+        // The rope is searched in place.
+        assert!(area.flat_content.borrow().is_none());
+        area.find_next("one", options);
+
+        // Replacing moves to the next match.
+        assert!(area.replace("one", "1", options));
+        assert_eq!(&*area.get_content(), "1 two One\ntwo one");
+        assert_eq!(area.selection(), Some(6..9));
+
+        let options = options.case_sensitive(true);
+        assert_eq!(area.replace_all("one", "1", options), 1);
+        assert_eq!(&*area.get_content(), "1 two One\ntwo 1");
+
+        // Replacing everything is a single undo step.
+        assert_eq!(area.replace_all("two", "2", options), 2);
+        area.undo();
+        assert_eq!(&*area.get_content(), "1 two One\ntwo 1");
+    }
+
+    #[test]
+    fn find_bar_searches_as_you_type() {
+        let mut area = TextArea::new().content("abc abd abe").find_bar(true);
+        area.layout(Vec2::new(20, 5));
+
+        area.on_event(Event::CtrlChar('f'));
+        area.on_event(Event::Char('a'));
+        area.on_event(Event::Char('b'));
+        assert_eq!(area.selection(), Some(0..2));
+        area.on_event(Event::Char('d'));
+        assert_eq!(area.selection(), Some(4..7));
+
+        area.on_event(Event::Key(Key::Backspace));
+        area.on_event(Event::Key(Key::Enter));
+        assert_eq!(area.selection(), Some(4..6));
+
+        area.on_event(Event::Key(Key::Esc));
+        assert!(area.search.is_none());
+        area.on_event(Event::Char('x'));
+        assert_eq!(&*area.get_content(), "abc xd abe");
+    }
 }
@@ -8,7 +8,7 @@ use bear_lib_terminal::terminal::{self, state, Event as BltEvent, KeyCode};
 use bear_lib_terminal::Color as BltColor;
 
 use crate::backend;
-use crate::event::{Event, Key, MouseButton, MouseEvent};
+use crate::event::{Event, Key, KeyCombo, Modifiers, MouseButton, MouseEvent};
 use crate::theme::{BaseColor, Color, ColorPair, Effect};
 use crate::Vec2;
 
@@ -24,6 +24,7 @@ enum ColorRole {
 pub struct Backend {
     buttons_pressed: HashSet<MouseButton>,
     mouse_position: Vec2,
+    mouse_motion: bool,
 }
 
 impl Backend {
@@ -46,6 +47,7 @@ impl Backend {
         let c = Backend {
             buttons_pressed: HashSet::default(),
             mouse_position: Vec2::zero(),
+            mouse_motion: false,
         };
 
         Box::new(c)
@@ -63,6 +65,11 @@ impl Backend {
                     self.mouse_position = Vec2::new(x as usize, y as usize);
                     // TODO: find out if a button is pressed?
                     match self.buttons_pressed.iter().next() {
+                        None if self.mouse_motion => Event::Mouse {
+                            event: MouseEvent::Move,
+                            position: self.mouse_position,
+                            offset: Vec2::zero(),
+                        },
                         None => Event::Refresh,
                         Some(btn) => Event::Mouse {
                             event: MouseEvent::Hold(*btn),
@@ -142,12 +149,10 @@ impl Backend {
             | KeyCode::Right
             | KeyCode::Left
             | KeyCode::Down
-            | KeyCode::Up => match (shift, ctrl) {
-                (true, true) => Event::CtrlShift(blt_keycode_to_key(kc)),
-                (true, false) => Event::Shift(blt_keycode_to_key(kc)),
-                (false, true) => Event::Ctrl(blt_keycode_to_key(kc)),
-                (false, false) => Event::Key(blt_keycode_to_key(kc)),
-            },
+            | KeyCode::Up => {
+                let modifiers = blt_modifiers(shift, ctrl);
+                KeyCombo::new(blt_keycode_to_key(kc), modifiers).into()
+            }
             // TODO: mouse support
             KeyCode::MouseLeft
             | KeyCode::MouseRight
@@ -226,11 +231,9 @@ impl Backend {
             | KeyCode::Num8
             | KeyCode::Num9
             | KeyCode::Num0 => {
-                if ctrl {
-                    Event::CtrlChar(blt_keycode_to_char(kc, shift))
-                } else {
-                    Event::Char(blt_keycode_to_char(kc, shift))
-                }
+                // Shift is already applied to the character.
+                let modifiers = blt_modifiers(false, ctrl);
+                KeyCombo::new(blt_keycode_to_char(kc, shift), modifiers).into()
             }
         }
     }
@@ -247,6 +250,10 @@ impl backend::Backend for Backend {
         "bear-lib-terminal"
     }
 
+    fn set_mouse_motion(&mut self, enabled: bool) {
+        self.mouse_motion = enabled;
+    }
+
     fn set_color(&self, color: ColorPair) -> ColorPair {
         let current = ColorPair {
             front: blt_colour_to_colour(state::foreground()),
@@ -495,6 +502,17 @@ fn blt_keycode_to_char_impl(kc: KeyCode, shift: bool) -> char {
     }
 }
 
+fn blt_modifiers(shift: bool, ctrl: bool) -> Modifiers {
+    let mut modifiers = Modifiers::NONE;
+    if shift {
+        modifiers |= Modifiers::SHIFT;
+    }
+    if ctrl {
+        modifiers |= Modifiers::CTRL;
+    }
+    modifiers
+}
+
 fn blt_keycode_to_key(kc: KeyCode) -> Key {
     match kc {
         KeyCode::F1 => Key::F1,
@@ -533,6 +551,8 @@ fn blt_keycode_to_mouse_button(kc: KeyCode) -> Option<MouseButton> {
         KeyCode::MouseLeft => MouseButton::Left,
         KeyCode::MouseRight => MouseButton::Right,
         KeyCode::MouseMiddle => MouseButton::Middle,
+        KeyCode::MouseFourth => MouseButton::Extra(8),
+        KeyCode::MouseFifth => MouseButton::Extra(9),
         _ => return None,
     })
 }
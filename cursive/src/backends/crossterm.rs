@@ -1,6 +1,10 @@
 //! Backend using the pure-rust crossplatform crossterm library.
 //!
 //! Requires the `crossterm-backend` feature.
+//!
+//! The kitty keyboard protocol can be enabled with
+//! [`Backend::init_with_kitty_keyboard`]. Crossterm parses its sequences
+//! itself, so [`kitty`](super::kitty) is only used for the flags.
 
 #![cfg(feature = "crossterm")]
 
@@ -14,9 +18,13 @@ use std::{
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
     event::{
-        poll, read, DisableMouseCapture, EnableMouseCapture, Event as CEvent,
-        KeyCode, KeyEvent as CKeyEvent, KeyModifiers,
+        poll, read, DisableBracketedPaste, DisableFocusChange,
+        DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+        EnableMouseCapture, Event as CEvent, KeyCode, KeyEvent as CKeyEvent,
+        KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
         MouseButton as CMouseButton, MouseEvent as CMouseEvent,
+        MouseEventKind, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
     },
     execute, queue,
     style::{
@@ -24,14 +32,18 @@ use crossterm::{
         SetForegroundColor,
     },
     terminal::{
-        self, disable_raw_mode, enable_raw_mode, Clear, ClearType,
-        EnterAlternateScreen, LeaveAlternateScreen,
+        self, disable_raw_mode, enable_raw_mode,
+        supports_keyboard_enhancement, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
     },
 };
 
 use crate::{
     backend,
-    event::{Event, Key, MouseButton, MouseEvent},
+    event::{
+        Event, Key, KeyCode as EKeyCode, KeyCombo, Modifiers, MouseButton,
+        MouseEvent,
+    },
     theme, Vec2,
 };
 
@@ -46,6 +58,9 @@ pub struct Backend {
     current_style: Cell<theme::ColorPair>,
 
     stdout: RefCell<BufWriter<Stdout>>,
+
+    // Whether kitty keyboard flags were pushed, and must be popped.
+    kitty_enabled: bool,
 }
 
 fn translate_button(button: CMouseButton) -> MouseButton {
@@ -56,8 +71,8 @@ fn translate_button(button: CMouseButton) -> MouseButton {
     }
 }
 
-fn translate_key(code: KeyCode) -> Key {
-    match code {
+fn translate_key(code: KeyCode) -> Option<Key> {
+    Some(match code {
         KeyCode::Esc => Key::Esc,
         KeyCode::Backspace => Key::Backspace,
         KeyCode::Left => Key::Left,
@@ -73,80 +88,55 @@ fn translate_key(code: KeyCode) -> Key {
         KeyCode::Enter => Key::Enter,
         KeyCode::Tab => Key::Tab,
         KeyCode::F(n) => Key::from_f(n),
-        KeyCode::BackTab => Key::Tab, /* not supported */
-        KeyCode::Char(_) => Key::Tab, /* is handled at `Event` level, use tab as default */
-        KeyCode::Null => Key::Tab, /* is handled at `Event` level, use tab as default */
+        KeyCode::BackTab => Key::Tab,
+        // Lock, media and modifier keys are not supported.
+        _ => return None,
+    })
+}
+
+fn translate_modifiers(modifiers: KeyModifiers) -> Modifiers {
+    let mut result = Modifiers::NONE;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        result |= Modifiers::SHIFT;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        result |= Modifiers::CTRL;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        result |= Modifiers::ALT;
+    }
+    result
+}
+
+// Shift+wheel can stand in for the horizontal wheel.
+fn shift_wheel(event: MouseEvent, modifiers: KeyModifiers) -> MouseEvent {
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        event.shifted()
+    } else {
+        event
     }
 }
 
-fn translate_event(event: CKeyEvent) -> Event {
-    const CTRL_ALT: KeyModifiers = KeyModifiers::from_bits_truncate(
-        KeyModifiers::CONTROL.bits() | KeyModifiers::ALT.bits(),
-    );
-    const CTRL_SHIFT: KeyModifiers = KeyModifiers::from_bits_truncate(
-        KeyModifiers::CONTROL.bits() | KeyModifiers::SHIFT.bits(),
-    );
-    const ALT_SHIFT: KeyModifiers = KeyModifiers::from_bits_truncate(
-        KeyModifiers::ALT.bits() | KeyModifiers::SHIFT.bits(),
-    );
-
-    match event {
-        // Handle Char + modifier.
-        CKeyEvent {
-            modifiers: KeyModifiers::CONTROL,
-            code: KeyCode::Char(c),
-        } => Event::CtrlChar(c),
-        CKeyEvent {
-            modifiers: KeyModifiers::ALT,
-            code: KeyCode::Char(c),
-        } => Event::AltChar(c),
-        CKeyEvent {
-            modifiers: KeyModifiers::SHIFT,
-            code: KeyCode::Char(c),
-        } => Event::Char(c),
-
-        // Handle key + multiple modifiers
-        CKeyEvent {
-            modifiers: CTRL_ALT,
-            code,
-        } => Event::CtrlAlt(translate_key(code)),
-        CKeyEvent {
-            modifiers: CTRL_SHIFT,
-            code,
-        } => Event::CtrlShift(translate_key(code)),
-        CKeyEvent {
-            modifiers: ALT_SHIFT,
-            code,
-        } => Event::AltShift(translate_key(code)),
-
-        // Handle key + single modifier
-        CKeyEvent {
-            modifiers: KeyModifiers::CONTROL,
-            code,
-        } => Event::Ctrl(translate_key(code)),
-        CKeyEvent {
-            modifiers: KeyModifiers::ALT,
-            code,
-        } => Event::Alt(translate_key(code)),
-        CKeyEvent {
-            modifiers: KeyModifiers::SHIFT,
-            code,
-        } => Event::Shift(translate_key(code)),
-
-        CKeyEvent {
-            code: KeyCode::Char(c),
-            ..
-        } => Event::Char(c),
+fn translate_event(event: CKeyEvent) -> Option<Event> {
+    let mut modifiers = translate_modifiers(event.modifiers);
 
+    let code = match event.code {
+        KeyCode::Char(c) => EKeyCode::Char(c),
         // Explicitly handle 'backtab' since crossterm does not sent SHIFT alongside the back tab key.
-        CKeyEvent {
-            code: KeyCode::BackTab,
-            ..
-        } => Event::Shift(Key::Tab),
-
-        // All other keys.
-        CKeyEvent { code, .. } => Event::Key(translate_key(code)),
-    }
+        KeyCode::BackTab => {
+            modifiers |= Modifiers::SHIFT;
+            EKeyCode::Key(Key::Tab)
+        }
+        code => EKeyCode::Key(translate_key(code)?),
+    };
+
+    let combo = KeyCombo::new(code, modifiers);
+    // Releases are reported with the kitty keyboard protocol, and on Windows.
+    Some(if event.kind == KeyEventKind::Release {
+        Event::KeyRelease(combo)
+    } else {
+        combo.into()
+    })
 }
 
 fn translate_color(base_color: theme::Color) -> Color {
@@ -187,20 +177,67 @@ fn translate_color(base_color: theme::Color) -> Color {
 
 impl Backend {
     /// Creates a new crossterm backend.
-    pub fn init() -> Result<Box<dyn backend::Backend>, crossterm::ErrorKind>
+    ///
+    /// Bracketed paste is enabled, so pasted text is sent as a single
+    /// `Event::Paste`. Focus reporting is enabled too, to send
+    /// `Event::FocusGained` and `Event::FocusLost`.
+    pub fn init() -> io::Result<Box<dyn backend::Backend>>
     where
         Self: Sized,
     {
+        Self::init_with_options(None)
+    }
+
+    /// Creates a new crossterm backend using the kitty keyboard protocol.
+    ///
+    /// `flags` is a combination of [`kitty::DISAMBIGUATE`] and
+    /// [`kitty::REPORT_EVENT_TYPES`].
+    ///
+    /// If the terminal doesn't support the protocol, this is the same as
+    /// [`Backend::init`].
+    ///
+    /// [`kitty::DISAMBIGUATE`]: super::kitty::DISAMBIGUATE
+    /// [`kitty::REPORT_EVENT_TYPES`]: super::kitty::REPORT_EVENT_TYPES
+    pub fn init_with_kitty_keyboard(
+        flags: u8,
+    ) -> io::Result<Box<dyn backend::Backend>> {
+        Self::init_with_options(Some(flags))
+    }
+
+    fn init_with_options(
+        kitty_flags: Option<u8>,
+    ) -> io::Result<Box<dyn backend::Backend>> {
         enable_raw_mode()?;
 
+        // TODO: `MouseEvent::Move` is never sent with this backend, and the
+        // extra buttons are dropped.
+
         // TODO: Use the stdout we define down there
         execute!(
             io::stdout(),
             EnterAlternateScreen,
             EnableMouseCapture,
+            EnableBracketedPaste,
+            EnableFocusChange,
             Hide
         )?;
 
+        // Terminals without support for the protocol keep sending legacy
+        // sequences.
+        let kitty_enabled = match kitty_flags {
+            Some(flags) if supports_keyboard_enhancement()? => {
+                let flags =
+                    KeyboardEnhancementFlags::from_bits_truncate(flags);
+                execute!(io::stdout(), PushKeyboardEnhancementFlags(flags))?;
+                true
+            }
+            Some(_) => {
+                log::info!("No support for the kitty keyboard protocol");
+                false
+            }
+            None => false,
+        };
+
         #[cfg(unix)]
         let stdout = RefCell::new(BufWriter::new(File::create("/dev/tty")?));
         #[cfg(windows)]
@@ -209,6 +246,7 @@ impl Backend {
         Ok(Box::new(Backend {
             current_style: Cell::new(theme::ColorPair::from_256colors(0, 0)),
             stdout,
+            kitty_enabled,
         }))
     }
 
@@ -229,57 +267,64 @@ impl Backend {
         queue!(self.stdout_mut(), SetAttribute(attr)).unwrap();
     }
 
-    fn map_key(&mut self, event: CEvent) -> Event {
-        match event {
-            CEvent::Key(key_event) => translate_event(key_event),
-            CEvent::Mouse(mouse_event) => {
-                let position;
-                let event;
-
-                match mouse_event {
-                    CMouseEvent::Down(button, x, y, _) => {
-                        let button = translate_button(button);
-                        event = MouseEvent::Press(button);
-                        position = (x, y).into();
+    fn map_key(&mut self, event: CEvent) -> Option<Event> {
+        Some(match event {
+            CEvent::Key(key_event) => translate_event(key_event)?,
+            CEvent::Mouse(CMouseEvent {
+                kind,
+                column,
+                row,
+                modifiers,
+            }) => {
+                let event = match kind {
+                    MouseEventKind::Down(button) => {
+                        MouseEvent::Press(translate_button(button))
                     }
-                    CMouseEvent::Up(button, x, y, _) => {
-                        let button = translate_button(button);
-                        event = MouseEvent::Release(button);
-                        position = (x, y).into();
+                    MouseEventKind::Up(button) => {
+                        MouseEvent::Release(translate_button(button))
                     }
-                    CMouseEvent::Drag(button, x, y, _) => {
-                        let button = translate_button(button);
-                        event = MouseEvent::Hold(button);
-                        position = (x, y).into();
+                    MouseEventKind::Drag(button) => {
+                        MouseEvent::Hold(translate_button(button))
                     }
-                    CMouseEvent::ScrollDown(x, y, _) => {
-                        event = MouseEvent::WheelDown;
-                        position = (x, y).into();
+                    MouseEventKind::ScrollDown => {
+                        shift_wheel(MouseEvent::WheelDown, modifiers)
                     }
-                    CMouseEvent::ScrollUp(x, y, _) => {
-                        event = MouseEvent::WheelUp;
-                        position = (x, y).into();
+                    MouseEventKind::ScrollUp => {
+                        shift_wheel(MouseEvent::WheelUp, modifiers)
                     }
+                    MouseEventKind::ScrollLeft => MouseEvent::WheelLeft,
+                    MouseEventKind::ScrollRight => MouseEvent::WheelRight,
+                    _ => return None,
                 };
 
                 Event::Mouse {
                     event,
-                    position,
+                    position: (column, row).into(),
                     offset: Vec2::zero(),
                 }
             }
+            CEvent::Paste(text) => Event::Paste(text),
             CEvent::Resize(_, _) => Event::WindowResize,
-        }
+            CEvent::FocusGained => Event::FocusGained,
+            CEvent::FocusLost => Event::FocusLost,
+        })
     }
 }
 
 impl Drop for Backend {
     fn drop(&mut self) {
+        if self.kitty_enabled {
+            execute!(io::stdout(), PopKeyboardEnhancementFlags)
+                .expect("Can not restore the keyboard flags.");
+        }
+
         // We have to execute the show cursor command at the `stdout`.
         execute!(
             io::stdout(),
             LeaveAlternateScreen,
             DisableMouseCapture,
+            DisableBracketedPaste,
+            DisableFocusChange,
             Show
         )
         .expect("Can not disable mouse capture or show cursor.");
@@ -292,7 +337,7 @@ impl backend::Backend for Backend {
     fn poll_event(&mut self) -> Option<Event> {
         match poll(Duration::from_millis(1)) {
             Ok(true) => match read() {
-                Ok(event) => Some(self.map_key(event)),
+                Ok(event) => self.map_key(event),
                 Err(e) => panic!("{:?}", e),
             },
             _ => None,
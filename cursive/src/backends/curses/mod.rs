@@ -3,7 +3,7 @@
 //! Requires either of `ncurses-backend` or `pancurses-backend`.
 #![cfg(any(feature = "ncurses-backend", feature = "pancurses-backend"))]
 
-use crate::event::{Event, Key};
+use crate::event::{Event, Key, KeyCombo, Modifiers};
 use crate::theme::{BaseColor, Color, ColorPair};
 use maplit::hashmap;
 
@@ -53,15 +53,12 @@ where
             Some(&key) => key,
             None => continue,
         };
-        let event = match modifier {
-            "3" => Event::Alt(key),
-            "4" => Event::AltShift(key),
-            "5" => Event::Ctrl(key),
-            "6" => Event::CtrlShift(key),
-            "7" => Event::CtrlAlt(key),
+        // The suffix is the xterm modifier parameter, like in `kUP5`.
+        let modifiers = match modifier.parse() {
+            Ok(param @ 2..=8) => Modifiers::from_xterm(param),
             _ => continue,
         };
-        target.insert(code, event);
+        target.insert(code, KeyCombo::new(key, modifiers).into());
     }
 }
 
@@ -9,7 +9,8 @@ use std::io;
 use std::io::Write;
 
 use crate::backend;
-use crate::event::{Event, Key, MouseButton, MouseEvent};
+use crate::backends::mouse;
+use crate::event::{Event, Key, KeyCombo, Modifiers, MouseButton, MouseEvent};
 use crate::theme::{Color, ColorPair, Effect};
 use crate::utf8;
 use crate::Vec2;
@@ -19,6 +20,25 @@ use super::split_i32;
 // Use AHash instead of the slower SipHash
 type HashMap<K, V> = std::collections::HashMap<K, V, ahash::RandomState>;
 
+// Key codes reported for the start and end of a bracketed paste.
+const PASTE_START: i32 = 2048;
+const PASTE_END: i32 = 2049;
+
+// Key codes reported when the terminal gains or loses focus.
+const FOCUS_IN: i32 = 2050;
+const FOCUS_OUT: i32 = 2051;
+
+// Key code reported for the start of an SGR mouse report.
+const MOUSE_SGR: i32 = 2052;
+
+// Longest SGR mouse report we expect, like `\x1B[<131;1000;1000M`.
+const MOUSE_SGR_MAX_LEN: usize = 32;
+
+extern "C" {
+    // Not exposed by the ncurses crate.
+    fn define_key(definition: *const libc::c_char, keycode: i32) -> i32;
+}
+
 /// Backend using ncurses.
 pub struct Backend {
     current_style: Cell<ColorPair>,
@@ -32,6 +52,9 @@ pub struct Backend {
     // Remember the last pressed button to correctly feed Released Event
     last_mouse_button: Option<MouseButton>,
 
+    // Set when any mouse motion is reported, not only drags.
+    mouse_motion: bool,
+
     // Sometimes a code from ncurses should be split in two Events.
     //
     // So remember the one we didn't return.
@@ -111,6 +134,19 @@ impl Backend {
         // Enable keypad (like arrows)
         ncurses::keypad(ncurses::stdscr(), true);
 
+        // Report the bracketed paste markers, focus changes and SGR mouse
+        // reports as custom key codes.
+        for &(definition, code) in &[
+            ("\x1B[200~", PASTE_START),
+            ("\x1B[201~", PASTE_END),
+            ("\x1B[I", FOCUS_IN),
+            ("\x1B[O", FOCUS_OUT),
+            ("\x1B[<", MOUSE_SGR),
+        ] {
+            let definition = CString::new(definition).unwrap();
+            unsafe { define_key(definition.as_ptr(), code) };
+        }
+
         // This disables mouse click detection,
         // and provides 0-delay access to mouse presses.
         ncurses::mouseinterval(0);
@@ -135,14 +171,26 @@ impl Backend {
 
         // This asks the terminal to provide us with mouse drag events
         // (Mouse move when a button is pressed).
-        // Replacing 1002 with 1003 would give us ANY mouse move.
+        // `set_mouse_motion` replaces 1002 with 1003 to get ANY mouse move.
         write_to_tty(b"\x1B[?1002h")?;
 
+        // ncurses only knows 5 buttons. SGR reports are parsed by
+        // `read_mouse_report` instead, for the horizontal wheel and the extra
+        // buttons.
+        write_to_tty(b"\x1B[?1006h")?;
+
+        // Pasted text will be surrounded by markers.
+        write_to_tty(b"\x1B[?2004h")?;
+
+        // Report when the terminal gains or loses focus.
+        write_to_tty(b"\x1B[?1004h")?;
+
         let c = Backend {
             current_style: Cell::new(ColorPair::from_256colors(0, 0)),
             pairs: RefCell::new(HashMap::default()),
             key_codes: initialize_keymap(),
             last_mouse_button: None,
+            mouse_motion: false,
             input_buffer: None,
         };
 
@@ -202,6 +250,14 @@ impl Backend {
             return None;
         }
 
+        if ch == PASTE_START {
+            return Some(self.read_paste());
+        }
+
+        if ch == MOUSE_SGR {
+            return Some(self.read_mouse_report());
+        }
+
         // Is it a UTF-8 starting point?
         let event = if 32 <= ch && ch <= 255 && ch != 127 {
             utf8::read_char(ch as u8, || Some(ncurses::getch() as u8))
@@ -217,6 +273,62 @@ impl Backend {
         Some(event)
     }
 
+    /// Reads pasted text until the end of the bracketed paste.
+    fn read_paste(&mut self) -> Event {
+        let mut text = String::new();
+
+        // The rest of the paste may not be there yet, so wait a bit for it.
+        ncurses::timeout(100);
+        loop {
+            let ch = ncurses::getch();
+            match ch {
+                -1 | PASTE_END => break,
+                9 => text.push('\t'),
+                10 | 13 => text.push('\n'),
+                32..=255 if ch != 127 => {
+                    match utf8::read_char(ch as u8, || {
+                        Some(ncurses::getch() as u8)
+                    }) {
+                        Ok(c) => text.push(c),
+                        Err(e) => warn!("Error reading pasted text: {}", e),
+                    }
+                }
+                _ => (),
+            }
+        }
+        ncurses::timeout(0);
+
+        Event::Paste(text)
+    }
+
+    /// Reads the rest of an SGR mouse report.
+    fn read_mouse_report(&mut self) -> Event {
+        let mut bytes = b"\x1B[<".to_vec();
+
+        ncurses::timeout(100);
+        while bytes.len() < MOUSE_SGR_MAX_LEN {
+            match ncurses::getch() {
+                -1 => break,
+                ch => bytes.push(ch as u8),
+            }
+            if bytes.ends_with(b"M") || bytes.ends_with(b"m") {
+                break;
+            }
+        }
+        ncurses::timeout(0);
+
+        let event = mouse::parse(&bytes).and_then(|(report, _)| {
+            let event = report
+                .event(&mut self.last_mouse_button, self.mouse_motion)?;
+            Some(Event::Mouse {
+                offset: Vec2::zero(),
+                position: report.position,
+                event,
+            })
+        });
+        event.unwrap_or(Event::Unknown(bytes))
+    }
+
     fn parse_ncurses_char(&mut self, ch: i32) -> Event {
         // eprintln!("Found {:?}", ncurses::keyname(ch));
         if ch == ncurses::KEY_MOUSE {
@@ -242,7 +354,7 @@ impl Backend {
         {
             // Currently unused
             let _ctrl = (mevent.bstate & ncurses::BUTTON_CTRL as mmask_t) != 0;
-            let _shift =
+            let shift =
                 (mevent.bstate & ncurses::BUTTON_SHIFT as mmask_t) != 0;
             let _alt = (mevent.bstate & ncurses::BUTTON_ALT as mmask_t) != 0;
 
@@ -253,10 +365,10 @@ impl Backend {
                 as mmask_t;
 
             // This makes a full `Event` from a `MouseEvent`.
-            let make_event = |event| Event::Mouse {
+            let make_event = |event: MouseEvent| Event::Mouse {
                 offset: Vec2::zero(),
                 position: Vec2::new(mevent.x as usize, mevent.y as usize),
-                event,
+                event: if shift { event.shifted() } else { event },
             };
 
             if mevent.bstate == ncurses::REPORT_MOUSE_POSITION as mmask_t {
@@ -272,6 +384,8 @@ impl Backend {
                             == ncurses::BUTTON5_DOUBLE_CLICKED as mmask_t
                         {
                             Some(MouseEvent::WheelDown)
+                        } else if self.mouse_motion {
+                            Some(MouseEvent::Move)
                         } else {
                             None
                         }
@@ -324,7 +438,13 @@ impl Backend {
 
 impl Drop for Backend {
     fn drop(&mut self) {
+        write_to_tty(b"\x1B[?1004l").unwrap();
+        write_to_tty(b"\x1B[?2004l").unwrap();
+        if self.mouse_motion {
+            write_to_tty(b"\x1B[?1003l").unwrap();
+        }
         write_to_tty(b"\x1B[?1002l").unwrap();
+        write_to_tty(b"\x1B[?1006l").unwrap();
         ncurses::endwin();
     }
 }
@@ -334,6 +454,17 @@ impl backend::Backend for Backend {
         "ncurses"
     }
 
+    fn set_mouse_motion(&mut self, enabled: bool) {
+        self.mouse_motion = enabled;
+        if enabled {
+            write_to_tty(b"\x1B[?1003h").unwrap();
+        } else {
+            // This also stops drag reports, so ask for them again.
+            write_to_tty(b"\x1B[?1003l").unwrap();
+            write_to_tty(b"\x1B[?1002h").unwrap();
+        }
+    }
+
     fn screen_size(&self) -> Vec2 {
         let mut x: i32 = 0;
         let mut y: i32 = 0;
@@ -466,18 +597,18 @@ where
         | ncurses::BUTTON3_PRESSED => f(MouseEvent::Press(button)),
         ncurses::BUTTON4_PRESSED => f(MouseEvent::WheelUp),
         ncurses::BUTTON5_PRESSED => f(MouseEvent::WheelDown),
+        // ncurses only knows 5 buttons: the horizontal wheel and the extra
+        // buttons come as SGR reports instead, see `read_mouse_report`.
         // BUTTON4_RELEASED? BUTTON5_RELEASED?
         // Do they ever happen?
         _ => debug!("Unknown event: {:032b}", bare_event),
     }
 }
 
-fn add_fn<F>(start: i32, with_key: F, map: &mut HashMap<i32, Event>)
-where
-    F: Fn(Key) -> Event,
-{
+fn add_fn(start: i32, modifiers: Modifiers, map: &mut HashMap<i32, Event>) {
     for i in 0..12 {
-        map.insert(start + i, with_key(Key::from_f((i + 1) as u8)));
+        let key = Key::from_f((i + 1) as u8);
+        map.insert(start + i, KeyCombo::new(key, modifiers).into());
     }
 }
 
@@ -503,6 +634,8 @@ fn initialize_keymap() -> HashMap<i32, Event> {
     map.insert(ncurses::KEY_BACKSPACE, Event::Key(Key::Backspace));
 
     map.insert(410, Event::WindowResize);
+    map.insert(FOCUS_IN, Event::FocusGained);
+    map.insert(FOCUS_OUT, Event::FocusLost);
 
     map.insert(ncurses::KEY_B2, Event::Key(Key::NumpadCenter));
     map.insert(ncurses::KEY_DC, Event::Key(Key::Del));
@@ -534,17 +667,21 @@ fn initialize_keymap() -> HashMap<i32, Event> {
             // and Enter respecively. There's just no way to detect them. :(
             9 => Event::Key(Key::Tab),
             10 => Event::Key(Key::Enter),
-            other => Event::CtrlChar((b'a' - 1 + other as u8) as char),
+            other => {
+                let c = (b'a' - 1 + other as u8) as char;
+                KeyCombo::new(c, Modifiers::CTRL).into()
+            }
         };
         map.insert(c, event);
     }
 
     // Ncurses provides a F1 variable, but no modifiers
-    add_fn(ncurses::KEY_F1, Event::Key, &mut map);
-    add_fn(277, Event::Shift, &mut map);
-    add_fn(289, Event::Ctrl, &mut map);
-    add_fn(301, Event::CtrlShift, &mut map);
-    add_fn(313, Event::Alt, &mut map);
+    add_fn(ncurses::KEY_F1, Modifiers::NONE, &mut map);
+    add_fn(277, Modifiers::SHIFT, &mut map);
+    add_fn(289, Modifiers::CTRL, &mut map);
+    add_fn(301, Modifiers::CTRL | Modifiers::SHIFT, &mut map);
+    add_fn(313, Modifiers::ALT, &mut map);
+    add_fn(325, Modifiers::ALT | Modifiers::SHIFT, &mut map);
 
     // Those codes actually vary between ncurses versions...
     super::fill_key_codes(&mut map, ncurses::keyname);
@@ -5,7 +5,7 @@ use std::cell::{Cell, RefCell};
 use std::io::{stdout, Write};
 
 use crate::backend;
-use crate::event::{Event, Key, MouseButton, MouseEvent};
+use crate::event::{Event, Key, KeyCombo, Modifiers, MouseButton, MouseEvent};
 use crate::theme::{Color, ColorPair, Effect};
 use crate::Vec2;
 
@@ -26,6 +26,10 @@ pub struct Backend {
 
     key_codes: HashMap<i32, Event>,
     last_mouse_button: Option<MouseButton>,
+
+    // Set when any mouse motion is reported, not only drags.
+    mouse_motion: bool,
+
     input_buffer: Option<Event>,
 }
 
@@ -75,9 +79,13 @@ impl Backend {
 
         // This asks the terminal to provide us with mouse drag events
         // (Mouse move when a button is pressed).
-        // Replacing 1002 with 1003 would give us ANY mouse move.
+        // `set_mouse_motion` replaces 1002 with 1003 to get ANY mouse move.
         #[cfg(not(windows))]
         print!("\x1B[?1002h");
+
+        // Report when the terminal gains or loses focus.
+        #[cfg(not(windows))]
+        print!("\x1B[?1004h");
         stdout().flush()?;
 
         let c = Backend {
@@ -85,6 +93,7 @@ impl Backend {
             pairs: RefCell::new(HashMap::default()),
             key_codes: initialize_keymap(),
             last_mouse_button: None,
+            mouse_motion: false,
             input_buffer: None,
             window,
         };
@@ -153,10 +162,11 @@ impl Backend {
                     Event::Key(Key::Backspace)
                 }
                 pancurses::Input::Character('\u{9}') => Event::Key(Key::Tab),
-                pancurses::Input::Character('\u{1b}') => Event::Key(Key::Esc),
+                pancurses::Input::Character('\u{1b}') => self.parse_escape(),
                 // Ctrl+C
                 pancurses::Input::Character(c) if (c as u32) <= 26 => {
-                    Event::CtrlChar((b'a' - 1 + c as u8) as char)
+                    let c = (b'a' - 1 + c as u8) as char;
+                    KeyCombo::new(c, Modifiers::CTRL).into()
                 }
                 pancurses::Input::Character(c) => Event::Char(c),
                 // TODO: Some key combos are not recognized by pancurses,
@@ -297,13 +307,42 @@ impl Backend {
         }
     }
 
+    /// Parses the input following an escape character.
+    ///
+    /// pancurses cannot learn new key sequences, so focus reports
+    /// (`ESC [ I` and `ESC [ O`) come one character at a time.
+    fn parse_escape(&mut self) -> Event {
+        match self.window.getch() {
+            Some(pancurses::Input::Character('[')) => {
+                match self.window.getch() {
+                    Some(pancurses::Input::Character('I')) => {
+                        return Event::FocusGained
+                    }
+                    Some(pancurses::Input::Character('O')) => {
+                        return Event::FocusLost
+                    }
+                    Some(input) => {
+                        self.window.ungetch(&input);
+                    }
+                    None => (),
+                }
+                self.window.ungetch(&pancurses::Input::Character('['));
+            }
+            Some(input) => {
+                self.window.ungetch(&input);
+            }
+            None => (),
+        }
+        Event::Key(Key::Esc)
+    }
+
     fn parse_mouse_event(&mut self) -> Event {
         let mut mevent = match pancurses::getmouse() {
             Err(code) => return Event::Unknown(split_i32(code)),
             Ok(event) => event,
         };
 
-        let _shift = (mevent.bstate & pancurses::BUTTON_SHIFT as mmask_t) != 0;
+        let shift = (mevent.bstate & pancurses::BUTTON_SHIFT as mmask_t) != 0;
         let _alt = (mevent.bstate & pancurses::BUTTON_ALT as mmask_t) != 0;
         let _ctrl = (mevent.bstate & pancurses::BUTTON_CTRL as mmask_t) != 0;
 
@@ -311,22 +350,23 @@ impl Backend {
             | pancurses::BUTTON_ALT
             | pancurses::BUTTON_CTRL) as mmask_t;
 
-        let make_event = |event| Event::Mouse {
+        let make_event = |event: MouseEvent| Event::Mouse {
             offset: Vec2::zero(),
             position: Vec2::new(mevent.x as usize, mevent.y as usize),
-            event,
+            event: if shift { event.shifted() } else { event },
         };
 
         if mevent.bstate == pancurses::REPORT_MOUSE_POSITION as mmask_t {
             // The event is either a mouse drag event,
             // or a weird double-release event. :S
-            self.last_mouse_button
-                .map(MouseEvent::Hold)
-                .map(&make_event)
-                .unwrap_or_else(|| {
+            match self.last_mouse_button {
+                Some(btn) => make_event(MouseEvent::Hold(btn)),
+                None if self.mouse_motion => make_event(MouseEvent::Move),
+                None => {
                     debug!("We got a mouse drag, but no last mouse pressed?");
                     Event::Unknown(vec![])
-                })
+                }
+            }
         } else {
             // Identify the button
             let mut bare_event = mevent.bstate & ((1 << 25) - 1);
@@ -346,8 +386,14 @@ impl Backend {
                 });
             }
             if let Some(event) = event {
-                if let Some(btn) = event.button() {
-                    self.last_mouse_button = Some(btn);
+                match event {
+                    MouseEvent::Press(btn) => {
+                        self.last_mouse_button = Some(btn);
+                    }
+                    MouseEvent::Release(_) => {
+                        self.last_mouse_button = None;
+                    }
+                    _ => (),
                 }
                 make_event(event)
             } else {
@@ -360,7 +406,15 @@ impl Backend {
 
 impl Drop for Backend {
     fn drop(&mut self) {
+        #[cfg(not(windows))]
+        {
+            if self.mouse_motion {
+                print!("\x1B[?1003l");
+            }
+        }
         print!("\x1B[?1002l");
+        #[cfg(not(windows))]
+        print!("\x1B[?1004l");
         stdout().flush().expect("could not flush stdout");
         pancurses::endwin();
     }
@@ -371,6 +425,20 @@ impl backend::Backend for Backend {
         "pancurses"
     }
 
+    fn set_mouse_motion(&mut self, enabled: bool) {
+        self.mouse_motion = enabled;
+        #[cfg(not(windows))]
+        {
+            if enabled {
+                print!("\x1B[?1003h");
+            } else {
+                // This also stops drag reports, so ask for them again.
+                print!("\x1B[?1003l\x1B[?1002h");
+            }
+            stdout().flush().expect("could not flush stdout");
+        }
+    }
+
     fn screen_size(&self) -> Vec2 {
         // Coordinates are reversed here
         let (y, x) = self.window.get_max_yx();
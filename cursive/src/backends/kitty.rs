@@ -0,0 +1,255 @@
+//! Parser for the kitty keyboard protocol.
+//!
+//! With legacy escape sequences, some keys cannot be told apart: Tab and
+//! Ctrl-I send the same byte, and so do Enter and Ctrl-M. Escape is also the
+//! prefix of Alt combinations, and key releases are not reported at all.
+//!
+//! Terminals implementing the [kitty keyboard protocol] report these keys
+//! with `CSI ... u` sequences instead, once the application pushed the
+//! enhancements it wants. Keys producing text without modifiers, as well as
+//! Enter, Tab and Backspace, are still sent as usual.
+//!
+//! Backends first send [`QUERY`]. Terminals supporting the protocol answer
+//! with their current flags (`Parsed::Flags`), and then the backend can push
+//! its own with [`push_flags`] (and pop them with [`POP_FLAGS`] on exit).
+//! Other terminals only answer the device attributes query that follows
+//! (`Parsed::DeviceAttributes`), and legacy sequences keep being used.
+//!
+//! [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+use crate::event::{Event, Key, KeyCode, KeyCombo, Modifiers};
+
+/// Report ambiguous keys like Escape or Ctrl-I with `CSI u` sequences.
+pub const DISAMBIGUATE: u8 = 1;
+
+/// Also report key repeats and releases.
+pub const REPORT_EVENT_TYPES: u8 = 1 << 1;
+
+/// Asks for the current flags, then for the primary device attributes.
+///
+/// Terminals without support for the protocol only answer the second query.
+pub const QUERY: &str = "\x1B[?u\x1B[c";
+
+/// Restores the flags active before the last [`push_flags`].
+pub const POP_FLAGS: &str = "\x1B[<u";
+
+/// Returns the sequence enabling the given flags.
+///
+/// Flags are a combination of [`DISAMBIGUATE`] and [`REPORT_EVENT_TYPES`].
+pub fn push_flags(flags: u8) -> String {
+    format!("\x1B[>{}u", flags)
+}
+
+/// Result of parsing bytes received from the terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Parsed {
+    /// A key was pressed, repeated or released.
+    ///
+    /// Releases are given as `Event::KeyRelease`.
+    Key(Event),
+
+    /// The terminal supports the protocol, and these flags are active.
+    Flags(u8),
+
+    /// The terminal answered the device attributes query.
+    ///
+    /// If it did not send `Flags` before, it doesn't support the protocol.
+    DeviceAttributes,
+
+    /// The bytes start a sequence, but more are needed.
+    Incomplete,
+
+    /// The bytes are not a sequence from this protocol.
+    ///
+    /// They should be parsed as a legacy sequence instead.
+    Unknown,
+}
+
+/// Parses a single sequence at the start of `bytes`.
+///
+/// Returns the result, and the number of bytes it used.
+pub fn parse(bytes: &[u8]) -> (Parsed, usize) {
+    if !bytes.starts_with(b"\x1B[") {
+        return if bytes == b"\x1B" {
+            (Parsed::Incomplete, 0)
+        } else {
+            (Parsed::Unknown, 0)
+        };
+    }
+
+    // Parameters and intermediate bytes, then a final byte.
+    let end = match bytes[2..].iter().position(|&b| (0x40..0x7F).contains(&b))
+    {
+        Some(i) => i + 2,
+        None => return (Parsed::Incomplete, 0),
+    };
+    let params = match std::str::from_utf8(&bytes[2..end]) {
+        Ok(params) => params,
+        Err(_) => return (Parsed::Unknown, 0),
+    };
+    let parsed = parse_csi(params, bytes[end]).unwrap_or(Parsed::Unknown);
+    match parsed {
+        Parsed::Unknown => (Parsed::Unknown, 0),
+        parsed => (parsed, end + 1),
+    }
+}
+
+/// Parses a complete `CSI params final` sequence.
+fn parse_csi(params: &str, final_byte: u8) -> Option<Parsed> {
+    if let Some(params) = params.strip_prefix('?') {
+        return match final_byte {
+            b'u' => Some(Parsed::Flags(params.parse().ok()?)),
+            b'c' => Some(Parsed::DeviceAttributes),
+            _ => None,
+        };
+    }
+
+    // Parameters can have sub-parameters, separated by colons:
+    // `code:shifted:base;modifiers:event-type;text`.
+    let mut params = params.split(';');
+    let number = match params.next()?.split(':').next()? {
+        "" => 1,
+        number => number.parse().ok()?,
+    };
+    let mut modifiers = params.next().unwrap_or("").split(':');
+    // Modifiers and event types are sent plus one.
+    let bits = match modifiers.next()? {
+        "" => 0,
+        bits => bits.parse::<u16>().ok()?.checked_sub(1)?,
+    };
+    let release = match modifiers.next() {
+        None | Some("1") | Some("2") => false,
+        Some("3") => true,
+        Some(_) => return None,
+    };
+
+    let code = match final_byte {
+        b'u' => key_code(number)?,
+        b'~' => tilde_key(number)?,
+        final_byte if number == 1 => letter_key(final_byte)?,
+        _ => return None,
+    };
+    // Lock keys and hyper or meta are not modifiers cursive knows about.
+    let modifiers = Modifiers::from_bits_truncate((bits & 0b1111) as u8);
+    let combo = KeyCombo::new(code, modifiers);
+
+    Some(Parsed::Key(if release {
+        Event::KeyRelease(combo)
+    } else {
+        combo.into()
+    }))
+}
+
+/// Returns the key for a `CSI code u` sequence.
+fn key_code(code: u32) -> Option<KeyCode> {
+    Some(match code {
+        9 => Key::Tab.into(),
+        13 => Key::Enter.into(),
+        27 => Key::Esc.into(),
+        127 => Key::Backspace.into(),
+        // Private use area: keypad and modifier keys, media keys, ...
+        57344..=63743 => return None,
+        code => std::char::from_u32(code)?.into(),
+    })
+}
+
+/// Returns the key for a `CSI number ~` sequence.
+fn tilde_key(number: u32) -> Option<KeyCode> {
+    Some(
+        match number {
+            2 => Key::Ins,
+            3 => Key::Del,
+            5 => Key::PageUp,
+            6 => Key::PageDown,
+            7 => Key::Home,
+            8 => Key::End,
+            11 => Key::F1,
+            12 => Key::F2,
+            13 => Key::F3,
+            14 => Key::F4,
+            15 => Key::F5,
+            17 => Key::F6,
+            18 => Key::F7,
+            19 => Key::F8,
+            20 => Key::F9,
+            21 => Key::F10,
+            23 => Key::F11,
+            24 => Key::F12,
+            _ => return None,
+        }
+        .into(),
+    )
+}
+
+/// Returns the key for a `CSI 1 letter` sequence.
+fn letter_key(letter: u8) -> Option<KeyCode> {
+    Some(
+        match letter {
+            b'A' => Key::Up,
+            b'B' => Key::Down,
+            b'C' => Key::Right,
+            b'D' => Key::Left,
+            b'E' => Key::NumpadCenter,
+            b'F' => Key::End,
+            b'H' => Key::Home,
+            b'P' => Key::F1,
+            b'Q' => Key::F2,
+            b'S' => Key::F4,
+            _ => return None,
+        }
+        .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(bytes: &[u8]) -> Event {
+        match parse(bytes) {
+            (Parsed::Key(event), len) if len == bytes.len() => event,
+            parsed => panic!("{:?} gave {:?}", bytes, parsed),
+        }
+    }
+
+    #[test]
+    fn keys() {
+        // Tab and Ctrl-I, Enter and Ctrl-M, Esc and Alt are now different.
+        assert_eq!(key(b"\x1B[105;5u"), Event::CtrlChar('i'));
+        assert_eq!(key(b"\x1B[109;5u"), Event::CtrlChar('m'));
+        assert_eq!(key(b"\x1B[9;5u"), Event::Ctrl(Key::Tab));
+        assert_eq!(key(b"\x1B[27u"), Event::Key(Key::Esc));
+        assert_eq!(key(b"\x1B[120;3u"), Event::AltChar('x'));
+        assert_eq!(
+            key(b"\x1B[107;6u"),
+            Event::Combo("ctrl+shift+k".parse().unwrap())
+        );
+
+        assert_eq!(key(b"\x1B[1;5A"), Event::Ctrl(Key::Up));
+        assert_eq!(key(b"\x1B[H"), Event::Key(Key::Home));
+        assert_eq!(key(b"\x1B[3;2~"), Event::Shift(Key::Del));
+        assert_eq!(key(b"\x1B[15~"), Event::Key(Key::F5));
+        // Caps lock doesn't matter.
+        assert_eq!(key(b"\x1B[97;69u"), Event::CtrlChar('a'));
+
+        assert_eq!(key(b"\x1B[97;1:2u"), Event::Char('a'));
+        assert_eq!(
+            key(b"\x1B[97;5:3u"),
+            Event::KeyRelease("ctrl+a".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn replies_and_fallback() {
+        assert_eq!(parse(b"\x1B[?1u\x1B[?62c"), (Parsed::Flags(1), 5));
+        assert_eq!(parse(b"\x1B[?62;22c"), (Parsed::DeviceAttributes, 9));
+
+        assert_eq!(parse(b"\x1B[97;5"), (Parsed::Incomplete, 0));
+        assert_eq!(parse(b"\x1B"), (Parsed::Incomplete, 0));
+        // Legacy sequences are left to the backend.
+        assert_eq!(parse(b"a"), (Parsed::Unknown, 0));
+        assert_eq!(parse(b"\x1BOP"), (Parsed::Unknown, 0));
+        assert_eq!(parse(b"\x1B[200~"), (Parsed::Unknown, 0));
+        assert_eq!(parse(b"\x1B[<0;3;4M"), (Parsed::Unknown, 0));
+        assert_eq!(parse(b"\x1B[57441u"), (Parsed::Unknown, 0));
+    }
+}
@@ -14,5 +14,9 @@ mod resize;
 pub mod blt;
 pub mod crossterm;
 pub mod curses;
+pub mod kitty;
+#[cfg(any(feature = "termion", feature = "ncurses"))]
+mod mouse;
 pub mod puppet;
+pub mod record;
 pub mod termion;
@@ -0,0 +1,211 @@
+//! Decoder for xterm mouse reports.
+//!
+//! Terminals report mouse events as `CSI M cb cx cy`, with one byte for each
+//! value plus 32 (X10 encoding), or as `CSI < cb ; cx ; cy M` (SGR encoding,
+//! ending with `m` for releases).
+//!
+//! The low bits of `cb` are the button, 3 meaning a release in the X10
+//! encoding. Higher bits are modifiers (4 for Shift), motion (32), the wheel
+//! (64) and the extra buttons (128).
+//!
+//! Termion 1 and ncurses only know the first 5 buttons: the horizontal wheel
+//! (codes 66 and 67) and the extra buttons (codes 128 and up) are decoded
+//! here instead.
+use crate::event::{MouseButton, MouseEvent};
+use crate::Vec2;
+
+const SHIFT: u16 = 4;
+const META: u16 = 8;
+const CTRL: u16 = 16;
+const MOTION: u16 = 32;
+const WHEEL: u16 = 64;
+const EXTRA: u16 = 128;
+
+/// A single mouse report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// Button code, with modifiers.
+    code: u16,
+    /// Position of the mouse, starting at (0, 0).
+    pub position: Vec2,
+    /// `true` for SGR releases.
+    release: bool,
+}
+
+/// Parses a single report at the start of `bytes`.
+///
+/// Returns the report and the number of bytes it used, or `None` if the
+/// bytes don't start with a complete report.
+pub fn parse(bytes: &[u8]) -> Option<(Report, usize)> {
+    match bytes {
+        [0x1B, b'[', b'M', code, x, y, ..] => {
+            let report = Report {
+                code: u16::from(code.checked_sub(32)?),
+                position: Vec2::new(
+                    usize::from(x.checked_sub(33)?),
+                    usize::from(y.checked_sub(33)?),
+                ),
+                release: false,
+            };
+            Some((report, 6))
+        }
+        [0x1B, b'[', b'<', rest @ ..] => {
+            let end = rest.iter().position(|&b| b == b'M' || b == b'm')?;
+            let params = std::str::from_utf8(&rest[..end]).ok()?;
+            let mut params = params.split(';').map(str::parse::<u16>);
+            let code = params.next()?.ok()?;
+            let x = params.next()?.ok()?.checked_sub(1)?;
+            let y = params.next()?.ok()?.checked_sub(1)?;
+            let report = Report {
+                code,
+                position: Vec2::new(usize::from(x), usize::from(y)),
+                release: rest[end] == b'm',
+            };
+            Some((report, end + 4))
+        }
+        _ => None,
+    }
+}
+
+impl Report {
+    /// Returns `true` for the horizontal wheel and the extra buttons.
+    ///
+    /// These reports are not understood by termion 1 or ncurses.
+    #[cfg(feature = "termion")]
+    pub fn is_extended(&self) -> bool {
+        let button = self.button_code();
+        button & EXTRA != 0 || button & WHEEL != 0 && button & 2 != 0
+    }
+
+    /// Returns the event for this report.
+    ///
+    /// `last_button` is the button currently held: it is needed for X10
+    /// releases, which don't say which button was released, and it is
+    /// updated. `motion` tells if motion without any button is reported.
+    pub fn event(
+        &self,
+        last_button: &mut Option<MouseButton>,
+        motion: bool,
+    ) -> Option<MouseEvent> {
+        let button = self.button_code();
+
+        if button & WHEEL != 0 {
+            if self.release {
+                return None;
+            }
+            let event = match button & 3 {
+                0 => MouseEvent::WheelUp,
+                1 => MouseEvent::WheelDown,
+                2 => MouseEvent::WheelLeft,
+                _ => MouseEvent::WheelRight,
+            };
+            return Some(if self.code & SHIFT != 0 {
+                event.shifted()
+            } else {
+                event
+            });
+        }
+
+        let pressed = match button {
+            0 => Some(MouseButton::Left),
+            1 => Some(MouseButton::Middle),
+            2 => Some(MouseButton::Right),
+            EXTRA..=131 => {
+                Some(MouseButton::Extra(8 + (button - EXTRA) as u8))
+            }
+            _ => None,
+        };
+
+        if self.code & MOTION != 0 {
+            match pressed.or(*last_button) {
+                Some(button) => Some(MouseEvent::Hold(button)),
+                None if motion => Some(MouseEvent::Move),
+                None => None,
+            }
+        } else if self.release || pressed.is_none() {
+            let released = pressed.or(*last_button);
+            *last_button = None;
+            released.map(MouseEvent::Release)
+        } else {
+            *last_button = pressed;
+            pressed.map(MouseEvent::Press)
+        }
+    }
+
+    /// Returns the button code, without modifiers or motion.
+    fn button_code(&self) -> u16 {
+        self.code & !(SHIFT | META | CTRL | MOTION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(reports: &[&[u8]]) -> Vec<Option<MouseEvent>> {
+        let mut last_button = None;
+        reports
+            .iter()
+            .map(|bytes| {
+                let (report, len) = parse(bytes).unwrap();
+                assert_eq!(len, bytes.len());
+                report.event(&mut last_button, true)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sgr() {
+        assert_eq!(
+            events(&[
+                b"\x1B[<66;3;4M",
+                b"\x1B[<67;3;4M",
+                b"\x1B[<68;3;4M",
+                b"\x1B[<128;3;4M",
+                b"\x1B[<160;5;4M",
+                b"\x1B[<128;5;4m",
+                b"\x1B[<129;3;4M",
+                b"\x1B[<35;6;7M",
+            ]),
+            [
+                Some(MouseEvent::WheelLeft),
+                Some(MouseEvent::WheelRight),
+                Some(MouseEvent::WheelLeft),
+                Some(MouseEvent::Press(MouseButton::Extra(8))),
+                Some(MouseEvent::Hold(MouseButton::Extra(8))),
+                Some(MouseEvent::Release(MouseButton::Extra(8))),
+                Some(MouseEvent::Press(MouseButton::Extra(9))),
+                Some(MouseEvent::Hold(MouseButton::Extra(9))),
+            ]
+        );
+
+        let (report, _) = parse(b"\x1B[<0;3;4M").unwrap();
+        assert_eq!(report.position, Vec2::new(2, 3));
+        #[cfg(feature = "termion")]
+        assert!(!report.is_extended());
+        assert!(parse(b"\x1B[<0;3;4").is_none());
+    }
+
+    #[test]
+    fn x10() {
+        // Buttons are sent plus 32, and positions plus 33.
+        assert_eq!(
+            events(&[
+                b"\x1B[M\x62\x23\x24",
+                b"\x1B[M\xA1\x23\x24",
+                b"\x1B[M\x23\x23\x24"
+            ]),
+            [
+                Some(MouseEvent::WheelLeft),
+                Some(MouseEvent::Press(MouseButton::Extra(9))),
+                Some(MouseEvent::Release(MouseButton::Extra(9))),
+            ]
+        );
+
+        let (report, _) = parse(b"\x1B[M\x62\x23\x24").unwrap();
+        assert_eq!(report.position, Vec2::new(2, 3));
+        #[cfg(feature = "termion")]
+        assert!(report.is_extended());
+        assert!(parse(b"\x1B[M\x62\x23").is_none());
+    }
+}
@@ -73,6 +73,15 @@ impl Backend {
     pub fn input(&self) -> Sender<Option<Event>> {
         self.inner_sender.clone()
     }
+
+    /// Changes the screen size.
+    ///
+    /// The frame being drawn is discarded. This does not send
+    /// `Event::WindowResize`.
+    pub fn set_size(&self, size: Vec2) {
+        self.size.set(size);
+        self.current_frame.replace(ObservedScreen::new(size));
+    }
 }
 
 impl backend::Backend for Backend {
@@ -85,9 +94,9 @@ impl backend::Backend for Backend {
     }
 
     fn refresh(&mut self) {
-        let size = self.size.get();
-        let current_frame =
-            self.current_frame.replace(ObservedScreen::new(size));
+        // Like a terminal, the next frame starts with the current content:
+        // only changed cells are printed again.
+        let current_frame = self.current_frame.borrow().clone();
         self.prev_frame.replace(Some(current_frame.clone()));
         self.screen_channel.0.send(current_frame).unwrap();
     }
@@ -152,3 +161,43 @@ impl backend::Backend for Backend {
         self.current_style.replace(Rc::new(copied_style));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::Resizable;
+    use crate::utils::Counter;
+    use crate::views::ProgressBar;
+
+    #[test]
+    fn idle_frames_are_skipped() {
+        let backend = Backend::init(Some(Vec2::new(10, 1)));
+        let frames = backend.stream();
+        let input = backend.input();
+        // Drop the empty frame sent by `init`.
+        frames.try_iter().count();
+
+        let counter = Counter::new(0);
+        let mut siv = crate::Cursive::new().into_runner(backend);
+        siv.add_fullscreen_layer(
+            ProgressBar::new().with_value(counter.clone()).full_width(),
+        );
+
+        siv.refresh();
+        assert_eq!(frames.try_iter().count(), 1);
+
+        // Nothing changed since the last frame.
+        siv.refresh();
+        assert_eq!(frames.try_iter().count(), 0);
+
+        // The counter is updated without any event.
+        counter.set(50);
+        siv.refresh();
+        let frame = frames.try_iter().last().unwrap();
+        assert_eq!(frame.find_occurences("50 %").len(), 1);
+
+        input.send(Some(Event::Char('a'))).unwrap();
+        siv.step();
+        assert_eq!(frames.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,629 @@
+//! Record and replay sessions.
+//!
+//! A [`Recorder`] wraps any backend and writes every event it polls to a
+//! file, along with the screen size. A [`Replayer`] reads such a file and
+//! feeds it to `Cursive` through a [puppet backend], so a bug can be
+//! reproduced from a recorded session.
+//!
+//! Recordings are text files with one entry per line: the time in
+//! milliseconds since the recording started, followed by the entry.
+//!
+//! ```text
+//! # cursive recording
+//! 0 size 80 24
+//! 1042 key ctrl+s
+//! 1200 char 10
+//! 1530 mouse press:left 12 4
+//! 1610 mouse release:left 12 4
+//! 2200 paste hello\nworld
+//! 3000 key q
+//! ```
+//!
+//! [puppet backend]: crate::backends::puppet
+use crate::backend;
+use crate::backends::puppet;
+use crate::backends::puppet::observed::ObservedScreen;
+use crate::event::{Event, MouseButton, MouseEvent};
+use crate::theme;
+use crate::Vec2;
+use crossbeam_channel::{Receiver, Sender};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const HEADER: &str = "# cursive recording";
+
+/// Backend wrapper recording every event polled from another backend.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "ncurses-backend")] {
+/// use cursive::backends::{curses, record::Recorder};
+///
+/// let mut siv = cursive::Cursive::new();
+/// siv.run_with(|| {
+///     let backend = curses::n::Backend::init().unwrap();
+///     Recorder::to_file(backend, "session.rec").unwrap()
+/// });
+/// # }
+/// ```
+pub struct Recorder {
+    inner: Box<dyn backend::Backend>,
+    output: Box<dyn Write>,
+    start: Instant,
+    last_size: Vec2,
+}
+
+impl Recorder {
+    /// Records events from `inner` into `output`.
+    ///
+    /// Each entry is flushed right away, so the recording survives a crash.
+    pub fn new<W>(
+        inner: Box<dyn backend::Backend>,
+        output: W,
+    ) -> io::Result<Box<Self>>
+    where
+        W: Write + 'static,
+    {
+        let mut recorder = Recorder {
+            last_size: inner.screen_size(),
+            inner,
+            output: Box::new(output),
+            start: Instant::now(),
+        };
+        writeln!(recorder.output, "{}", HEADER)?;
+        let size = recorder.last_size;
+        recorder.write_entry(&format!("size {} {}", size.x, size.y))?;
+
+        Ok(Box::new(recorder))
+    }
+
+    /// Records events from `inner` into the given file.
+    pub fn to_file<P: AsRef<Path>>(
+        inner: Box<dyn backend::Backend>,
+        path: P,
+    ) -> io::Result<Box<Self>> {
+        let file = File::create(path)?;
+        Self::new(inner, BufWriter::new(file))
+    }
+
+    fn write_entry(&mut self, entry: &str) -> io::Result<()> {
+        let millis = self.start.elapsed().as_millis();
+        writeln!(self.output, "{} {}", millis, entry)?;
+        self.output.flush()
+    }
+
+    fn record(&mut self, event: Option<&Event>) -> io::Result<()> {
+        let size = self.inner.screen_size();
+        if size != self.last_size {
+            self.last_size = size;
+            self.write_entry(&format!("size {} {}", size.x, size.y))?;
+        }
+
+        match event.map(write_event) {
+            None => Ok(()),
+            Some(Some(entry)) => self.write_entry(&entry),
+            Some(None) => {
+                writeln!(self.output, "# unsupported: {:?}", event)?;
+                self.output.flush()
+            }
+        }
+    }
+}
+
+impl backend::Backend for Recorder {
+    fn poll_event(&mut self) -> Option<Event> {
+        let event = self.inner.poll_event();
+        if let Err(e) = self.record(event.as_ref()) {
+            log::warn!("Could not record event: {}", e);
+        }
+        event
+    }
+
+    fn refresh(&mut self) {
+        self.inner.refresh()
+    }
+
+    fn has_colors(&self) -> bool {
+        self.inner.has_colors()
+    }
+
+    fn screen_size(&self) -> Vec2 {
+        self.inner.screen_size()
+    }
+
+    fn print_at(&self, pos: Vec2, text: &str) {
+        self.inner.print_at(pos, text)
+    }
+
+    fn print_at_rep(&self, pos: Vec2, repetitions: usize, text: &str) {
+        self.inner.print_at_rep(pos, repetitions, text)
+    }
+
+    fn clear(&self, color: theme::Color) {
+        self.inner.clear(color)
+    }
+
+    fn set_color(&self, colors: theme::ColorPair) -> theme::ColorPair {
+        self.inner.set_color(colors)
+    }
+
+    fn set_effect(&self, effect: theme::Effect) {
+        self.inner.set_effect(effect)
+    }
+
+    fn unset_effect(&self, effect: theme::Effect) {
+        self.inner.unset_effect(effect)
+    }
+
+    fn set_mouse_motion(&mut self, enabled: bool) {
+        self.inner.set_mouse_motion(enabled)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// An entry from a recording.
+enum Entry {
+    Size(Vec2),
+    Event(Event),
+}
+
+/// Backend replaying a recorded session through a puppet backend.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use cursive::backends::record::Replayer;
+///
+/// let replayer = Replayer::from_file("session.rec").unwrap().realtime(false);
+/// let frames = replayer.stream();
+///
+/// let mut siv = cursive::Cursive::new();
+/// siv.run_with(|| Box::new(replayer));
+///
+/// let last_frame = frames.try_iter().last();
+/// ```
+pub struct Replayer {
+    puppet: Box<puppet::Backend>,
+    input: Sender<Option<Event>>,
+    entries: VecDeque<(Duration, Entry)>,
+    start: Option<Instant>,
+    realtime: bool,
+    exit_at_end: bool,
+}
+
+impl Replayer {
+    /// Reads a recording.
+    ///
+    /// Returns an error with `io::ErrorKind::InvalidData` if an entry cannot
+    /// be parsed.
+    pub fn new<R: BufRead>(recording: R) -> io::Result<Self> {
+        let mut entries = VecDeque::new();
+        for (i, line) in recording.lines().enumerate() {
+            let line = line?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_entry(&line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid entry on line {}: `{}`", i + 1, line),
+                )
+            })?;
+            entries.push_back(entry);
+        }
+
+        let size = entries.iter().find_map(|(_, entry)| match *entry {
+            Entry::Size(size) => Some(size),
+            _ => None,
+        });
+        let puppet = puppet::Backend::init(size);
+        let input = puppet.input();
+
+        Ok(Replayer {
+            puppet,
+            input,
+            entries,
+            start: None,
+            realtime: true,
+            exit_at_end: true,
+        })
+    }
+
+    /// Reads a recording from the given file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+
+    /// Sets whether events are replayed with their recorded timing.
+    ///
+    /// Otherwise, they are sent as fast as possible. Defaults to `true`.
+    pub fn set_realtime(&mut self, realtime: bool) {
+        self.realtime = realtime;
+    }
+
+    /// Sets whether events are replayed with their recorded timing.
+    ///
+    /// Chainable variant.
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.set_realtime(realtime);
+        self
+    }
+
+    /// Sets whether to stop the event loop when the recording ends.
+    ///
+    /// Defaults to `true`.
+    pub fn set_exit_at_end(&mut self, exit_at_end: bool) {
+        self.exit_at_end = exit_at_end;
+    }
+
+    /// Sets whether to stop the event loop when the recording ends.
+    ///
+    /// Chainable variant.
+    pub fn exit_at_end(mut self, exit_at_end: bool) -> Self {
+        self.set_exit_at_end(exit_at_end);
+        self
+    }
+
+    /// Returns `true` once every entry has been replayed.
+    pub fn is_done(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Output stream of the frames drawn during the replay.
+    pub fn stream(&self) -> Receiver<ObservedScreen> {
+        self.puppet.stream()
+    }
+
+    /// Sends the next entry to the puppet, if it is due.
+    ///
+    /// Returns `false` if no entry was sent.
+    fn replay_next(&mut self) -> bool {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        match self.entries.front() {
+            Some(&(time, _)) if self.realtime && start.elapsed() < time => {
+                return false;
+            }
+            None => return false,
+            _ => (),
+        }
+
+        match self.entries.pop_front() {
+            // The backend's own `WindowResize` is recorded after the size, so
+            // it is replayed with the other events.
+            Some((_, Entry::Size(size))) => self.puppet.set_size(size),
+            Some((_, Entry::Event(event))) => self.send(event),
+            None => (),
+        }
+        if self.entries.is_empty() && self.exit_at_end {
+            self.send(Event::Exit);
+        }
+        true
+    }
+
+    fn send(&self, event: Event) {
+        self.input.send(Some(event)).unwrap();
+    }
+}
+
+impl backend::Backend for Replayer {
+    fn poll_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.puppet.poll_event() {
+                return Some(event);
+            }
+            if !self.replay_next() {
+                return None;
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.puppet.refresh()
+    }
+
+    fn has_colors(&self) -> bool {
+        self.puppet.has_colors()
+    }
+
+    fn screen_size(&self) -> Vec2 {
+        self.puppet.screen_size()
+    }
+
+    fn print_at(&self, pos: Vec2, text: &str) {
+        self.puppet.print_at(pos, text)
+    }
+
+    fn print_at_rep(&self, pos: Vec2, repetitions: usize, text: &str) {
+        self.puppet.print_at_rep(pos, repetitions, text)
+    }
+
+    fn clear(&self, color: theme::Color) {
+        self.puppet.clear(color)
+    }
+
+    fn set_color(&self, colors: theme::ColorPair) -> theme::ColorPair {
+        self.puppet.set_color(colors)
+    }
+
+    fn set_effect(&self, effect: theme::Effect) {
+        self.puppet.set_effect(effect)
+    }
+
+    fn unset_effect(&self, effect: theme::Effect) {
+        self.puppet.unset_effect(effect)
+    }
+
+    fn name(&self) -> &str {
+        "replay"
+    }
+}
+
+/// Returns the recorded form of `event`, if it can be recorded.
+fn write_event(event: &Event) -> Option<String> {
+    Some(match *event {
+        Event::WindowResize => "resize".to_string(),
+        Event::Refresh => "refresh".to_string(),
+        Event::FocusGained => "focus-in".to_string(),
+        Event::FocusLost => "focus-out".to_string(),
+        Event::Exit => "exit".to_string(),
+        // Control characters would break the line.
+        Event::Char(c) if c.is_control() => format!("char {}", c as u32),
+        Event::KeyRelease(combo) => format!("release {}", combo),
+        Event::Paste(ref text) => format!("paste {}", escape(text)),
+        Event::Unknown(ref bytes) => {
+            let bytes: Vec<String> =
+                bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("unknown {}", bytes.join(" "))
+        }
+        Event::Mouse {
+            event, position, ..
+        } => format!(
+            "mouse {} {} {}",
+            write_mouse_event(event)?,
+            position.x,
+            position.y
+        ),
+        ref event => format!("key {}", event.key_combo()?),
+    })
+}
+
+fn write_mouse_event(event: MouseEvent) -> Option<String> {
+    Some(match event {
+        MouseEvent::Press(button) => {
+            format!("press:{}", write_button(button)?)
+        }
+        MouseEvent::Release(button) => {
+            format!("release:{}", write_button(button)?)
+        }
+        MouseEvent::Hold(button) => format!("hold:{}", write_button(button)?),
+        MouseEvent::WheelUp => "wheel-up".to_string(),
+        MouseEvent::WheelDown => "wheel-down".to_string(),
+        MouseEvent::WheelLeft => "wheel-left".to_string(),
+        MouseEvent::WheelRight => "wheel-right".to_string(),
+        MouseEvent::Move => "move".to_string(),
+        // Gestures are built from the other events, not sent by backends.
+        _ => return None,
+    })
+}
+
+fn write_button(button: MouseButton) -> Option<String> {
+    Some(match button {
+        MouseButton::Left => "left".to_string(),
+        MouseButton::Middle => "middle".to_string(),
+        MouseButton::Right => "right".to_string(),
+        MouseButton::Button4 => "4".to_string(),
+        MouseButton::Button5 => "5".to_string(),
+        MouseButton::Extra(n) => n.to_string(),
+        MouseButton::Other => return None,
+    })
+}
+
+fn parse_entry(line: &str) -> Option<(Duration, Entry)> {
+    let mut parts = line.splitn(3, ' ');
+    let millis = parts.next()?.parse().ok()?;
+    let kind = parts.next()?;
+    let args = parts.next().unwrap_or("");
+
+    let entry = match kind {
+        "size" => Entry::Size(parse_position(args)?),
+        kind => Entry::Event(parse_event(kind, args)?),
+    };
+    Some((Duration::from_millis(millis), entry))
+}
+
+fn parse_event(kind: &str, args: &str) -> Option<Event> {
+    Some(match kind {
+        "resize" => Event::WindowResize,
+        "refresh" => Event::Refresh,
+        "focus-in" => Event::FocusGained,
+        "focus-out" => Event::FocusLost,
+        "exit" => Event::Exit,
+        "char" => Event::Char(std::char::from_u32(args.parse().ok()?)?),
+        "paste" => Event::Paste(unescape(args)?),
+        "unknown" => Event::Unknown(
+            args.split_whitespace()
+                .map(|b| u8::from_str_radix(b, 16).ok())
+                .collect::<Option<_>>()?,
+        ),
+        "mouse" => {
+            let mut parts = args.splitn(2, ' ');
+            let event = parse_mouse_event(parts.next()?)?;
+            Event::Mouse {
+                event,
+                position: parse_position(parts.next()?)?,
+                offset: Vec2::zero(),
+            }
+        }
+        "key" => args.parse().ok()?,
+        "release" => Event::KeyRelease(args.parse().ok()?),
+        _ => return None,
+    })
+}
+
+fn parse_mouse_event(s: &str) -> Option<MouseEvent> {
+    Some(match s {
+        "wheel-up" => MouseEvent::WheelUp,
+        "wheel-down" => MouseEvent::WheelDown,
+        "wheel-left" => MouseEvent::WheelLeft,
+        "wheel-right" => MouseEvent::WheelRight,
+        "move" => MouseEvent::Move,
+        s => {
+            let mut parts = s.splitn(2, ':');
+            let kind = parts.next()?;
+            let button = parse_button(parts.next()?)?;
+            match kind {
+                "press" => MouseEvent::Press(button),
+                "release" => MouseEvent::Release(button),
+                "hold" => MouseEvent::Hold(button),
+                _ => return None,
+            }
+        }
+    })
+}
+
+fn parse_button(s: &str) -> Option<MouseButton> {
+    Some(match s {
+        "left" => MouseButton::Left,
+        "middle" => MouseButton::Middle,
+        "right" => MouseButton::Right,
+        "4" => MouseButton::Button4,
+        "5" => MouseButton::Button5,
+        s => MouseButton::Extra(s.parse().ok()?),
+    })
+}
+
+fn parse_position(s: &str) -> Option<Vec2> {
+    let mut parts = s.split(' ');
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(Vec2::new(x, y))
+}
+
+/// Escapes backslashes and line breaks, to keep `text` on one line.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        result.push(match chars.next()? {
+            '\\' => '\\',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend as _;
+    use crate::event::Key;
+
+    #[test]
+    fn events_round_trip() {
+        let events = vec![
+            Event::Char('q'),
+            Event::Char(' '),
+            Event::Char('\n'),
+            Event::CtrlChar('s'),
+            Event::AltShift(Key::Left),
+            Event::Key(Key::F5),
+            Event::Paste("a \\ b\nc".to_string()),
+            Event::Unknown(vec![0x1b, 0x5b]),
+            Event::Mouse {
+                event: MouseEvent::Press(MouseButton::Extra(8)),
+                position: Vec2::new(3, 4),
+                offset: Vec2::zero(),
+            },
+            Event::Mouse {
+                event: MouseEvent::WheelLeft,
+                position: Vec2::new(0, 1),
+                offset: Vec2::zero(),
+            },
+            Event::FocusLost,
+            Event::KeyRelease("ctrl+a".parse().unwrap()),
+            Event::WindowResize,
+        ];
+
+        for event in events {
+            let line = format!("12 {}", write_event(&event).unwrap());
+            match parse_entry(&line) {
+                Some((time, Entry::Event(parsed))) => {
+                    assert_eq!(time, Duration::from_millis(12));
+                    assert_eq!(parsed, event, "{}", line);
+                }
+                _ => panic!("could not parse `{}`", line),
+            }
+        }
+    }
+
+    const RECORDING: &str = "# cursive recording
+0 size 20 4
+10 key h
+20 key i
+30 size 24 5
+30 resize
+";
+
+    #[test]
+    fn resizes_are_replayed_once() {
+        let mut replayer =
+            Replayer::new(RECORDING.as_bytes()).unwrap().realtime(false);
+        let events: Vec<Event> =
+            std::iter::from_fn(|| replayer.poll_event()).collect();
+        assert_eq!(
+            events,
+            [
+                Event::Char('h'),
+                Event::Char('i'),
+                Event::WindowResize,
+                Event::Exit
+            ]
+        );
+        assert_eq!(replayer.screen_size(), Vec2::new(24, 5));
+    }
+
+    #[test]
+    fn replay_through_cursive() {
+        use crate::views::TextArea;
+
+        let replayer =
+            Replayer::new(RECORDING.as_bytes()).unwrap().realtime(false);
+        let frames = replayer.stream();
+        let mut siv = crate::Cursive::new().into_runner(Box::new(replayer));
+        siv.add_fullscreen_layer(TextArea::new());
+        siv.run();
+
+        let frame = frames.try_iter().last().unwrap();
+        assert_eq!(frame.size(), Vec2::new(24, 5));
+        assert_eq!(frame.find_occurences("hi").len(), 1);
+    }
+}
@@ -3,29 +3,52 @@
 //! Requires the `termion-backend` feature.
 #![cfg(feature = "termion")]
 
-use crossbeam_channel::{self, select, Receiver};
+use crossbeam_channel::{self, select, Receiver, Sender};
 use termion::color as tcolor;
 use termion::event::Event as TEvent;
 use termion::event::Key as TKey;
 use termion::event::MouseButton as TMouseButton;
 use termion::event::MouseEvent as TMouseEvent;
-use termion::input::{MouseTerminal, TermRead};
+use termion::input::MouseTerminal;
 use termion::raw::{IntoRawMode, RawTerminal};
 use termion::screen::AlternateScreen;
 use termion::style as tstyle;
 
 use crate::backend;
 use crate::backends;
-use crate::event::{Event, Key, MouseButton, MouseEvent};
+use crate::backends::kitty;
+use crate::backends::mouse;
+use crate::event::{Event, Key, KeyCombo, Modifiers, MouseButton, MouseEvent};
 use crate::theme;
 use crate::Vec2;
 
 use std::cell::{Cell, RefCell};
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{BufWriter, Read, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
+
+const PASTE_ENABLE: &str = "\x1B[?2004h";
+const PASTE_DISABLE: &str = "\x1B[?2004l";
+
+// Termion doesn't know the bracketed paste markers, and reports them as
+// unsupported events.
+const PASTE_START: &[u8] = b"\x1B[200~";
+const PASTE_END: &[u8] = b"\x1B[201~";
+
+const FOCUS_ENABLE: &str = "\x1B[?1004h";
+const FOCUS_DISABLE: &str = "\x1B[?1004l";
+
+// Same for focus changes.
+const FOCUS_IN: &[u8] = b"\x1B[I";
+const FOCUS_OUT: &[u8] = b"\x1B[O";
+
+// `MouseTerminal` only reports drags; this also reports motion without any
+// button pressed. Disabling it stops drag reports, so they are enabled again.
+const MOTION_ENABLE: &str = "\x1B[?1003h";
+const MOTION_DISABLE: &str = "\x1B[?1003l\x1B[?1002h";
 
 /// Backend using termion
 pub struct Backend {
@@ -36,6 +59,12 @@ pub struct Backend {
 
     // Inner state required to parse input
     last_button: Option<MouseButton>,
+    mouse_motion: bool,
+
+    // Kitty keyboard flags to push once the terminal says it supports the
+    // protocol, and whether they were pushed.
+    kitty_flags: Option<u8>,
+    kitty_enabled: bool,
 
     input_receiver: Receiver<TEvent>,
     resize_receiver: Receiver<()>,
@@ -62,10 +91,35 @@ impl Backend {
         )
     }
 
+    /// Creates a new termion-based backend using the kitty keyboard protocol.
+    ///
+    /// Uses `/dev/tty` for input and output. `flags` is a combination of
+    /// [`kitty::DISAMBIGUATE`] and [`kitty::REPORT_EVENT_TYPES`].
+    ///
+    /// If the terminal doesn't support the protocol, this is the same as
+    /// [`Backend::init`].
+    pub fn init_with_kitty_keyboard(
+        flags: u8,
+    ) -> std::io::Result<Box<dyn backend::Backend>> {
+        Self::init_with_options(
+            File::open("/dev/tty")?,
+            File::create("/dev/tty")?,
+            Some(flags),
+        )
+    }
+
     /// Creates a new termion-based backend using the given input and output files.
     pub fn init_with_files(
         input_file: File,
         output_file: File,
+    ) -> std::io::Result<Box<dyn backend::Backend>> {
+        Self::init_with_options(input_file, output_file, None)
+    }
+
+    fn init_with_options(
+        input_file: File,
+        output_file: File,
+        kitty_flags: Option<u8>,
     ) -> std::io::Result<Box<dyn backend::Backend>> {
         // Use a ~8MB buffer
         // Should be enough for a single screen most of the time.
@@ -77,6 +131,18 @@ impl Backend {
 
         write!(terminal.borrow_mut(), "{}", termion::cursor::Hide)?;
 
+        // Pasted text will be surrounded by markers.
+        write!(terminal.borrow_mut(), "{}", PASTE_ENABLE)?;
+
+        // Report when the terminal gains or loses focus.
+        write!(terminal.borrow_mut(), "{}", FOCUS_ENABLE)?;
+
+        // Flags are only pushed once the terminal answers.
+        if kitty_flags.is_some() {
+            write!(terminal.borrow_mut(), "{}", kitty::QUERY)?;
+            terminal.borrow_mut().flush()?;
+        }
+
         let (input_sender, input_receiver) = crossbeam_channel::unbounded();
         let (resize_sender, resize_receiver) = crossbeam_channel::bounded(0);
 
@@ -91,17 +157,7 @@ impl Backend {
         // We want nonblocking input, but termion is blocking by default
         // Read input from a separate thread
         thread::spawn(move || {
-            let mut events = input_file.events();
-
-            // Take all the events we can
-            while let Some(Ok(event)) = events.next() {
-                // If we can't send, it means the receiving side closed,
-                // so just stop.
-                if input_sender.send(event).is_err() {
-                    break;
-                }
-            }
-
+            read_events(input_file, input_sender, kitty_flags.is_some());
             running.store(false, Ordering::Relaxed);
         });
 
@@ -110,6 +166,9 @@ impl Backend {
             current_style: Cell::new(theme::ColorPair::from_256colors(0, 0)),
 
             last_button: None,
+            mouse_motion: false,
+            kitty_flags,
+            kitty_enabled: false,
             input_receiver,
             resize_receiver,
         };
@@ -124,7 +183,22 @@ impl Backend {
 
     fn map_key(&mut self, event: TEvent) -> Event {
         match event {
-            TEvent::Unsupported(bytes) => Event::Unknown(bytes),
+            TEvent::Unsupported(ref bytes) if bytes == FOCUS_IN => {
+                Event::FocusGained
+            }
+            TEvent::Unsupported(ref bytes) if bytes == FOCUS_OUT => {
+                Event::FocusLost
+            }
+            TEvent::Unsupported(bytes) => match mouse::parse(&bytes) {
+                Some((report, _)) => report
+                    .event(&mut self.last_button, self.mouse_motion)
+                    .map_or(Event::Unknown(vec![]), |event| Event::Mouse {
+                        event,
+                        position: report.position,
+                        offset: Vec2::zero(),
+                    }),
+                None => Event::Unknown(bytes),
+            },
             TEvent::Key(TKey::Esc) => Event::Key(Key::Esc),
             TEvent::Key(TKey::Backspace) => Event::Key(Key::Backspace),
             TEvent::Key(TKey::Left) => Event::Key(Key::Left),
@@ -142,7 +216,9 @@ impl Backend {
             TEvent::Key(TKey::Char('\n')) => Event::Key(Key::Enter),
             TEvent::Key(TKey::Char('\t')) => Event::Key(Key::Tab),
             TEvent::Key(TKey::Char(c)) => Event::Char(c),
-            TEvent::Key(TKey::Ctrl(c)) => Event::CtrlChar(c),
+            TEvent::Key(TKey::Ctrl(c)) => {
+                KeyCombo::new(c, Modifiers::CTRL).into()
+            }
             TEvent::Key(TKey::Alt(c)) => Event::AltChar(c),
             TEvent::Mouse(TMouseEvent::Press(btn, x, y)) => {
                 let position = (x - 1, y - 1).into();
@@ -172,7 +248,8 @@ impl Backend {
             TEvent::Mouse(TMouseEvent::Release(x, y))
                 if self.last_button.is_some() =>
             {
-                let event = MouseEvent::Release(self.last_button.unwrap());
+                let event =
+                    MouseEvent::Release(self.last_button.take().unwrap());
                 let position = (x - 1, y - 1).into();
                 Event::Mouse {
                     event,
@@ -191,10 +268,62 @@ impl Backend {
                     offset: Vec2::zero(),
                 }
             }
+            TEvent::Mouse(TMouseEvent::Hold(x, y)) if self.mouse_motion => {
+                // Termion reports motion without any button as a hold.
+                let position = (x - 1, y - 1).into();
+                Event::Mouse {
+                    event: MouseEvent::Move,
+                    position,
+                    offset: Vec2::zero(),
+                }
+            }
             _ => Event::Unknown(vec![]),
         }
     }
 
+    /// Handles a sequence from the kitty keyboard protocol.
+    ///
+    /// Returns `None` for answers to the initial query.
+    fn map_kitty(&mut self, bytes: Vec<u8>) -> Option<Event> {
+        match kitty::parse(&bytes).0 {
+            kitty::Parsed::Key(event) => Some(event),
+            kitty::Parsed::Flags(_) => {
+                if let (Some(flags), false) =
+                    (self.kitty_flags, self.kitty_enabled)
+                {
+                    self.write(kitty::push_flags(flags));
+                    self.kitty_enabled = true;
+                }
+                None
+            }
+            kitty::Parsed::DeviceAttributes => {
+                if !self.kitty_enabled {
+                    // Legacy sequences keep working.
+                    log::info!("No support for the kitty keyboard protocol");
+                }
+                None
+            }
+            _ => Some(self.map_key(TEvent::Unsupported(bytes))),
+        }
+    }
+
+    /// Reads pasted text until the end of the bracketed paste.
+    fn read_paste(&mut self) -> Event {
+        let mut text = String::new();
+
+        // The rest of the paste may not be there yet, so wait a bit for it.
+        let timeout = Duration::from_millis(100);
+        while let Ok(event) = self.input_receiver.recv_timeout(timeout) {
+            match event {
+                TEvent::Unsupported(ref bytes) if bytes == PASTE_END => break,
+                TEvent::Key(TKey::Char(c)) => text.push(c),
+                _ => (),
+            }
+        }
+
+        Event::Paste(text)
+    }
+
     fn write<T>(&self, content: T)
     where
         T: std::fmt::Display,
@@ -205,9 +334,15 @@ impl Backend {
 
 impl Drop for Backend {
     fn drop(&mut self) {
+        if self.kitty_enabled {
+            write!(self.terminal.get_mut(), "{}", kitty::POP_FLAGS).unwrap();
+        }
+
         write!(
             self.terminal.get_mut(),
-            "{}{}",
+            "{}{}{}{}",
+            FOCUS_DISABLE,
+            PASTE_DISABLE,
             termion::cursor::Show,
             termion::cursor::Goto(1, 1)
         )
@@ -229,6 +364,16 @@ impl backend::Backend for Backend {
         "termion"
     }
 
+    fn set_mouse_motion(&mut self, enabled: bool) {
+        self.mouse_motion = enabled;
+        let sequence = if enabled {
+            MOTION_ENABLE
+        } else {
+            MOTION_DISABLE
+        };
+        write!(self.terminal.get_mut(), "{}", sequence).unwrap();
+    }
+
     fn set_color(&self, color: theme::ColorPair) -> theme::ColorPair {
         let current_style = self.current_style.get();
 
@@ -322,7 +467,114 @@ impl backend::Backend for Backend {
             recv(self.resize_receiver) -> _ => return Some(Event::WindowResize),
             default => return None,
         };
-        event.map(|event| self.map_key(event))
+        event.and_then(|event| match event {
+            TEvent::Unsupported(ref bytes) if bytes == PASTE_START => {
+                Some(self.read_paste())
+            }
+            TEvent::Unsupported(bytes) if self.kitty_flags.is_some() => {
+                self.map_kitty(bytes)
+            }
+            event => Some(self.map_key(event)),
+        })
+    }
+}
+
+/// Reads events, keeping sequences termion doesn't know whole.
+///
+/// Termion stops parsing a sequence at the first byte it doesn't expect,
+/// and the rest would come out as characters. If `kitty` is set, sequences
+/// from the kitty protocol are sent as unsupported events instead, for
+/// `map_kitty`. So are the mouse reports termion gets wrong, for `map_key`.
+fn read_events(
+    mut input_file: File,
+    input_sender: Sender<TEvent>,
+    kitty: bool,
+) {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 1024];
+
+    loop {
+        match input_file.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+
+        let mut start = 0;
+        while start < buffer.len() {
+            let bytes = &buffer[start..];
+            // This also tells when a sequence is incomplete.
+            let parsed = match kitty::parse(bytes) {
+                // A lone escape at the end of a read is the Escape key.
+                (kitty::Parsed::Incomplete, _) if bytes == b"\x1B" => {
+                    Some((TEvent::Key(TKey::Esc), 1))
+                }
+                (kitty::Parsed::Incomplete, _) => None,
+                (kitty::Parsed::Unknown, _) => parse_legacy(bytes),
+                (_, len) if kitty => {
+                    Some((TEvent::Unsupported(bytes[..len].to_vec()), len))
+                }
+                _ => parse_legacy(bytes),
+            };
+            let (event, len) = match parsed {
+                Some(parsed) => parsed,
+                None => break,
+            };
+            start += len;
+
+            // If we can't send, it means the receiving side closed.
+            if input_sender.send(event).is_err() {
+                return;
+            }
+        }
+        buffer.drain(..start);
+    }
+}
+
+/// Parses a legacy sequence with termion.
+///
+/// Returns the event and the number of bytes used, or `None` if more bytes
+/// are needed.
+fn parse_legacy(bytes: &[u8]) -> Option<(TEvent, usize)> {
+    // Termion panics if a sequence is cut short, so wait for the bytes it
+    // expects after these prefixes.
+    let needed = match bytes {
+        [0x1B, b'[', b'M', ..] => 6,
+        [0x1B, b'[', b'[', ..] | [0x1B, b'O', ..] => 4,
+        [0x1B, b'[', ..] => bytes.len(),
+        [0x1B, c, ..] => 1 + utf8_len(*c),
+        [c, ..] => utf8_len(*c),
+        [] => return None,
+    };
+    if bytes.len() < needed {
+        return None;
+    }
+
+    // Termion 1 reads the horizontal wheel and the extra buttons as other
+    // buttons, or fails to parse them.
+    if let Some((report, len)) = mouse::parse(bytes) {
+        if report.is_extended() {
+            return Some((TEvent::Unsupported(bytes[..len].to_vec()), len));
+        }
+    }
+
+    let mut rest = bytes[1..].iter();
+    let event = {
+        let mut iter = (&mut rest).map(|&b| Ok(b));
+        termion::event::parse_event(bytes[0], &mut iter)
+    };
+    let len = bytes.len() - rest.len();
+    let event =
+        event.unwrap_or_else(|_| TEvent::Unsupported(bytes[..len].to_vec()));
+    Some((event, len))
+}
+
+/// Returns the length of the UTF-8 character starting with `byte`.
+fn utf8_len(byte: u8) -> usize {
+    match byte {
+        0xF0..=0xFF => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
     }
 }
 
@@ -49,7 +49,7 @@ pub trait CursiveExt {
 
     /// Creates a new Cursive root using a crossterm backend.
     #[cfg(feature = "crossterm-backend")]
-    fn run_crossterm(&mut self) -> Result<(), crossterm::ErrorKind>;
+    fn run_crossterm(&mut self) -> std::io::Result<()>;
 
     /// Creates a new Cursive root using a bear-lib-terminal backend.
     #[cfg(feature = "blt-backend")]
@@ -92,7 +92,7 @@ impl CursiveExt for cursive_core::Cursive {
     }
 
     #[cfg(feature = "crossterm-backend")]
-    fn run_crossterm(&mut self) -> Result<(), crossterm::ErrorKind> {
+    fn run_crossterm(&mut self) -> std::io::Result<()> {
         self.try_run_with(crate::backends::crossterm::Backend::init)
     }
 
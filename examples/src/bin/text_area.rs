@@ -58,7 +58,8 @@ fn find(siv: &mut Cursive, text: &str) {
     let res = siv.call_on_name("text", |v: &mut TextArea| {
         // Find the given text from the text area content
         // Possible improvement: search after the current cursor.
-        if let Some(i) = v.get_content().find(text) {
+        let found = v.get_content().find(text);
+        if let Some(i) = found {
             // If we found it, move the cursor
             v.set_cursor(i);
             Ok(())